@@ -6,10 +6,69 @@ fn main() {
         "block_engine.proto",
     ];
 
+    let mut config = prost_build::Config::new();
+    // Token values are secrets; `proto::auth` provides a redacting `Debug` instead.
+    config.skip_debug([".auth.Token"]);
+
+    if std::env::var_os("CARGO_FEATURE_SERDE").is_some() {
+        add_serde_attributes(&mut config);
+    }
+
     tonic_build::configure()
         // The `optional` keyword in the message requires compiling the .proto file with
         // the `--experimental_allow_proto3_optional` flag (see https://github.com/hyperium/tonic/issues/627)
         .protoc_arg("--experimental_allow_proto3_optional")
-        .compile(&protbuf_files, &["grpc/proto"])
+        .compile_with_config(config, &protbuf_files, &["grpc/proto"])
         .expect("Failed to compile protobuf files");
 }
+
+// Payloads are base64, hashes and shards hex, timestamps RFC 3339. The helpers live in
+// `src/proto_serde.rs`.
+fn add_serde_attributes(config: &mut prost_build::Config) {
+    config.message_attribute(
+        ".",
+        "#[derive(serde::Serialize, serde::Deserialize)] #[serde(default)]",
+    );
+    config.enum_attribute(
+        ".",
+        "#[derive(serde::Serialize, serde::Deserialize)] #[serde(rename_all = \"snake_case\")]",
+    );
+
+    let fields = [
+        ("base64", ".auth.GenerateAuthChallengeRequest.pubkey"),
+        ("base64", ".auth.GenerateAuthChallengeResponse.challenge"),
+        ("base64", ".auth.GenerateAuthTokensRequest.challenge"),
+        ("base64", ".auth.GenerateAuthTokensRequest.signed_challenge"),
+        ("base64", ".dto.ExternalMessage.data"),
+        ("base64", ".dto.MempoolExternalMessage.data"),
+        ("hex", ".dto.MempoolExternalMessage.hash"),
+        ("hex", ".dto.MempoolExternalMessage.shard"),
+        ("hex", ".searcher.WorkchainShardSubscriptionV0.shard"),
+        (
+            "option_hex",
+            ".searcher.ExternalOutMessageBodyOpcodeSubscriptionV0.shard",
+        ),
+        (
+            "option_hex",
+            ".searcher.InternalMessageBodyOpcodeSubscriptionV0.shard",
+        ),
+        ("timestamp", ".auth.Token.expires_at_utc"),
+        ("timestamp", ".dto.MempoolPacket.server_ts"),
+        ("timestamp", ".dto.MempoolPacket.expiration_ns"),
+        ("timestamp", ".dto.Bundle.expiration_ns"),
+    ];
+    for (helper, field) in fields {
+        config.field_attribute(
+            field,
+            format!("#[serde(with = \"crate::proto_serde::{helper}\")]"),
+        );
+    }
+
+    // Like `Debug`, serialized tokens never carry their value, so they come back empty. It
+    // still deserializes from input that has it.
+    config.field_attribute(".auth.Token.value", "#[serde(skip_serializing)]");
+    config.type_attribute(
+        ".auth.Token",
+        "/// The value is never serialized, so serialized tokens deserialize with an empty value.",
+    );
+}
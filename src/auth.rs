@@ -1,96 +1,248 @@
-use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
-use tonic::transport::{Certificate, Channel, ClientTlsConfig};
+use std::fmt;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+use base64::Engine;
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use tonic::transport::Channel;
 
 use crate::error::SovaError;
+use crate::interceptor::TokenSource;
 use crate::proto::auth::auth_service_client::AuthServiceClient;
-use crate::proto::auth::Token;
 use crate::proto::auth::{
     GenerateAuthChallengeRequest, GenerateAuthTokensRequest, RefreshAccessTokenRequest,
 };
+use crate::signer::ChallengeSigner;
+use crate::telemetry::{self, RpcTimer};
+use crate::tls::{self, TlsMode};
+use crate::types::AccessToken;
 
-pub struct NewKeyPair {
-    private_key: SigningKey,
-    public_key: VerifyingKey,
-}
-
+/// Clones share the identity and tokens, so a refresh through one clone is seen by all of them
+/// and by interceptors using [`token_source`](Self::token_source).
+#[derive(Clone)]
 pub struct SovaAuth {
     auth_client: AuthServiceClient<Channel>,
-    key: NewKeyPair,
-    access_token: Option<Token>,
-    refresh_token: Option<Token>,
+    signer: Arc<dyn ChallengeSigner>,
+    public_key: Arc<RwLock<Option<VerifyingKey>>>,
+    access_token: TokenSource,
+    refresh_token: TokenSource,
+}
+
+/// Snapshot of the identity and token lifetimes held by [`SovaAuth`].
+#[derive(Clone, Debug)]
+pub struct AuthInfo {
+    pub public_key: Option<AuthPublicKey>,
+    pub access_token: Option<TokenInfo>,
+    pub refresh_token: Option<TokenInfo>,
+}
+
+impl AuthInfo {
+    /// Whether the access token is missing or expires within `margin`.
+    pub fn needs_refresh(&self, margin: Duration) -> bool {
+        self.access_token
+            .as_ref()
+            .is_none_or(|token| token.expires_within(margin))
+    }
+
+    /// Whether the refresh token can still be used to obtain a new access token.
+    pub fn can_refresh(&self) -> bool {
+        self.refresh_token
+            .as_ref()
+            .is_some_and(|token| !token.is_expired())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct AuthPublicKey(VerifyingKey);
+
+impl AuthPublicKey {
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_bytes()
+    }
+
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.0.as_bytes())
+    }
+
+    pub fn to_base64(&self) -> String {
+        base64::engine::general_purpose::STANDARD.encode(self.0.as_bytes())
+    }
+
+    pub fn verifying_key(&self) -> &VerifyingKey {
+        &self.0
+    }
+}
+
+impl From<VerifyingKey> for AuthPublicKey {
+    fn from(key: VerifyingKey) -> Self {
+        Self(key)
+    }
+}
+
+impl fmt::Debug for AuthPublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("AuthPublicKey")
+            .field(&self.to_hex())
+            .finish()
+    }
+}
+
+impl fmt::Display for AuthPublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+/// Lifetime of a token, without its value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TokenInfo {
+    expires_at: Option<SystemTime>,
+}
+
+impl TokenInfo {
+    /// `None` when the service did not report an expiry.
+    pub fn expires_at(&self) -> Option<SystemTime> {
+        self.expires_at
+    }
+
+    /// Time left until expiry, zero once expired. `None` when the expiry is unknown.
+    pub fn time_remaining(&self) -> Option<Duration> {
+        self.expires_at.map(|expires_at| {
+            expires_at
+                .duration_since(SystemTime::now())
+                .unwrap_or(Duration::ZERO)
+        })
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.time_remaining() == Some(Duration::ZERO)
+    }
+
+    /// Tokens without a reported expiry are treated as never expiring.
+    pub fn expires_within(&self, margin: Duration) -> bool {
+        self.time_remaining()
+            .is_some_and(|remaining| remaining <= margin)
+    }
+}
+
+impl From<&AccessToken> for TokenInfo {
+    fn from(token: &AccessToken) -> Self {
+        Self {
+            expires_at: token.expires_at(),
+        }
+    }
 }
 
 impl SovaAuth {
     pub async fn new(
-        url: &'static str,
-        ca_pem: Option<&str>,
-        domain_name: Option<&str>,
+        url: &str,
+        tls: TlsMode,
         private_key: &[u8; 32],
     ) -> Result<Self, Box<dyn std::error::Error>> {
-        let auth_client = if let (Some(ca_pem), Some(domain_name)) = (ca_pem, domain_name) {
-            let ca = Certificate::from_pem(ca_pem);
-
-            let tls = ClientTlsConfig::new()
-                .ca_certificate(ca)
-                .domain_name(domain_name);
-
-            let channel = Channel::from_static(url).tls_config(tls)?.connect().await?;
-
-            AuthServiceClient::new(channel)
-        } else {
-            AuthServiceClient::connect(url).await?
-        };
+        Self::with_signer(url, tls, SigningKey::from_bytes(private_key)).await
+    }
 
-        let private_key = SigningKey::from_bytes(private_key);
-        let public_key = VerifyingKey::from(&private_key);
-        let key = NewKeyPair {
-            private_key,
-            public_key,
-        };
+    pub async fn with_signer(
+        url: &str,
+        tls: TlsMode,
+        signer: impl ChallengeSigner + 'static,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let auth_client = AuthServiceClient::new(tls::connect(url, &tls).await?);
 
         Ok(Self {
             auth_client,
-            key,
-            access_token: None,
-            refresh_token: None,
+            signer: Arc::new(signer),
+            public_key: Arc::default(),
+            access_token: TokenSource::default(),
+            refresh_token: TokenSource::default(),
         })
     }
 
-    pub async fn authenticate(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let bytes_public_key: &[u8] = &self.key.public_key.to_bytes();
+    /// Keeps the access token in `tokens`, e.g. the source shared with a client's searchers.
+    pub(crate) fn with_token_source(mut self, tokens: TokenSource) -> Self {
+        self.access_token = tokens;
+        self
+    }
+
+    /// The key this client authenticates with, e.g. for
+    /// [`SovaSearcher::with_bundle_signer`](crate::searcher::SovaSearcher::with_bundle_signer).
+    pub fn signer(&self) -> Arc<dyn ChallengeSigner> {
+        self.signer.clone()
+    }
+
+    #[tracing::instrument(skip_all, fields(public_key = tracing::field::Empty))]
+    pub async fn authenticate(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let result = self.request_tokens().await;
+        telemetry::token_refreshed("authenticate", result.is_ok());
+        if let Err(error) = &result {
+            telemetry::log_error("authentication", error.as_ref());
+        }
+        result
+    }
+
+    async fn request_tokens(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let public_key = self.signer.public_key().await.map_err(SovaError::Signer)?;
+        let public_key_hex = AuthPublicKey(public_key).to_hex();
+        tracing::Span::current().record("public_key", public_key_hex.as_str());
 
         let request = tonic::Request::new(GenerateAuthChallengeRequest {
-            pubkey: Vec::from(bytes_public_key),
+            pubkey: public_key.to_bytes().to_vec(),
         });
-        let response = self.auth_client.generate_auth_challenge(request).await?;
+        let mut auth_client = self.auth_client.clone();
+        let timer = RpcTimer::start("generate_auth_challenge");
+        let response = auth_client.generate_auth_challenge(request).await;
+        timer.finish(response.is_ok());
+        let response = response?;
 
         let challenge = response.into_inner().challenge;
-        let signed_challenge: Signature = self.key.private_key.sign(&challenge);
+        let signed_challenge = self
+            .signer
+            .sign_challenge(&challenge)
+            .await
+            .map_err(SovaError::Signer)?;
 
         let token_request = tonic::Request::new(GenerateAuthTokensRequest {
             challenge,
             signed_challenge: signed_challenge.to_vec(),
         });
 
-        let token_response = self
-            .auth_client
-            .generate_auth_tokens(token_request)
-            .await?
-            .into_inner();
-        self.access_token = token_response.access_token;
-        self.refresh_token = token_response.refresh_token;
+        let timer = RpcTimer::start("generate_auth_tokens");
+        let token_response = auth_client.generate_auth_tokens(token_request).await;
+        timer.finish(token_response.is_ok());
+        let token_response = token_response?.into_inner();
+        *self.public_key.write().unwrap() = Some(public_key);
+        self.access_token
+            .set(token_response.access_token.map(AccessToken::from));
+        self.refresh_token
+            .set(token_response.refresh_token.map(AccessToken::from));
+        tracing::info!("authenticated");
 
         Ok(())
     }
 
-    pub async fn refresh_access_token(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        if let Some(refresh_token) = &self.refresh_token {
+    #[tracing::instrument(skip_all)]
+    pub async fn refresh_access_token(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let result = self.request_refresh().await;
+        telemetry::token_refreshed("refresh", result.is_ok());
+        if let Err(error) = &result {
+            telemetry::log_error("token refresh", error.as_ref());
+        }
+        result
+    }
+
+    async fn request_refresh(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(refresh_token) = self.refresh_token.get() {
             let request = tonic::Request::new(RefreshAccessTokenRequest {
-                refresh_token: refresh_token.value.clone(),
+                refresh_token: refresh_token.value().to_owned(),
             });
 
-            let response = self.auth_client.refresh_access_token(request).await?;
-            self.access_token = response.into_inner().access_token;
+            let timer = RpcTimer::start("refresh_access_token");
+            let response = self.auth_client.clone().refresh_access_token(request).await;
+            timer.finish(response.is_ok());
+            let response = response?;
+            self.access_token
+                .set(response.into_inner().access_token.map(AccessToken::from));
+            tracing::debug!("access token refreshed");
 
             return Ok(());
         }
@@ -98,11 +250,44 @@ impl SovaAuth {
         Err(Box::from(SovaError::AuthenticationRequired))
     }
 
-    pub fn access_token(&self) -> Option<Token> {
+    pub fn access_token(&self) -> Option<AccessToken> {
+        self.access_token.get()
+    }
+
+    /// Token source kept up to date by [`authenticate`](Self::authenticate) and
+    /// [`refresh_access_token`](Self::refresh_access_token), for use with
+    /// [`AuthInterceptor`](crate::interceptor::AuthInterceptor).
+    pub fn token_source(&self) -> TokenSource {
         self.access_token.clone()
     }
 
-    pub fn refresh_token(&self) -> Option<Token> {
-        self.refresh_token.clone()
+    pub fn refresh_token(&self) -> Option<AccessToken> {
+        self.refresh_token.get()
+    }
+
+    pub fn info(&self) -> AuthInfo {
+        AuthInfo {
+            public_key: self.public_key().map(AuthPublicKey::from),
+            access_token: self.access_token.get().as_ref().map(TokenInfo::from),
+            refresh_token: self.refresh_token.get().as_ref().map(TokenInfo::from),
+        }
+    }
+
+    pub fn needs_refresh(&self, margin: Duration) -> bool {
+        self.info().needs_refresh(margin)
+    }
+
+    fn public_key(&self) -> Option<VerifyingKey> {
+        *self.public_key.read().unwrap()
+    }
+}
+
+impl fmt::Debug for SovaAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SovaAuth")
+            .field("public_key", &self.public_key().map(AuthPublicKey::from))
+            .field("access_token", &self.access_token.get())
+            .field("refresh_token", &self.refresh_token.get())
+            .finish_non_exhaustive()
     }
 }
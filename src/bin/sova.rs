@@ -0,0 +1,505 @@
+//! Operator tool for quick checks against a block engine.
+//!
+//! Endpoints and keys come from a config profile (see `sova_sdk_rs::config`), `SOVA_*`
+//! environment variables, or the flags below, in increasing order of precedence.
+
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use base64::Engine;
+use clap::{Args, Parser, Subcommand};
+use ed25519_dalek::SigningKey;
+use futures_util::future::try_join_all;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+
+use sova_sdk_rs::auth::AuthPublicKey;
+use sova_sdk_rs::client::SovaClient;
+use sova_sdk_rs::config::{KeySource, SovaConfig, SubscriptionConfig};
+use sova_sdk_rs::proto::dto::{Bundle, ExternalMessage};
+use sova_sdk_rs::subscription::{EndReason, SubscriptionHandle};
+use sova_sdk_rs::types::{BundleUpdate, MempoolEvent};
+
+#[derive(Parser)]
+#[command(name = "sova", version, about = "Sova block engine operator tool")]
+struct Cli {
+    /// TOML or JSON config file with profiles.
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+    /// Profile to use from the config file.
+    #[arg(long, global = true)]
+    profile: Option<String>,
+    /// Preset network: mainnet or testnet.
+    #[arg(long, global = true)]
+    network: Option<String>,
+    /// Engine URL, overriding the profile.
+    #[arg(long, global = true)]
+    url: Option<String>,
+    /// TLS mode: system, webpki, lets_encrypt, custom_ca or plaintext.
+    #[arg(long, global = true)]
+    tls: Option<String>,
+    /// File holding the 32-byte private key, raw or hex encoded.
+    #[arg(long, global = true)]
+    key_file: Option<PathBuf>,
+    /// Print one JSON object per line instead of human-readable text.
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Authenticate with the configured key and print the token expiry.
+    Auth {
+        /// Also print the access token value.
+        #[arg(long)]
+        show_token: bool,
+    },
+    /// Print the tip addresses.
+    Tips,
+    /// Mempool commands.
+    Mempool {
+        #[command(subcommand)]
+        command: MempoolCommand,
+    },
+    /// Bundle result commands.
+    Bundles {
+        #[command(subcommand)]
+        command: BundlesCommand,
+    },
+    /// Bundle submission commands.
+    Bundle {
+        #[command(subcommand)]
+        command: BundleCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum MempoolCommand {
+    /// Stream mempool packets. Without filters the profile subscriptions are used.
+    Tail(TailArgs),
+}
+
+#[derive(Subcommand)]
+enum BundlesCommand {
+    /// Stream bundle results.
+    Watch {
+        /// Exit after this many results.
+        #[arg(long)]
+        count: Option<usize>,
+    },
+}
+
+#[derive(Subcommand)]
+enum BundleCommand {
+    /// Send a bundle described by a JSON file:
+    /// `{"messages": ["<base64 BoC>", ...], "expires_in_ms": 5000}`.
+    Send { file: PathBuf },
+}
+
+#[derive(Args)]
+struct TailArgs {
+    /// Account address to follow. Repeatable.
+    #[arg(long = "address")]
+    addresses: Vec<String>,
+    #[arg(long)]
+    workchain: Option<i32>,
+    /// Hex encoded shard prefix.
+    #[arg(long)]
+    shard: Option<String>,
+    /// Message body opcode, decimal or 0x-prefixed hex. Matches external out messages unless
+    /// `--internal` is given.
+    #[arg(long, value_parser = parse_opcode)]
+    opcode: Option<u32>,
+    #[arg(long, requires = "opcode")]
+    internal: bool,
+    /// Exit after this many packets.
+    #[arg(long)]
+    count: Option<usize>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BundleFile {
+    messages: Vec<String>,
+    #[serde(default)]
+    expires_in_ms: Option<u64>,
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let cli = Cli::parse();
+
+    if let Err(error) = run(cli).await {
+        eprintln!("error: {error}");
+        std::process::exit(1);
+    }
+}
+
+async fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
+    let config = match &cli.config {
+        Some(path) => SovaConfig::load(path)?,
+        None => SovaConfig::default(),
+    };
+
+    // Flags win over the environment, which wins over the file.
+    let flags = [
+        ("SOVA_NETWORK", cli.network.clone()),
+        ("SOVA_URL", cli.url.clone()),
+        ("SOVA_TLS", cli.tls.clone()),
+        (
+            "SOVA_KEY_FILE",
+            cli.key_file.as_ref().map(|path| path.display().to_string()),
+        ),
+    ];
+    let profile = config.profile_with_env(cli.profile.as_deref(), |var| {
+        flags
+            .iter()
+            .find(|(name, _)| *name == var)
+            .and_then(|(_, value)| value.clone())
+            .or_else(|| std::env::var(var).ok())
+    })?;
+
+    let client = SovaClient::from_profile(profile, None)?;
+    let json = cli.json;
+
+    match cli.command {
+        Command::Auth { show_token } => {
+            let public_key = public_key(&client).await?;
+            let token = client.authenticate_with_configured_key().await?;
+            let expires_at = token.expires_at().map(unix_seconds);
+
+            if json {
+                let mut output = json!({
+                    "endpoint": client.endpoint().url,
+                    "public_key": public_key,
+                    "expires_at": expires_at,
+                });
+                if show_token {
+                    output["access_token"] = json!(token.value());
+                }
+                println!("{output}");
+            } else {
+                println!("authenticated to {}", client.endpoint().url);
+                println!("public key: {public_key}");
+                match expires_at {
+                    Some(expires_at) => println!("access token expires at: {expires_at} (unix)"),
+                    None => println!("access token expires at: unknown"),
+                }
+                if show_token {
+                    println!("access token: {}", token.value());
+                }
+            }
+        }
+        Command::Tips => {
+            authenticate_if_configured(&client).await?;
+            let options = client.profile().call_options();
+            let tips = client
+                .searcher()
+                .await?
+                .get_tip_addresses_with_options(options)
+                .await?;
+
+            if json {
+                println!("{}", json!({ "tip_addresses": tips.addresses() }));
+            } else {
+                for address in tips {
+                    println!("{address}");
+                }
+            }
+        }
+        Command::Mempool {
+            command: MempoolCommand::Tail(args),
+        } => {
+            authenticate_if_configured(&client).await?;
+            let subscriptions = match tail_subscription(&args)? {
+                Some(subscription) => vec![subscription],
+                None => client.profile().subscriptions.clone(),
+            };
+            if subscriptions.is_empty() {
+                return Err("no mempool filter given and the profile has no subscriptions".into());
+            }
+
+            let searcher = client.searcher().await?;
+            let (tx, rx) = mpsc::unbounded_channel();
+            let mut handles = Vec::new();
+            for subscription in subscriptions {
+                let tx = tx.clone();
+                handles.push(
+                    searcher
+                        .subscribe(subscription, move |event| {
+                            let _ = tx.send(event);
+                        })
+                        .await?,
+                );
+            }
+
+            print_until_ended(rx, &handles, args.count, |event| {
+                print_mempool_event(event, json)
+            })
+            .await?;
+        }
+        Command::Bundles {
+            command: BundlesCommand::Watch { count },
+        } => {
+            authenticate_if_configured(&client).await?;
+
+            let (tx, rx) = mpsc::unbounded_channel();
+            let handle = client
+                .searcher()
+                .await?
+                .subscribe_bundle_results(move |update| {
+                    let _ = tx.send(update);
+                })
+                .await?;
+
+            print_until_ended(rx, &[handle], count, |update| {
+                print_bundle_update(update, json)
+            })
+            .await?;
+        }
+        Command::Bundle {
+            command: BundleCommand::Send { file },
+        } => {
+            let bundle = read_bundle(&file)?;
+            authenticate_if_configured(&client).await?;
+            let options = client.profile().call_options();
+            let response = client
+                .searcher()
+                .await?
+                .send_bundle_with_options(bundle, options)
+                .await?;
+
+            if json {
+                println!("{}", json!({ "id": response.id }));
+            } else {
+                println!("bundle accepted: {}", response.id);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints what the subscriptions deliver until `count` items were printed or every
+/// subscription ended. Fails as soon as one of them fails.
+async fn print_until_ended<T>(
+    mut items: mpsc::UnboundedReceiver<T>,
+    handles: &[SubscriptionHandle],
+    count: Option<usize>,
+    print: impl Fn(&T),
+) -> Result<(), Box<dyn std::error::Error>> {
+    let ended = try_join_all(handles.iter().map(|handle| async move {
+        match handle.join().await {
+            EndReason::Failed { code, message } => Err(format!(
+                "the {} subscription failed: {code:?}: {message}",
+                handle.kind()
+            )),
+            _ => Ok(()),
+        }
+    }));
+    tokio::pin!(ended);
+
+    let mut printed = 0;
+    loop {
+        tokio::select! {
+            // Items delivered before the subscriptions ended are printed first.
+            biased;
+            Some(item) = items.recv() => {
+                print(&item);
+                printed += 1;
+                if count.is_some_and(|count| printed >= count) {
+                    break;
+                }
+            }
+            ended = &mut ended => {
+                ended?;
+                break;
+            }
+        }
+    }
+
+    for handle in handles {
+        handle.cancel();
+    }
+    Ok(())
+}
+
+async fn authenticate_if_configured(client: &SovaClient) -> Result<(), Box<dyn std::error::Error>> {
+    if client.profile().key.is_some() {
+        client.authenticate_with_configured_key().await?;
+    }
+    Ok(())
+}
+
+async fn public_key(client: &SovaClient) -> Result<String, Box<dyn std::error::Error>> {
+    let key = match &client.profile().key {
+        #[cfg(unix)]
+        Some(KeySource::UnixSocket(path)) => {
+            use sova_sdk_rs::signer::{ChallengeSigner, UnixSocketSigner};
+
+            UnixSocketSigner::new(path)
+                .public_key()
+                .await
+                .map_err(|error| error.to_string())?
+        }
+        Some(key) => SigningKey::from_bytes(&key.private_key()?).verifying_key(),
+        None => return Err("the profile has no key; pass --key-file or set SOVA_KEY_FILE".into()),
+    };
+
+    Ok(AuthPublicKey::from(key).to_hex())
+}
+
+fn tail_subscription(
+    args: &TailArgs,
+) -> Result<
+    Option<sova_sdk_rs::proto::searcher::mempool_subscription::Subscription>,
+    Box<dyn std::error::Error>,
+> {
+    let workchain_id = args.workchain.unwrap_or(0);
+
+    let config = if !args.addresses.is_empty() {
+        SubscriptionConfig::Addresses {
+            addresses: args.addresses.clone(),
+        }
+    } else if let Some(opcode) = args.opcode {
+        if args.internal {
+            SubscriptionConfig::InternalMessageBodyOpcode {
+                workchain_id,
+                shard: args.shard.clone(),
+                opcode,
+            }
+        } else {
+            SubscriptionConfig::ExternalOutMessageBodyOpcode {
+                workchain_id,
+                shard: args.shard.clone(),
+                opcode,
+            }
+        }
+    } else if let Some(shard) = &args.shard {
+        SubscriptionConfig::WorkchainShard {
+            workchain_id,
+            shard: shard.clone(),
+        }
+    } else if let Some(workchain_id) = args.workchain {
+        SubscriptionConfig::Workchain { workchain_id }
+    } else {
+        return Ok(None);
+    };
+
+    Ok(Some(config.to_subscription()?))
+}
+
+fn read_bundle(path: &PathBuf) -> Result<Bundle, Box<dyn std::error::Error>> {
+    let file: BundleFile = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+
+    let message = file
+        .messages
+        .iter()
+        .map(|boc| {
+            Ok(ExternalMessage {
+                data: base64::engine::general_purpose::STANDARD.decode(boc)?,
+            })
+        })
+        .collect::<Result<Vec<_>, base64::DecodeError>>()?;
+
+    let expiration_ns = file.expires_in_ms.map(|expires_in_ms| {
+        prost_types::Timestamp::from(
+            SystemTime::now() + std::time::Duration::from_millis(expires_in_ms),
+        )
+    });
+
+    Ok(Bundle {
+        message,
+        expiration_ns,
+    })
+}
+
+fn print_mempool_event(event: &MempoolEvent, json: bool) {
+    let server_time = event.server_time.map(unix_millis);
+    let latency_ms = event
+        .latency()
+        .map(|latency| latency.as_secs_f64() * 1000.0);
+
+    if json {
+        let messages = event
+            .messages
+            .iter()
+            .map(|message| {
+                json!({
+                    "hash": hex::encode(message.hash),
+                    "workchain_id": message.workchain_id,
+                    "shard": message.shard.to_string(),
+                    "data": base64::engine::general_purpose::STANDARD.encode(&message.data),
+                })
+            })
+            .collect::<Vec<Value>>();
+
+        println!(
+            "{}",
+            json!({
+                "server_time_ms": server_time,
+                "latency_ms": latency_ms,
+                "messages": messages,
+            })
+        );
+    } else {
+        match (server_time, latency_ms) {
+            (Some(server_time), Some(latency_ms)) => println!(
+                "packet at {server_time} ms (latency {latency_ms:.3} ms) with {} message(s)",
+                event.messages.len()
+            ),
+            (Some(server_time), None) => println!(
+                "packet at {server_time} ms with {} message(s)",
+                event.messages.len()
+            ),
+            _ => println!("packet with {} message(s)", event.messages.len()),
+        }
+        for message in &event.messages {
+            println!(
+                "  {} wc={} shard={} {} bytes",
+                hex::encode(message.hash),
+                message.workchain_id,
+                message.shard,
+                message.data.len()
+            );
+        }
+    }
+}
+
+fn print_bundle_update(update: &BundleUpdate, json: bool) {
+    if json {
+        println!(
+            "{}",
+            json!({
+                "id": update.bundle_id,
+                "outcome": update.outcome.kind(),
+                "detail": update.outcome.detail(),
+            })
+        );
+    } else {
+        println!("{} {}", update.bundle_id, update.outcome);
+    }
+}
+
+fn parse_opcode(value: &str) -> Result<u32, String> {
+    match value.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => value.parse(),
+    }
+    .map_err(|error| error.to_string())
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+fn unix_millis(time: SystemTime) -> u128 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis())
+        .unwrap_or_default()
+}
@@ -1,98 +1,144 @@
-use std::str::FromStr;
+use std::future;
+use std::time::Duration;
 
 use tonic::codegen::tokio_stream::Stream;
-use tonic::transport::{Certificate, Channel, ClientTlsConfig};
 
+use crate::call_options::CallOptions;
+use crate::delivery::Concurrency;
+use crate::error::SovaError;
+use crate::interceptor::{AuthChannel, AuthInterceptor, TokenSource};
 use crate::proto;
-use crate::proto::auth::Token;
 use crate::proto::block_engine::block_engine_validator_client::BlockEngineValidatorClient;
 use crate::proto::block_engine::SubscribeBundlesRequest;
 use crate::proto::dto::MempoolPacket;
+use crate::subscription::{self, SubscriptionHandle, SubscriptionId, SubscriptionSet};
+use crate::telemetry::{self, RpcTimer};
+use crate::tls::{self, TlsMode};
+use crate::types::AccessToken;
 
+#[derive(Clone)]
 pub struct SovaBlockEngine {
-    block_engine_client: BlockEngineValidatorClient<Channel>,
-    access_token: Option<Token>,
+    block_engine_client: BlockEngineValidatorClient<AuthChannel>,
+    tokens: TokenSource,
+    endpoint: String,
+    subscriptions: SubscriptionSet,
 }
 
 impl SovaBlockEngine {
     pub async fn new(
-        url: &'static str,
-        ca_pem: Option<&str>,
-        domain_name: Option<&str>,
-        access_token: Token,
+        url: &str,
+        tls: TlsMode,
+        access_token: AccessToken,
     ) -> Result<Self, Box<dyn std::error::Error>> {
-        let block_engine_client = if let (Some(ca_pem), Some(domain_name)) = (ca_pem, domain_name) {
-            let ca = Certificate::from_pem(ca_pem);
-
-            let tls = ClientTlsConfig::new()
-                .ca_certificate(ca)
-                .domain_name(domain_name);
+        Self::new_with_token_source(url, tls, TokenSource::new(Some(access_token))).await
+    }
 
-            let channel = Channel::from_static(url).tls_config(tls)?.connect().await?;
+    pub async fn new_with_token_source(
+        url: &str,
+        tls: TlsMode,
+        tokens: TokenSource,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let channel = tls::connect(url, &tls).await?;
 
-            BlockEngineValidatorClient::new(channel)
-        } else {
-            BlockEngineValidatorClient::connect(url).await?
-        };
+        let block_engine_client = BlockEngineValidatorClient::with_interceptor(
+            channel,
+            AuthInterceptor::new(tokens.clone()),
+        );
 
         Ok(Self {
             block_engine_client,
-            access_token: Some(access_token),
+            tokens,
+            endpoint: url.to_owned(),
+            subscriptions: SubscriptionSet::default(),
         })
     }
 
+    pub fn set_access_token(&self, token: AccessToken) {
+        self.tokens.set(Some(token));
+    }
+
+    pub fn token_source(&self) -> &TokenSource {
+        &self.tokens
+    }
+
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// Subscriptions started through [`subscribe_bundles`](Self::subscribe_bundles) that are
+    /// still running.
+    pub fn subscriptions(&self) -> Vec<SubscriptionHandle> {
+        self.subscriptions.active()
+    }
+
+    /// Cancels every running subscription and waits up to `deadline` for their callbacks to
+    /// finish.
+    pub async fn shutdown(&self, deadline: Duration) -> Result<(), SovaError> {
+        self.subscriptions.shutdown(deadline).await
+    }
+
+    #[tracing::instrument(skip_all, fields(endpoint = %self.endpoint))]
     pub async fn stream_mempool(
-        &mut self,
+        &self,
         stream: impl Stream<Item = MempoolPacket> + Send + 'static,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        let mut request = tonic::Request::new(stream);
-
-        if let Some(access_token) = &self.access_token {
-            request.metadata_mut().insert(
-                "authorization",
-                tonic::metadata::MetadataValue::from_str(&format!(
-                    "Bearer {}",
-                    access_token.value
-                ))?,
-            );
-        }
+        let request = tonic::Request::new(stream);
 
-        self.block_engine_client.stream_mempool(request).await?;
+        let timer = RpcTimer::start("stream_mempool");
+        let response = self
+            .block_engine_client
+            .clone()
+            .stream_mempool(request)
+            .await;
+        timer.finish(response.is_ok());
+        if let Err(status) = &response {
+            telemetry::log_error("stream_mempool", status);
+        }
+        response?;
 
         Ok(())
     }
 
     pub async fn subscribe_bundles<F>(
-        &mut self,
+        &self,
         on_data: F,
-    ) -> Result<(), Box<dyn std::error::Error>>
+    ) -> Result<SubscriptionHandle, Box<dyn std::error::Error>>
     where
         F: Fn(proto::dto::ValidatorBundle) + Send + 'static,
     {
-        let mut request = tonic::Request::new(SubscribeBundlesRequest {});
-
-        if let Some(access_token) = &self.access_token {
-            request.metadata_mut().insert(
-                "authorization",
-                tonic::metadata::MetadataValue::from_str(&format!(
-                    "Bearer {}",
-                    access_token.value
-                ))?,
-            );
-        }
-
-        let mut stream = self
+        let span = tracing::info_span!(
+            "subscription",
+            kind = "validator_bundles",
+            endpoint = %self.endpoint
+        );
+        let request = tonic::Request::new(SubscribeBundlesRequest {});
+
+        let timer = RpcTimer::start("subscribe_bundles");
+        let response = self
             .block_engine_client
+            .clone()
             .subscribe_bundles(request)
-            .await?
-            .into_inner();
-
-        tokio::spawn(async move {
-            while let Some(response) = stream.message().await.unwrap_or(None) {
-                on_data(response);
-            }
-        });
-
-        Ok(())
+            .await;
+        timer.finish(response.is_ok());
+        if let Err(status) = &response {
+            span.in_scope(|| telemetry::log_error("opening the subscription", status));
+        }
+        let stream = response?.into_inner();
+
+        let handle = subscription::spawn_stream(
+            stream,
+            CallOptions::default(),
+            Concurrency::Sequential,
+            span,
+            SubscriptionId::next(),
+            "validator_bundles",
+            move |bundle, _| {
+                on_data(bundle);
+                future::ready(())
+            },
+        );
+        self.subscriptions.insert(&handle);
+
+        Ok(handle)
     }
 }
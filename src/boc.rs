@@ -0,0 +1,217 @@
+//! Just enough of the TON bag of cells format to compute cell hashes, e.g. the message hash
+//! behind [`ExternalMessage::hash`](crate::proto::dto::ExternalMessage::hash).
+
+use sha2::{Digest, Sha256};
+
+use crate::error::SovaError;
+
+const MAGIC: [u8; 4] = [0xb5, 0xee, 0x9c, 0x72];
+const LIBRARY_CELL: u8 = 2;
+
+struct Cell<'a> {
+    d1: u8,
+    d2: u8,
+    data: &'a [u8],
+    refs: Vec<usize>,
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], SovaError> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| invalid("unexpected end of data"))?;
+        let bytes = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8, SovaError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn uint(&mut self, len: usize) -> Result<usize, SovaError> {
+        let value = self
+            .take(len)?
+            .iter()
+            .fold(0u64, |value, &byte| (value << 8) | u64::from(byte));
+        usize::try_from(value).map_err(|_| invalid("size does not fit in memory"))
+    }
+}
+
+fn invalid(reason: &str) -> SovaError {
+    SovaError::InvalidBoc(reason.to_owned())
+}
+
+/// Representation hash of the first root cell of a serialized bag of cells.
+///
+/// Cells with a non-zero level, such as pruned branches and Merkle proofs, are not supported;
+/// messages do not contain them.
+pub fn root_hash(boc: &[u8]) -> Result<[u8; 32], SovaError> {
+    let (cells, root) = parse(boc)?;
+
+    // References always point to later cells, so hashing from the end sees children first.
+    let mut hashes = vec![([0u8; 32], 0u16); cells.len()];
+    for (index, cell) in cells.iter().enumerate().rev() {
+        hashes[index] = hash_cell(cell, &hashes)?;
+    }
+
+    Ok(hashes[root].0)
+}
+
+/// Data of the first root cell and its length in bits.
+pub(crate) fn root_data(boc: &[u8]) -> Result<(&[u8], usize), SovaError> {
+    let (cells, root) = parse(boc)?;
+    let cell = &cells[root];
+
+    let bits = match cell.data.last() {
+        None => 0,
+        Some(_) if cell.d2 % 2 == 0 => cell.data.len() * 8,
+        // The last byte ends with a completion tag: a one followed by zeros.
+        Some(0) => return Err(invalid("missing completion tag")),
+        Some(last) => cell.data.len() * 8 - 1 - last.trailing_zeros() as usize,
+    };
+
+    Ok((cell.data, bits))
+}
+
+fn parse(boc: &[u8]) -> Result<(Vec<Cell<'_>>, usize), SovaError> {
+    let mut reader = Reader {
+        bytes: boc,
+        position: 0,
+    };
+    if reader.take(4)? != MAGIC {
+        return Err(invalid("unknown magic"));
+    }
+
+    let flags = reader.byte()?;
+    let has_index = flags & 0x80 != 0;
+    let has_crc = flags & 0x40 != 0;
+    let ref_size = usize::from(flags & 0x07);
+    let offset_size = usize::from(reader.byte()?);
+    if !(1..=4).contains(&ref_size) || !(1..=8).contains(&offset_size) {
+        return Err(invalid("invalid header sizes"));
+    }
+
+    let cell_count = reader.uint(ref_size)?;
+    let root_count = reader.uint(ref_size)?;
+    let _absent = reader.uint(ref_size)?;
+    let cells_size = reader.uint(offset_size)?;
+    if root_count == 0 {
+        return Err(invalid("no root cell"));
+    }
+    let root = reader.uint(ref_size)?;
+    reader.take((root_count - 1).saturating_mul(ref_size))?;
+    if has_index {
+        reader.take(cell_count.saturating_mul(offset_size))?;
+    }
+
+    let mut cells = Reader {
+        bytes: reader.take(cells_size)?,
+        position: 0,
+    };
+    let checked = reader.position;
+    let crc = if has_crc { Some(reader.take(4)?) } else { None };
+    if reader.position != boc.len() {
+        return Err(invalid("trailing bytes"));
+    }
+    if let Some(crc) = crc {
+        if crc32c(&boc[..checked]).to_le_bytes() != crc {
+            return Err(invalid("checksum mismatch"));
+        }
+    }
+
+    let mut parsed = Vec::new();
+    for index in 0..cell_count {
+        parsed.push(read_cell(&mut cells, index, cell_count, ref_size)?);
+    }
+    if root >= parsed.len() {
+        return Err(invalid("root index out of range"));
+    }
+
+    Ok((parsed, root))
+}
+
+fn read_cell<'a>(
+    reader: &mut Reader<'a>,
+    index: usize,
+    cell_count: usize,
+    ref_size: usize,
+) -> Result<Cell<'a>, SovaError> {
+    let d1 = reader.byte()?;
+    let d2 = reader.byte()?;
+    let ref_count = usize::from(d1 & 0x07);
+    if ref_count > 4 {
+        return Err(invalid("absent cells are not supported"));
+    }
+    if d1 & 0x10 != 0 {
+        // Stored hashes and depths, one pair per level.
+        let levels = (d1 >> 5).count_ones() as usize + 1;
+        reader.take(levels * (32 + 2))?;
+    }
+
+    let data = reader.take(usize::from(d2).div_ceil(2))?;
+    let mut refs = Vec::with_capacity(ref_count);
+    for _ in 0..ref_count {
+        let child = reader.uint(ref_size)?;
+        if child <= index || child >= cell_count {
+            return Err(invalid("cell reference out of order"));
+        }
+        refs.push(child);
+    }
+
+    Ok(Cell { d1, d2, data, refs })
+}
+
+fn hash_cell(cell: &Cell, hashes: &[([u8; 32], u16)]) -> Result<([u8; 32], u16), SovaError> {
+    if cell.d1 >> 5 != 0 {
+        return Err(invalid("cells with a non-zero level are not supported"));
+    }
+    if cell.d1 & 0x08 != 0 && cell.data.first() != Some(&LIBRARY_CELL) {
+        return Err(invalid(
+            "exotic cells other than libraries are not supported",
+        ));
+    }
+
+    let children = cell.refs.iter().map(|&child| hashes[child]);
+    let depth = match children.clone().map(|(_, depth)| depth).max() {
+        Some(depth) => depth
+            .checked_add(1)
+            .ok_or_else(|| invalid("cell tree is too deep"))?,
+        None => 0,
+    };
+
+    let mut hasher = Sha256::new();
+    // The descriptor without the stored-hashes flag, then the padded data as serialized.
+    hasher.update([cell.d1 & !0x10, cell.d2]);
+    hasher.update(cell.data);
+    for (_, depth) in children.clone() {
+        hasher.update(depth.to_be_bytes());
+    }
+    for (hash, _) in children {
+        hasher.update(hash);
+    }
+
+    Ok((hasher.finalize().into(), depth))
+}
+
+fn crc32c(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0x82f6_3b78
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
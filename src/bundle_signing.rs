@@ -0,0 +1,178 @@
+//! Optional bundle signatures proving which searcher submitted a bundle.
+//!
+//! A searcher with a [bundle signer](crate::searcher::SovaSearcher::with_bundle_signer) signs
+//! the canonical [`Bundle::hash`] and the expiration with its ed25519 key and sends the signature
+//! as request metadata. [`BundleVerifier`] checks it on the engine side or later, against a
+//! stored bundle.
+//!
+//! The signed message starts with [`SIGNING_DOMAIN`], so a bundle signature can never be
+//! mistaken for an answer to an auth challenge signed by the same key.
+
+use std::collections::HashSet;
+
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use prost_types::Timestamp;
+use tonic::metadata::{MetadataMap, MetadataValue};
+
+use crate::error::SovaError;
+use crate::proto::dto::Bundle;
+use crate::signer::ChallengeSigner;
+
+/// Hex-encoded public key of the signer.
+pub const SIGNER_HEADER: &str = "x-sova-bundle-signer";
+/// Hex-encoded [`Bundle::hash`].
+pub const HASH_HEADER: &str = "x-sova-bundle-hash";
+/// Hex-encoded ed25519 signature.
+pub const SIGNATURE_HEADER: &str = "x-sova-bundle-signature";
+
+/// Prefix of every signed message.
+pub const SIGNING_DOMAIN: &[u8] = b"sova-bundle-signature-v1:";
+
+/// The message actually signed for a bundle: the domain, the bundle hash and the expiration.
+pub fn signing_message(hash: &[u8; 32], expiration: Option<&Timestamp>) -> Vec<u8> {
+    let mut message = [SIGNING_DOMAIN, hash].concat();
+    match expiration {
+        Some(expiration) => {
+            message.push(1);
+            message.extend(expiration.seconds.to_be_bytes());
+            message.extend(expiration.nanos.to_be_bytes());
+        }
+        None => message.push(0),
+    }
+    message
+}
+
+/// A searcher's signature over a bundle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BundleSignature {
+    pub signer: VerifyingKey,
+    pub hash: [u8; 32],
+    pub signature: Signature,
+}
+
+impl BundleSignature {
+    pub fn to_metadata(&self, metadata: &mut MetadataMap) {
+        let entries = [
+            (SIGNER_HEADER, hex::encode(self.signer.as_bytes())),
+            (HASH_HEADER, hex::encode(self.hash)),
+            (SIGNATURE_HEADER, hex::encode(self.signature.to_bytes())),
+        ];
+        for (key, value) in entries {
+            let value = MetadataValue::try_from(value).expect("hex is valid metadata");
+            metadata.insert(key, value);
+        }
+    }
+
+    /// Reads the signature sent with a bundle. Returns `Ok(None)` if the bundle is unsigned.
+    pub fn from_metadata(metadata: &MetadataMap) -> Result<Option<Self>, SovaError> {
+        if !metadata.contains_key(SIGNATURE_HEADER) {
+            return Ok(None);
+        }
+
+        let signer: [u8; 32] = header_bytes(metadata, SIGNER_HEADER)?;
+        let hash = header_bytes(metadata, HASH_HEADER)?;
+        let signature: [u8; 64] = header_bytes(metadata, SIGNATURE_HEADER)?;
+
+        Ok(Some(Self {
+            signer: VerifyingKey::from_bytes(&signer)
+                .map_err(|_| SovaError::InvalidBundleSignature("invalid signer key".into()))?,
+            hash,
+            signature: Signature::from_bytes(&signature),
+        }))
+    }
+}
+
+fn header_bytes<const N: usize>(metadata: &MetadataMap, key: &str) -> Result<[u8; N], SovaError> {
+    let invalid = || SovaError::InvalidBundleSignature(format!("missing or malformed {key}"));
+
+    let value = metadata.get(key).ok_or_else(invalid)?;
+    let bytes = hex::decode(value.to_str().map_err(|_| invalid())?).map_err(|_| invalid())?;
+
+    bytes.try_into().map_err(|_| invalid())
+}
+
+/// Signs the bundle with `signer`.
+pub async fn sign_bundle(
+    signer: &dyn ChallengeSigner,
+    bundle: &Bundle,
+) -> Result<BundleSignature, SovaError> {
+    let signer_key = signer.public_key().await.map_err(SovaError::Signer)?;
+    sign_bundle_as(signer, signer_key, bundle).await
+}
+
+/// Like [`sign_bundle`], with the public key of `signer` already known.
+pub(crate) async fn sign_bundle_as(
+    signer: &dyn ChallengeSigner,
+    signer_key: VerifyingKey,
+    bundle: &Bundle,
+) -> Result<BundleSignature, SovaError> {
+    let hash = bundle.hash()?;
+    let signature = signer
+        .sign_challenge(&signing_message(&hash, bundle.expiration_ns.as_ref()))
+        .await
+        .map_err(SovaError::Signer)?;
+
+    Ok(BundleSignature {
+        signer: signer_key,
+        hash,
+        signature,
+    })
+}
+
+/// Checks bundle signatures, optionally only accepting known signers.
+#[derive(Clone, Debug, Default)]
+pub struct BundleVerifier {
+    trusted: Option<HashSet<[u8; 32]>>,
+}
+
+impl BundleVerifier {
+    /// Accepts a valid signature from any key.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only accepts signatures from `signer` and other trusted keys.
+    pub fn trust(mut self, signer: VerifyingKey) -> Self {
+        self.trusted
+            .get_or_insert_with(HashSet::new)
+            .insert(signer.to_bytes());
+        self
+    }
+
+    /// Checks that `signature` covers exactly this bundle and was made by an accepted signer.
+    pub fn verify(&self, bundle: &Bundle, signature: &BundleSignature) -> Result<(), SovaError> {
+        if let Some(trusted) = &self.trusted {
+            if !trusted.contains(signature.signer.as_bytes()) {
+                return Err(SovaError::InvalidBundleSignature(format!(
+                    "signer {} is not trusted",
+                    hex::encode(signature.signer.as_bytes())
+                )));
+            }
+        }
+        if bundle.hash()? != signature.hash {
+            return Err(SovaError::InvalidBundleSignature(
+                "hash does not match the bundle".into(),
+            ));
+        }
+
+        signature
+            .signer
+            .verify(
+                &signing_message(&signature.hash, bundle.expiration_ns.as_ref()),
+                &signature.signature,
+            )
+            .map_err(|_| SovaError::InvalidSignature)
+    }
+
+    /// Verifies the signature sent with a `send_bundle` request and returns it.
+    pub fn verify_request(
+        &self,
+        request: &tonic::Request<Bundle>,
+    ) -> Result<BundleSignature, SovaError> {
+        let signature = BundleSignature::from_metadata(request.metadata())?
+            .ok_or_else(|| SovaError::InvalidBundleSignature("the bundle is unsigned".into()))?;
+        self.verify(request.get_ref(), &signature)?;
+
+        Ok(signature)
+    }
+}
@@ -0,0 +1,265 @@
+//! Auction analytics over the bundle results stream: win rate, failure reasons, tips paid
+//! against winning tips, and outcomes per strategy tag.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::call_options::CallOptions;
+use crate::proto::searcher::BundleResult;
+use crate::searcher::SovaSearcher;
+use crate::subscription::SubscriptionHandle;
+use crate::types::BundleUpdate;
+
+/// How long results are kept by [`BundleStats::default`].
+pub const DEFAULT_RETENTION: Duration = Duration::from_secs(60 * 60);
+
+/// Tag of results for bundles that were not [tracked](BundleStats::track).
+pub const UNTAGGED: &str = "untagged";
+
+/// Collects bundle results for [`snapshot`](Self::snapshot)s over rolling windows. Clones share
+/// the collected results.
+///
+/// [`track`](Self::track) each bundle after sending it to attribute its result to a strategy
+/// tag and compare the tip it paid with the winning tip.
+#[derive(Clone)]
+pub struct BundleStats {
+    retention: Duration,
+    state: Arc<Mutex<StatsState>>,
+}
+
+#[derive(Default)]
+struct StatsState {
+    sent: HashMap<String, Sent>,
+    results: VecDeque<Record>,
+    invalid: VecDeque<Instant>,
+}
+
+struct Sent {
+    at: Instant,
+    tag: String,
+    tip: Option<u64>,
+}
+
+struct Record {
+    at: Instant,
+    tag: String,
+    outcome: &'static str,
+    included: bool,
+    tip: Option<u64>,
+    winning_tip: Option<u64>,
+}
+
+impl Default for BundleStats {
+    fn default() -> Self {
+        Self::new(DEFAULT_RETENTION)
+    }
+}
+
+impl BundleStats {
+    /// Keeps results for `retention`, the longest window a snapshot can cover.
+    pub fn new(retention: Duration) -> Self {
+        Self {
+            retention,
+            state: Arc::default(),
+        }
+    }
+
+    /// Attributes the result of `bundle_id` to `tag`, with the tip the bundle paid if known.
+    pub fn track(&self, bundle_id: impl Into<String>, tag: impl Into<String>, tip: Option<u64>) {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        self.prune(&mut state, now);
+        state.sent.insert(
+            bundle_id.into(),
+            Sent {
+                at: now,
+                tag: tag.into(),
+                tip,
+            },
+        );
+    }
+
+    pub fn record(&self, update: &BundleUpdate) {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        self.prune(&mut state, now);
+
+        let sent = state.sent.remove(&update.bundle_id);
+        state.results.push_back(Record {
+            at: now,
+            tag: sent
+                .as_ref()
+                .map_or_else(|| UNTAGGED.to_owned(), |sent| sent.tag.clone()),
+            outcome: update.outcome.kind(),
+            included: update.outcome.is_included(),
+            tip: sent.and_then(|sent| sent.tip),
+            winning_tip: update.outcome.winning_tip(),
+        });
+    }
+
+    /// Records a raw result; results that do not decode into a [`BundleUpdate`] are only
+    /// counted as invalid.
+    pub fn record_result(&self, result: BundleResult) {
+        match BundleUpdate::try_from(result) {
+            Ok(update) => self.record(&update),
+            Err(_) => {
+                let now = Instant::now();
+                let mut state = self.state.lock().unwrap();
+                self.prune(&mut state, now);
+                state.invalid.push_back(now);
+            }
+        }
+    }
+
+    /// Feeds every result from `searcher`'s bundle results stream into these stats.
+    pub async fn subscribe(
+        &self,
+        searcher: &SovaSearcher,
+    ) -> Result<SubscriptionHandle, Box<dyn std::error::Error>> {
+        let stats = self.clone();
+
+        searcher
+            .subscribe_bundle_results_raw_with_options(CallOptions::default(), move |result| {
+                stats.record_result(result)
+            })
+            .await
+    }
+
+    /// Statistics over results received in the last `window`, capped at the retention.
+    pub fn snapshot(&self, window: Duration) -> BundleStatsSnapshot {
+        let window = window.min(self.retention);
+        let now = Instant::now();
+        let in_window = |at: Instant| now.duration_since(at) <= window;
+
+        let state = self.state.lock().unwrap();
+        let mut snapshot = BundleStatsSnapshot {
+            window,
+            invalid: state.invalid.iter().filter(|&&at| in_window(at)).count() as u64,
+            ..Default::default()
+        };
+        let mut included_tips = Average::default();
+        let mut lost_tips = Average::default();
+        let mut winning_tips = Average::default();
+
+        for record in state.results.iter().filter(|record| in_window(record.at)) {
+            snapshot.outcomes.add(record);
+            snapshot
+                .tags
+                .entry(record.tag.clone())
+                .or_default()
+                .add(record);
+
+            match (record.included, record.tip, record.winning_tip) {
+                (true, Some(tip), _) => included_tips.add(tip),
+                (false, Some(tip), Some(winning_tip)) => {
+                    lost_tips.add(tip);
+                    winning_tips.add(winning_tip);
+                }
+                _ => {}
+            }
+        }
+
+        snapshot.tips = TipStats {
+            included: included_tips.count,
+            average_included_tip: included_tips.mean(),
+            lost_to_higher_tip: lost_tips.count,
+            average_lost_tip: lost_tips.mean(),
+            average_winning_tip: winning_tips.mean(),
+        };
+
+        snapshot
+    }
+
+    /// Drops results older than the retention and tracked bundles that never got one.
+    fn prune(&self, state: &mut StatsState, now: Instant) {
+        let expired = |at: Instant| now.duration_since(at) > self.retention;
+
+        while state
+            .results
+            .front()
+            .is_some_and(|record| expired(record.at))
+        {
+            state.results.pop_front();
+        }
+        while state.invalid.front().is_some_and(|&at| expired(at)) {
+            state.invalid.pop_front();
+        }
+        state.sent.retain(|_, sent| !expired(sent.at));
+    }
+}
+
+/// Bundle statistics over one window, serializable for export.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct BundleStatsSnapshot {
+    pub window: Duration,
+    #[serde(flatten)]
+    pub outcomes: OutcomeStats,
+    /// Results that could not be decoded.
+    pub invalid: u64,
+    pub tips: TipStats,
+    /// Outcomes per strategy tag; untracked bundles are under [`UNTAGGED`].
+    pub tags: BTreeMap<String, OutcomeStats>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct OutcomeStats {
+    pub results: u64,
+    pub included: u64,
+    /// Share of results that were included, or `None` without results.
+    pub win_rate: Option<f64>,
+    /// Counts per [`BundleOutcome::kind`](crate::types::BundleOutcome::kind), including `included`.
+    pub by_outcome: BTreeMap<String, u64>,
+}
+
+impl OutcomeStats {
+    /// Results that were not included, per outcome.
+    pub fn failures(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.by_outcome
+            .iter()
+            .filter(|(outcome, _)| *outcome != "included")
+            .map(|(outcome, &count)| (outcome.as_str(), count))
+    }
+
+    fn add(&mut self, record: &Record) {
+        self.results += 1;
+        self.included += u64::from(record.included);
+        *self
+            .by_outcome
+            .entry(record.outcome.to_owned())
+            .or_default() += 1;
+        self.win_rate = Some(self.included as f64 / self.results as f64);
+    }
+}
+
+/// Tips of tracked bundles, in nanotons. Winning tips are only known when the engine reports
+/// them as the detail of
+/// [`BundleOutcome::HigherTipWon`](crate::types::BundleOutcome::HigherTipWon).
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct TipStats {
+    pub included: u64,
+    pub average_included_tip: Option<u64>,
+    /// Bundles that lost to a higher tip with both tips known.
+    pub lost_to_higher_tip: u64,
+    pub average_lost_tip: Option<u64>,
+    pub average_winning_tip: Option<u64>,
+}
+
+#[derive(Default)]
+struct Average {
+    count: u64,
+    sum: u128,
+}
+
+impl Average {
+    fn add(&mut self, value: u64) {
+        self.count += 1;
+        self.sum += u128::from(value);
+    }
+
+    fn mean(&self) -> Option<u64> {
+        (self.count > 0).then(|| (self.sum / u128::from(self.count)) as u64)
+    }
+}
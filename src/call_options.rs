@@ -0,0 +1,117 @@
+use std::future::Future;
+use std::time::Duration;
+
+use tokio_util::sync::CancellationToken;
+use tonic::metadata::{MetadataKey, MetadataValue};
+use tonic::Status;
+
+use crate::delivery::DeliveryConfig;
+use crate::error::SovaError;
+
+/// Per-call settings for [`SovaSearcher`](crate::searcher::SovaSearcher) requests.
+///
+/// The timeout is sent as the `grpc-timeout` deadline, and unary calls also give up locally with
+/// `DeadlineExceeded` once it passes. For subscriptions it bounds the lifetime of the whole
+/// stream, and cancelling the token stops delivery. A [`DeliveryConfig`] buffers subscription
+/// messages so a slow callback does not stall the stream.
+#[derive(Clone, Debug, Default)]
+pub struct CallOptions {
+    timeout: Option<Duration>,
+    metadata: Vec<(String, String)>,
+    cancellation: Option<CancellationToken>,
+    delivery: Option<DeliveryConfig>,
+}
+
+impl CallOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Adds an ASCII metadata entry, validated when the request is built.
+    pub fn metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.push((key.into(), value.into()));
+        self
+    }
+
+    pub fn request_id(self, request_id: impl Into<String>) -> Self {
+        self.metadata("x-request-id", request_id)
+    }
+
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    pub fn cancellation_token(&self) -> Option<&CancellationToken> {
+        self.cancellation.as_ref()
+    }
+
+    /// Delivers subscription messages through a bounded buffer. Ignored by unary calls.
+    pub fn delivery(mut self, delivery: DeliveryConfig) -> Self {
+        self.delivery = Some(delivery);
+        self
+    }
+
+    pub fn delivery_config(&self) -> Option<&DeliveryConfig> {
+        self.delivery.as_ref()
+    }
+
+    pub(crate) fn request<T>(&self, message: T) -> Result<tonic::Request<T>, SovaError> {
+        let mut request = tonic::Request::new(message);
+
+        if let Some(timeout) = self.timeout {
+            request.set_timeout(timeout);
+        }
+
+        for (key, value) in &self.metadata {
+            let key = MetadataKey::from_bytes(key.as_bytes())
+                .map_err(|_| SovaError::InvalidMetadata(key.clone()))?;
+            let value = MetadataValue::try_from(value.as_str())
+                .map_err(|_| SovaError::InvalidMetadata(key.to_string()))?;
+
+            request.metadata_mut().insert(key, value);
+        }
+
+        Ok(request)
+    }
+
+    /// Runs `future` unless the cancellation token fires first.
+    pub(crate) async fn run<F: Future>(&self, future: F) -> Result<F::Output, SovaError> {
+        match &self.cancellation {
+            Some(token) => tokio::select! {
+                biased;
+                _ = token.cancelled() => Err(SovaError::Cancelled),
+                output = future => Ok(output),
+            },
+            None => Ok(future.await),
+        }
+    }
+
+    /// Like [`run`](Self::run) for a call, failing it with `DeadlineExceeded` once the timeout
+    /// passes even if the server never answers.
+    pub(crate) async fn run_call<T, F>(&self, call: F) -> Result<Result<T, Status>, SovaError>
+    where
+        F: Future<Output = Result<T, Status>>,
+    {
+        let Some(timeout) = self.timeout else {
+            return self.run(call).await;
+        };
+
+        // Checked first, since the channel reports its own expiry of the same deadline as
+        // `Cancelled`.
+        let deadline = tokio::time::sleep(timeout);
+        self.run(async {
+            tokio::select! {
+                biased;
+                _ = deadline => Err(Status::deadline_exceeded("Deadline expired")),
+                response = call => response,
+            }
+        })
+        .await
+    }
+}
@@ -1,13 +1,29 @@
+use std::path::Path;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use ed25519_dalek::SigningKey;
+use tokio::task::JoinHandle;
+
 use crate::auth::SovaAuth;
-use crate::pem::{TESTNET_CA_PEM, MAINNET_CA_PEM};
-use crate::proto::auth::Token;
+use crate::config::{KeySource, Profile, SovaConfig};
+use crate::error::SovaError;
+use crate::interceptor::TokenSource;
+use crate::pool::{EngineEndpoint, SearcherPool};
 use crate::searcher::SovaSearcher;
+use crate::signer::ChallengeSigner;
+use crate::subscription::SubscriptionSet;
+use crate::tls::TlsMode;
+use crate::types::AccessToken;
+
+/// Shortest pause between two checks of the [token refresh task](SovaClient::spawn_token_refresh).
+const MIN_REFRESH_WAIT: Duration = Duration::from_secs(1);
 
 pub struct SovaClient {
-    url: String,
-    ca_pem: String,
-    domain_name: String,
-    auth_token: Option<Token>,
+    profile: Profile,
+    auth_token: TokenSource,
+    auth: RwLock<Option<SovaAuth>>,
+    subscriptions: SubscriptionSet,
 }
 
 impl SovaClient {
@@ -15,67 +31,206 @@ impl SovaClient {
         Self::mainnet_with_auth(None)
     }
 
-    pub fn mainnet_with_auth(auth_token: Option<Token>) -> Self {
-        Self::custom(
-            "https://engine.sova.network:30020",
-            MAINNET_CA_PEM,
-            "engine.sova.network",
-            auth_token,
-        )
+    pub fn mainnet_with_auth(auth_token: Option<AccessToken>) -> Self {
+        Self::with_endpoint(EngineEndpoint::mainnet(), auth_token)
     }
 
     pub fn testnet() -> Self {
         Self::testnet_with_auth(None)
     }
 
-    pub fn testnet_with_auth(auth_token: Option<Token>) -> Self {
-        Self::custom(
-            "https://testnet-engine.sova.network:30020",
-            TESTNET_CA_PEM,
-            "testnet-engine.sova.network",
-            auth_token,
-        )
+    pub fn testnet_with_auth(auth_token: Option<AccessToken>) -> Self {
+        Self::with_endpoint(EngineEndpoint::testnet(), auth_token)
+    }
+
+    pub fn custom(url: &str, tls: TlsMode, auth_token: Option<AccessToken>) -> Self {
+        Self::with_endpoint(EngineEndpoint::new(url, tls), auth_token)
     }
 
-    pub fn custom(url: &str, ca_pem: &str, domain_name: &str, auth_token: Option<Token>) -> Self {
+    pub fn with_endpoint(endpoint: EngineEndpoint, auth_token: Option<AccessToken>) -> Self {
         Self {
-            url: url.to_owned(),
-            ca_pem: ca_pem.to_owned(),
-            domain_name: domain_name.to_owned(),
-            auth_token,
+            profile: Profile::new("custom", endpoint),
+            auth_token: TokenSource::new(auth_token),
+            auth: RwLock::default(),
+            subscriptions: SubscriptionSet::default(),
         }
     }
 
+    /// Builds a client from a profile in a TOML or JSON config file, with `SOVA_*` environment
+    /// variables overriding the file. See [`SovaConfig`].
+    pub fn from_config(path: impl AsRef<Path>, profile: Option<&str>) -> Result<Self, SovaError> {
+        Self::from_profile(SovaConfig::load(path)?.profile(profile)?, None)
+    }
+
+    pub fn from_profile(
+        profile: Profile,
+        auth_token: Option<AccessToken>,
+    ) -> Result<Self, SovaError> {
+        if profile.endpoints.is_empty() {
+            return Err(SovaError::Config(format!(
+                "profile {:?} has no endpoints",
+                profile.name
+            )));
+        }
+
+        Ok(Self {
+            profile,
+            auth_token: TokenSource::new(auth_token),
+            auth: RwLock::default(),
+            subscriptions: SubscriptionSet::default(),
+        })
+    }
+
+    /// The primary endpoint.
+    pub fn endpoint(&self) -> &EngineEndpoint {
+        &self.profile.endpoints[0]
+    }
+
+    pub fn profile(&self) -> &Profile {
+        &self.profile
+    }
+
     pub async fn authenticate(
-        &mut self,
+        &self,
         private_key: [u8; 32],
-    ) -> Result<Token, Box<dyn std::error::Error>> {
-        let mut auth = SovaAuth::new(
-            Box::leak(self.url.clone().into_boxed_str()),
-            Some(&self.ca_pem),
-            Some(&self.domain_name),
-            &private_key,
-        )
-        .await?;
+    ) -> Result<AccessToken, Box<dyn std::error::Error>> {
+        self.authenticate_with_signer(SigningKey::from_bytes(&private_key))
+            .await
+    }
+
+    /// Authenticates with the key source of the profile.
+    pub async fn authenticate_with_configured_key(
+        &self,
+    ) -> Result<AccessToken, Box<dyn std::error::Error>> {
+        let key = self.profile.key.clone().ok_or_else(|| {
+            SovaError::Config(format!("profile {:?} has no key", self.profile.name))
+        })?;
+
+        match key {
+            #[cfg(unix)]
+            KeySource::UnixSocket(path) => {
+                self.authenticate_with_signer(crate::signer::UnixSocketSigner::new(path))
+                    .await
+            }
+            key => self.authenticate(key.private_key()?).await,
+        }
+    }
+
+    /// Authenticates with `signer` and keeps the session, so the access token can be
+    /// [refreshed](Self::refresh_access_token) for every searcher created by this client.
+    pub async fn authenticate_with_signer(
+        &self,
+        signer: impl ChallengeSigner + 'static,
+    ) -> Result<AccessToken, Box<dyn std::error::Error>> {
+        let endpoint = self.endpoint();
+        let auth = SovaAuth::with_signer(&endpoint.url, endpoint.tls.clone(), signer)
+            .await?
+            .with_token_source(self.auth_token.clone());
 
         auth.authenticate().await?;
 
         let token = auth
             .access_token()
             .ok_or("Authentication failed: missing access token")?;
-
-        self.auth_token = Some(token.clone());
+        *self.auth.write().unwrap() = Some(auth);
 
         Ok(token)
     }
 
+    /// The session of the last successful authentication.
+    pub fn auth(&self) -> Option<SovaAuth> {
+        self.auth.read().unwrap().clone()
+    }
+
+    /// The access token sent by searchers created by this client.
+    pub fn access_token(&self) -> Option<AccessToken> {
+        self.auth_token.get()
+    }
+
+    /// Gets a new access token with the refresh token, or authenticates again once the refresh
+    /// token has expired. Fails with [`SovaError::AuthenticationRequired`] before the first
+    /// authentication.
+    pub async fn refresh_access_token(&self) -> Result<AccessToken, Box<dyn std::error::Error>> {
+        let auth = self.auth().ok_or(SovaError::AuthenticationRequired)?;
+        refresh(&auth).await?;
+
+        Ok(auth
+            .access_token()
+            .ok_or("Token refresh failed: missing access token")?)
+    }
+
+    /// Refreshes the access token in the background whenever it expires within `margin`,
+    /// until the returned task is aborted. Fails with [`SovaError::AuthenticationRequired`]
+    /// before the first authentication.
+    pub fn spawn_token_refresh(&self, margin: Duration) -> Result<JoinHandle<()>, SovaError> {
+        let auth = self.auth().ok_or(SovaError::AuthenticationRequired)?;
+
+        Ok(tokio::spawn(async move {
+            loop {
+                if auth.needs_refresh(margin) {
+                    if let Err(error) = refresh(&auth).await.map_err(|error| error.to_string()) {
+                        tracing::warn!(error, "background token refresh failed");
+                    }
+                }
+
+                let wait = auth
+                    .info()
+                    .access_token
+                    .and_then(|token| token.time_remaining())
+                    .map_or(margin, |remaining| remaining.saturating_sub(margin));
+                tokio::time::sleep(wait.max(MIN_REFRESH_WAIT)).await;
+            }
+        }))
+    }
+
+    /// Connects a searcher to the primary endpoint, using the profile's request timeout for its
+    /// unary calls.
     pub async fn searcher(&self) -> Result<SovaSearcher, Box<dyn std::error::Error>> {
-        SovaSearcher::new_with_access_token(
-            Box::leak(self.url.clone().into_boxed_str()),
-            Some(&self.ca_pem),
-            Some(&self.domain_name),
+        let endpoint = self.endpoint();
+
+        SovaSearcher::new_with_token_source(
+            &endpoint.url,
+            endpoint.tls.clone(),
+            self.auth_token.clone(),
+        )
+        .await
+        .map(|searcher| {
+            searcher
+                .with_subscriptions(self.subscriptions.clone())
+                .with_default_options(self.profile.call_options())
+        })
+    }
+
+    /// Connects a [`SearcherPool`] to this client's endpoints plus `extra_endpoints`, sharing the
+    /// client's access token and configured with the profile's [`pool`](Profile::pool) settings.
+    pub async fn searcher_pool(
+        &self,
+        extra_endpoints: Vec<EngineEndpoint>,
+    ) -> Result<SearcherPool, Box<dyn std::error::Error>> {
+        let mut endpoints = self.profile.endpoints.clone();
+        endpoints.extend(extra_endpoints);
+
+        SearcherPool::connect_with_subscriptions(
+            endpoints,
             self.auth_token.clone(),
+            self.profile.pool.clone(),
+            self.subscriptions.clone(),
         )
         .await
     }
+
+    /// Cancels every subscription started through searchers and pools created by this client,
+    /// and waits up to `deadline` for them to stop. Fails with [`SovaError::ShutdownTimedOut`]
+    /// if some are still running at the deadline.
+    pub async fn shutdown(&self, deadline: Duration) -> Result<(), SovaError> {
+        self.subscriptions.shutdown(deadline).await
+    }
+}
+
+async fn refresh(auth: &SovaAuth) -> Result<(), Box<dyn std::error::Error>> {
+    if auth.info().can_refresh() {
+        auth.refresh_access_token().await
+    } else {
+        auth.authenticate().await
+    }
 }
@@ -0,0 +1,518 @@
+//! Client profiles loaded from a TOML or JSON file with
+//! [`SovaClient::from_config`](crate::client::SovaClient::from_config).
+//!
+//! A profile sets the endpoints, TLS, key source, timeouts and default subscriptions. `SOVA_*`
+//! environment variables override the file.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::call_options::CallOptions;
+use crate::error::SovaError;
+use crate::pool::{EngineEndpoint, PoolConfig};
+use crate::proto::searcher::{
+    mempool_subscription, AddressSubscriptionV0, ExternalOutMessageBodyOpcodeSubscriptionV0,
+    InternalMessageBodyOpcodeSubscriptionV0, WorkchainShardSubscriptionV0, WorkchainSubscriptionV0,
+};
+use crate::tls::{TlsMode, CA_BUNDLE_ENV};
+
+/// A configuration file with named profiles, in TOML or JSON.
+///
+/// ```toml
+/// default_profile = "local"
+///
+/// [profiles.local]
+/// endpoints = [{ url = "http://[::1]:50051", tls = { mode = "plaintext" } }]
+/// key = { file = "searcher.key" }
+/// request_timeout_ms = 5000
+/// reconnect = { retry_delay_ms = 500, health_check_interval_ms = 10000 }
+/// subscriptions = [{ type = "workchain", workchain_id = 0 }]
+///
+/// [profiles.mainnet]
+/// network = "mainnet"
+/// key = { env = "SOVA_PRIVATE_KEY" }
+/// ```
+///
+/// When a profile is resolved, `SOVA_*` environment variables override the file values:
+/// `SOVA_PROFILE`, `SOVA_NETWORK`, `SOVA_URL`, `SOVA_TLS`, `SOVA_CA_BUNDLE`, `SOVA_KEY_FILE`,
+/// `SOVA_PRIVATE_KEY`, `SOVA_SIGNER_SOCKET`, `SOVA_REQUEST_TIMEOUT_MS`, `SOVA_PROBE_TIMEOUT_MS`,
+/// `SOVA_RETRY_DELAY_MS` and `SOVA_HEALTH_CHECK_INTERVAL_MS`.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SovaConfig {
+    #[serde(default)]
+    pub default_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, ProfileConfig>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProfileConfig {
+    /// Preset endpoint used when `endpoints` is empty.
+    pub network: Option<Network>,
+    pub endpoints: Vec<EndpointConfig>,
+    pub key: Option<KeySource>,
+    pub request_timeout_ms: Option<u64>,
+    pub reconnect: ReconnectConfig,
+    pub subscriptions: Vec<SubscriptionConfig>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    Mainnet,
+    Testnet,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EndpointConfig {
+    pub url: String,
+    #[serde(default)]
+    pub tls: TlsConfig,
+}
+
+/// File form of [`TlsMode`]. Certificates and keys are referenced by path.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case", deny_unknown_fields)]
+pub enum TlsConfig {
+    #[default]
+    System,
+    Webpki,
+    LetsEncrypt,
+    CustomCa {
+        ca_file: PathBuf,
+    },
+    Mtls {
+        ca_file: Option<PathBuf>,
+        cert_file: PathBuf,
+        key_file: PathBuf,
+    },
+    Pinned {
+        ca_file: Option<PathBuf>,
+        spki_sha256: Vec<String>,
+    },
+    Plaintext,
+}
+
+/// Where the searcher's ed25519 key comes from.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeySource {
+    /// A file holding the 32-byte private key, raw or hex encoded.
+    File(PathBuf),
+    /// An environment variable holding the hex encoded private key.
+    Env(String),
+    /// A [`UnixSocketSigner`](crate::signer::UnixSocketSigner) daemon.
+    UnixSocket(PathBuf),
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReconnectConfig {
+    pub retry_delay_ms: Option<u64>,
+    pub probe_timeout_ms: Option<u64>,
+    pub health_check_interval_ms: Option<u64>,
+}
+
+/// File form of a mempool subscription. Shards are 8-byte prefixes, hex encoded.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum SubscriptionConfig {
+    Addresses {
+        addresses: Vec<String>,
+    },
+    Workchain {
+        workchain_id: i32,
+    },
+    WorkchainShard {
+        workchain_id: i32,
+        shard: String,
+    },
+    ExternalOutMessageBodyOpcode {
+        workchain_id: i32,
+        #[serde(default)]
+        shard: Option<String>,
+        opcode: u32,
+    },
+    InternalMessageBodyOpcode {
+        workchain_id: i32,
+        #[serde(default)]
+        shard: Option<String>,
+        opcode: u32,
+    },
+}
+
+/// A resolved profile, ready to build a [`SovaClient`](crate::client::SovaClient).
+#[derive(Clone, Debug)]
+pub struct Profile {
+    pub name: String,
+    /// The first endpoint is the primary one, the rest join it in a searcher pool.
+    pub endpoints: Vec<EngineEndpoint>,
+    pub key: Option<KeySource>,
+    /// Deadline for bundles and other unary calls, applied to searchers built from the profile.
+    pub request_timeout: Option<Duration>,
+    /// Settings for [`SovaClient::searcher_pool`](crate::client::SovaClient::searcher_pool),
+    /// including its request timeout and health checks.
+    pub pool: PoolConfig,
+    pub subscriptions: Vec<mempool_subscription::Subscription>,
+}
+
+impl Profile {
+    /// A profile with a single endpoint and default settings.
+    pub fn new(name: &str, endpoint: EngineEndpoint) -> Self {
+        Self {
+            name: name.to_owned(),
+            endpoints: vec![endpoint],
+            key: None,
+            request_timeout: None,
+            pool: PoolConfig::default(),
+            subscriptions: Vec::new(),
+        }
+    }
+
+    /// Call options carrying the profile's request timeout.
+    pub fn call_options(&self) -> CallOptions {
+        match self.request_timeout {
+            Some(timeout) => CallOptions::new().timeout(timeout),
+            None => CallOptions::new(),
+        }
+    }
+}
+
+impl SovaConfig {
+    /// Reads a config file, as JSON if its extension is `.json` and as TOML otherwise.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SovaError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|error| SovaError::Config(format!("reading {}: {error}", path.display())))?;
+
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            Self::from_json_str(&text)
+        } else {
+            Self::from_toml_str(&text)
+        }
+    }
+
+    pub fn from_toml_str(text: &str) -> Result<Self, SovaError> {
+        toml::from_str(text).map_err(|error| SovaError::Config(error.to_string()))
+    }
+
+    pub fn from_json_str(text: &str) -> Result<Self, SovaError> {
+        serde_json::from_str(text).map_err(|error| SovaError::Config(error.to_string()))
+    }
+
+    /// Resolves a profile with the process environment applied on top.
+    ///
+    /// The profile is `name`, else `SOVA_PROFILE`, else `default_profile`, else the only profile
+    /// in the file. A file without profiles yields one built from the environment alone.
+    pub fn profile(&self, name: Option<&str>) -> Result<Profile, SovaError> {
+        self.profile_with_env(name, |var| std::env::var(var).ok())
+    }
+
+    /// Like [`profile`](Self::profile), reading overrides from `env` instead of the process
+    /// environment.
+    pub fn profile_with_env(
+        &self,
+        name: Option<&str>,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Profile, SovaError> {
+        let name = name
+            .map(str::to_owned)
+            .or_else(|| env("SOVA_PROFILE"))
+            .or_else(|| self.default_profile.clone())
+            .or_else(|| match self.profiles.len() {
+                1 => self.profiles.keys().next().cloned(),
+                _ => None,
+            });
+
+        let (name, mut config) = match name {
+            Some(name) => {
+                let config = self.profiles.get(&name).cloned().ok_or_else(|| {
+                    SovaError::Config(format!("no profile named {name:?} in the config"))
+                })?;
+                (name, config)
+            }
+            None if self.profiles.is_empty() => ("env".to_owned(), ProfileConfig::default()),
+            None => {
+                return Err(SovaError::Config(
+                    "several profiles are defined; select one by name or with SOVA_PROFILE".into(),
+                ))
+            }
+        };
+
+        let tls = config.apply_env(&env)?;
+        config.resolve(name, tls)
+    }
+}
+
+impl ProfileConfig {
+    /// Applies the overrides to the file values. `SOVA_TLS` is returned instead, since it also
+    /// applies to the preset endpoints and can carry an inline CA bundle.
+    fn apply_env(
+        &mut self,
+        env: &impl Fn(&str) -> Option<String>,
+    ) -> Result<Option<TlsMode>, SovaError> {
+        if let Some(network) = env("SOVA_NETWORK") {
+            self.network = Some(match network.as_str() {
+                "mainnet" => Network::Mainnet,
+                "testnet" => Network::Testnet,
+                _ => return Err(invalid_env("SOVA_NETWORK", &network)),
+            });
+            self.endpoints.clear();
+        }
+
+        if let Some(url) = env("SOVA_URL") {
+            let tls = self
+                .endpoints
+                .first()
+                .map(|endpoint| endpoint.tls.clone())
+                .unwrap_or_default();
+            self.endpoints = vec![EndpointConfig { url, tls }];
+        }
+
+        let tls = env("SOVA_TLS")
+            .map(|mode| {
+                Ok(match mode.as_str() {
+                    "system" => TlsMode::SystemRoots,
+                    "webpki" => TlsMode::WebPkiRoots,
+                    "lets_encrypt" => TlsMode::lets_encrypt(),
+                    "plaintext" => TlsMode::InsecurePlaintext,
+                    "custom_ca" => env(CA_BUNDLE_ENV)
+                        .and_then(|bundle| TlsMode::from_ca_bundle(&bundle))
+                        .ok_or_else(|| {
+                            SovaError::Config(format!(
+                                "SOVA_TLS=custom_ca requires {CA_BUNDLE_ENV}"
+                            ))
+                        })?,
+                    _ => return Err(invalid_env("SOVA_TLS", &mode)),
+                })
+            })
+            .transpose()?;
+
+        if let Some(path) = env("SOVA_KEY_FILE") {
+            self.key = Some(KeySource::File(path.into()));
+        }
+        if env("SOVA_PRIVATE_KEY").is_some() {
+            self.key = Some(KeySource::Env("SOVA_PRIVATE_KEY".into()));
+        }
+        if let Some(path) = env("SOVA_SIGNER_SOCKET") {
+            self.key = Some(KeySource::UnixSocket(path.into()));
+        }
+
+        let millis = |var: &str| -> Result<Option<u64>, SovaError> {
+            env(var)
+                .map(|value| value.parse().map_err(|_| invalid_env(var, &value)))
+                .transpose()
+        };
+        if let Some(timeout) = millis("SOVA_REQUEST_TIMEOUT_MS")? {
+            self.request_timeout_ms = Some(timeout);
+        }
+        if let Some(timeout) = millis("SOVA_PROBE_TIMEOUT_MS")? {
+            self.reconnect.probe_timeout_ms = Some(timeout);
+        }
+        if let Some(delay) = millis("SOVA_RETRY_DELAY_MS")? {
+            self.reconnect.retry_delay_ms = Some(delay);
+        }
+        if let Some(interval) = millis("SOVA_HEALTH_CHECK_INTERVAL_MS")? {
+            self.reconnect.health_check_interval_ms = Some(interval);
+        }
+
+        Ok(tls)
+    }
+
+    /// `tls` replaces the TLS settings of every endpoint, presets included.
+    fn resolve(self, name: String, tls: Option<TlsMode>) -> Result<Profile, SovaError> {
+        let endpoints = if self.endpoints.is_empty() {
+            let mut preset = match self.network {
+                Some(Network::Mainnet) => EngineEndpoint::mainnet(),
+                Some(Network::Testnet) => EngineEndpoint::testnet(),
+                None => {
+                    return Err(SovaError::Config(format!(
+                        "profile {name:?} has neither endpoints nor a network"
+                    )))
+                }
+            };
+            if let Some(tls) = tls {
+                preset.tls = tls;
+            }
+            vec![preset]
+        } else {
+            self.endpoints
+                .into_iter()
+                .map(|endpoint| {
+                    let tls = match &tls {
+                        Some(tls) => tls.clone(),
+                        None => endpoint.tls.resolve()?,
+                    };
+                    Ok(EngineEndpoint::new(&endpoint.url, tls))
+                })
+                .collect::<Result<_, SovaError>>()?
+        };
+
+        let defaults = PoolConfig::default();
+        let request_timeout = self.request_timeout_ms.map(Duration::from_millis);
+
+        Ok(Profile {
+            name,
+            endpoints,
+            key: self.key,
+            request_timeout,
+            pool: PoolConfig {
+                probe_timeout: self
+                    .reconnect
+                    .probe_timeout_ms
+                    .map_or(defaults.probe_timeout, Duration::from_millis),
+                retry_delay: self
+                    .reconnect
+                    .retry_delay_ms
+                    .map_or(defaults.retry_delay, Duration::from_millis),
+                request_timeout,
+                health_check_interval: self
+                    .reconnect
+                    .health_check_interval_ms
+                    .map(Duration::from_millis),
+            },
+            subscriptions: self
+                .subscriptions
+                .iter()
+                .map(SubscriptionConfig::to_subscription)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl TlsConfig {
+    /// Reads the referenced files into a [`TlsMode`].
+    pub fn resolve(&self) -> Result<TlsMode, SovaError> {
+        Ok(match self {
+            Self::System => TlsMode::SystemRoots,
+            Self::Webpki => TlsMode::WebPkiRoots,
+            Self::LetsEncrypt => TlsMode::lets_encrypt(),
+            Self::CustomCa { ca_file } => TlsMode::ca_bundle_file(ca_file),
+            Self::Mtls {
+                ca_file,
+                cert_file,
+                key_file,
+            } => TlsMode::MutualTls {
+                ca_pem: ca_file.as_deref().map(read_file).transpose()?,
+                cert_pem: read_file(cert_file)?,
+                key_pem: read_file(key_file)?,
+            },
+            Self::Pinned {
+                ca_file,
+                spki_sha256,
+            } => TlsMode::Pinned {
+                ca_pem: ca_file.as_deref().map(read_file).transpose()?,
+                spki_sha256: spki_sha256.clone(),
+            },
+            Self::Plaintext => TlsMode::InsecurePlaintext,
+        })
+    }
+}
+
+impl KeySource {
+    /// Loads the private key. Fails for [`UnixSocket`](Self::UnixSocket), whose key never
+    /// leaves the daemon.
+    pub fn private_key(&self) -> Result<[u8; 32], SovaError> {
+        match self {
+            Self::File(path) => {
+                let bytes = std::fs::read(path).map_err(|error| {
+                    SovaError::Config(format!("reading key {}: {error}", path.display()))
+                })?;
+
+                match <[u8; 32]>::try_from(bytes.as_slice()) {
+                    Ok(key) => Ok(key),
+                    Err(_) => decode_hex_key(String::from_utf8_lossy(&bytes).trim()),
+                }
+            }
+            Self::Env(var) => {
+                let value = std::env::var(var).map_err(|_| {
+                    SovaError::Config(format!("environment variable {var} is not set"))
+                })?;
+                decode_hex_key(value.trim())
+            }
+            Self::UnixSocket(_) => Err(SovaError::Config(
+                "a unix socket signer does not expose its private key".into(),
+            )),
+        }
+    }
+}
+
+impl SubscriptionConfig {
+    pub fn to_subscription(&self) -> Result<mempool_subscription::Subscription, SovaError> {
+        Ok(match self {
+            Self::Addresses { addresses } => {
+                mempool_subscription::Subscription::Addresses(AddressSubscriptionV0 {
+                    address: addresses.clone(),
+                })
+            }
+            Self::Workchain { workchain_id } => {
+                mempool_subscription::Subscription::Workchain(WorkchainSubscriptionV0 {
+                    workchain_id: *workchain_id,
+                })
+            }
+            Self::WorkchainShard {
+                workchain_id,
+                shard,
+            } => mempool_subscription::Subscription::WorkchainShard(WorkchainShardSubscriptionV0 {
+                workchain_id: *workchain_id,
+                shard: decode_shard(shard)?,
+            }),
+            Self::ExternalOutMessageBodyOpcode {
+                workchain_id,
+                shard,
+                opcode,
+            } => mempool_subscription::Subscription::ExternalOutMessageBodyOpcode(
+                ExternalOutMessageBodyOpcodeSubscriptionV0 {
+                    workchain_id: *workchain_id,
+                    shard: shard.as_deref().map(decode_shard).transpose()?,
+                    opcode: *opcode as i32,
+                },
+            ),
+            Self::InternalMessageBodyOpcode {
+                workchain_id,
+                shard,
+                opcode,
+            } => mempool_subscription::Subscription::InternalMessageBodyOpcode(
+                InternalMessageBodyOpcodeSubscriptionV0 {
+                    workchain_id: *workchain_id,
+                    shard: shard.as_deref().map(decode_shard).transpose()?,
+                    opcode: *opcode as i32,
+                },
+            ),
+        })
+    }
+}
+
+fn read_file(path: &Path) -> Result<String, SovaError> {
+    std::fs::read_to_string(path)
+        .map_err(|error| SovaError::Config(format!("reading {}: {error}", path.display())))
+}
+
+fn decode_hex_key(hex_key: &str) -> Result<[u8; 32], SovaError> {
+    let mut key = [0u8; 32];
+    hex::decode_to_slice(hex_key, &mut key)
+        .map_err(|_| SovaError::Config("private key must be 32 bytes, raw or hex".into()))?;
+    Ok(key)
+}
+
+/// A 64-bit shard prefix, as 16 hex digits.
+fn decode_shard(shard: &str) -> Result<Vec<u8>, SovaError> {
+    let mut prefix = [0u8; 8];
+    hex::decode_to_slice(shard.trim_start_matches("0x"), &mut prefix)
+        .map_err(|_| SovaError::Config(format!("shard {shard:?} must be 8 bytes of hex")))?;
+
+    Ok(prefix.to_vec())
+}
+
+fn invalid_env(var: &str, value: &str) -> SovaError {
+    SovaError::Config(format!("invalid value {value:?} for {var}"))
+}
@@ -0,0 +1,402 @@
+//! Bounded buffering between a subscription's stream reader and its callback.
+//!
+//! Without a [`DeliveryConfig`] the callback runs inline in the receive loop, so a slow callback
+//! stops the stream from being read. With one, messages are queued for a separate task and the
+//! [`OverflowPolicy`] decides what happens when the queue is full. [`Concurrency`] sets how many
+//! async callbacks run at once.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::{Notify, Semaphore};
+use tokio::task::JoinHandle;
+use tracing::Instrument;
+
+use crate::call_options::CallOptions;
+use crate::error::SovaError;
+use crate::telemetry;
+
+/// What to do with a new message when the delivery buffer is full.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OverflowPolicy {
+    /// Stop reading the stream until the callback catches up.
+    Block,
+    /// Discard the oldest queued message to make room.
+    DropOldest,
+    /// Discard the new message.
+    DropNewest,
+    /// Close the subscription. Queued messages are still delivered.
+    Disconnect,
+}
+
+impl OverflowPolicy {
+    /// Stable snake_case name, e.g. for metric labels.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Block => "block",
+            Self::DropOldest => "drop_oldest",
+            Self::DropNewest => "drop_newest",
+            Self::Disconnect => "disconnect",
+        }
+    }
+}
+
+/// Buffer size and overflow policy for a subscription, set with
+/// [`CallOptions::delivery`](crate::call_options::CallOptions::delivery).
+///
+/// Clones share their [`DeliveryStats`], so keep one to read the counters after subscribing.
+#[derive(Clone, Debug)]
+pub struct DeliveryConfig {
+    capacity: usize,
+    policy: OverflowPolicy,
+    stats: DeliveryStats,
+}
+
+impl DeliveryConfig {
+    /// Queues up to `capacity` messages (at least one).
+    pub fn new(capacity: usize, policy: OverflowPolicy) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            policy,
+            stats: DeliveryStats::default(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn policy(&self) -> OverflowPolicy {
+        self.policy
+    }
+
+    pub fn stats(&self) -> &DeliveryStats {
+        &self.stats
+    }
+}
+
+/// Delivery counters, shared by every subscription started with the same [`DeliveryConfig`].
+#[derive(Clone, Default)]
+pub struct DeliveryStats {
+    inner: Arc<Counters>,
+}
+
+#[derive(Default)]
+struct Counters {
+    delivered: AtomicU64,
+    blocked: AtomicU64,
+    dropped_oldest: AtomicU64,
+    dropped_newest: AtomicU64,
+    disconnects: AtomicU64,
+}
+
+impl DeliveryStats {
+    /// Messages the callback has taken: finished for [`Concurrency::Sequential`], started
+    /// otherwise.
+    pub fn delivered(&self) -> u64 {
+        self.inner.delivered.load(Ordering::Relaxed)
+    }
+
+    /// Times the reader waited for space under [`OverflowPolicy::Block`].
+    pub fn blocked(&self) -> u64 {
+        self.inner.blocked.load(Ordering::Relaxed)
+    }
+
+    pub fn dropped_oldest(&self) -> u64 {
+        self.inner.dropped_oldest.load(Ordering::Relaxed)
+    }
+
+    pub fn dropped_newest(&self) -> u64 {
+        self.inner.dropped_newest.load(Ordering::Relaxed)
+    }
+
+    /// Messages discarded under either drop policy.
+    pub fn dropped(&self) -> u64 {
+        self.dropped_oldest() + self.dropped_newest()
+    }
+
+    /// Subscriptions closed under [`OverflowPolicy::Disconnect`].
+    pub fn disconnects(&self) -> u64 {
+        self.inner.disconnects.load(Ordering::Relaxed)
+    }
+}
+
+impl fmt::Debug for DeliveryStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeliveryStats")
+            .field("delivered", &self.delivered())
+            .field("blocked", &self.blocked())
+            .field("dropped_oldest", &self.dropped_oldest())
+            .field("dropped_newest", &self.dropped_newest())
+            .field("disconnects", &self.disconnects())
+            .finish()
+    }
+}
+
+struct QueueState<T> {
+    items: VecDeque<T>,
+    closed: bool,
+}
+
+struct Queue<T> {
+    state: Mutex<QueueState<T>>,
+    item_ready: Notify,
+    space_ready: Notify,
+}
+
+/// How many invocations of an async subscription callback may run at once.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Concurrency {
+    /// Await each callback before starting the next, preserving message order.
+    #[default]
+    Sequential,
+    /// Run up to this many callbacks concurrently, each in its own task.
+    Bounded(usize),
+    /// Run every callback in its own task as soon as its message arrives.
+    Unbounded,
+}
+
+/// Runs a subscription callback according to its [`Concurrency`].
+pub(crate) struct Handler<F> {
+    on_data: F,
+    /// Limits concurrent callbacks, and lets [`finish`](Self::finish) wait for them. `None` for
+    /// sequential callbacks.
+    permits: Option<(Arc<Semaphore>, u32)>,
+}
+
+impl<F> Handler<F> {
+    pub(crate) fn new(concurrency: Concurrency, on_data: F) -> Self {
+        let limit = match concurrency {
+            Concurrency::Sequential => None,
+            Concurrency::Bounded(limit) => Some(limit.clamp(1, u32::MAX as usize)),
+            Concurrency::Unbounded => Some(Semaphore::MAX_PERMITS.min(u32::MAX as usize)),
+        };
+
+        Self {
+            on_data,
+            permits: limit.map(|limit| (Arc::new(Semaphore::new(limit)), limit as u32)),
+        }
+    }
+
+    /// Waits for callbacks running in their own tasks.
+    async fn finish(self) {
+        if let Some((permits, limit)) = self.permits {
+            let _ = permits.acquire_many(limit).await;
+        }
+    }
+
+    /// Returns once the callback has finished, or once it has started in its own task.
+    async fn call<T, Fut>(&mut self, item: T)
+    where
+        F: FnMut(T) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        match &self.permits {
+            Some((permits, _)) => {
+                let permit = Arc::clone(permits).acquire_owned().await;
+                let callback = (self.on_data)(item);
+
+                tokio::spawn(
+                    async move {
+                        callback.await;
+                        drop(permit);
+                    }
+                    .in_current_span(),
+                );
+            }
+            None => (self.on_data)(item).await,
+        }
+    }
+}
+
+/// Hands messages to a callback, inline or through a delivery buffer.
+pub(crate) enum Delivery<T, F> {
+    Inline(Handler<F>),
+    Buffered(DeliverySender<T>),
+}
+
+impl<T, F, Fut> Delivery<T, F>
+where
+    T: Send + 'static,
+    F: FnMut(T) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    pub(crate) fn new(
+        config: Option<DeliveryConfig>,
+        kind: &'static str,
+        handler: Handler<F>,
+    ) -> Self {
+        match config {
+            Some(config) => Self::Buffered(DeliverySender::spawn(config, kind, handler)),
+            None => Self::Inline(handler),
+        }
+    }
+
+    /// Returns `Ok(false)` when the subscription should disconnect. Cancelling `options` stops
+    /// waiting for buffer space, but a callback that has started is allowed to finish.
+    pub(crate) async fn deliver(
+        &mut self,
+        item: T,
+        options: &CallOptions,
+    ) -> Result<bool, SovaError> {
+        match self {
+            Self::Inline(handler) => {
+                handler.call(item).await;
+                Ok(true)
+            }
+            Self::Buffered(sender) => options.run(sender.send(item)).await,
+        }
+    }
+
+    /// Waits until every delivered and queued message has been handled.
+    pub(crate) async fn finish(self) {
+        match self {
+            Self::Inline(handler) => handler.finish().await,
+            Self::Buffered(mut sender) => {
+                sender.close();
+                if let Some(consumer) = sender.consumer.take() {
+                    let _ = consumer.await;
+                }
+            }
+        }
+    }
+}
+
+/// Producer side of a delivery buffer. Dropping it lets the consumer drain the queue and exit.
+pub(crate) struct DeliverySender<T> {
+    queue: Arc<Queue<T>>,
+    config: DeliveryConfig,
+    kind: &'static str,
+    consumer: Option<JoinHandle<()>>,
+}
+
+impl<T: Send + 'static> DeliverySender<T> {
+    /// Starts a task in the current span that passes queued messages to `handler`.
+    fn spawn<F, Fut>(config: DeliveryConfig, kind: &'static str, mut handler: Handler<F>) -> Self
+    where
+        F: FnMut(T) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let queue = Arc::new(Queue {
+            state: Mutex::new(QueueState {
+                items: VecDeque::with_capacity(config.capacity),
+                closed: false,
+            }),
+            item_ready: Notify::new(),
+            space_ready: Notify::new(),
+        });
+
+        let consumer = Arc::clone(&queue);
+        let stats = config.stats.clone();
+        let consumer = tokio::spawn(
+            async move {
+                while let Some(item) = consumer.recv().await {
+                    handler.call(item).await;
+                    stats.inner.delivered.fetch_add(1, Ordering::Relaxed);
+                }
+                handler.finish().await;
+            }
+            .in_current_span(),
+        );
+
+        Self {
+            queue,
+            config,
+            kind,
+            consumer: Some(consumer),
+        }
+    }
+
+    /// Queues `item`, applying the overflow policy when full. Returns `false` when the
+    /// subscription should disconnect.
+    async fn send(&self, item: T) -> bool {
+        let counters = &self.config.stats.inner;
+        let mut waited = false;
+
+        loop {
+            let space_ready = self.queue.space_ready.notified();
+
+            {
+                let mut state = self.queue.state.lock().unwrap();
+
+                if state.items.len() < self.config.capacity {
+                    state.items.push_back(item);
+                    self.queue.item_ready.notify_one();
+                    return true;
+                }
+
+                match self.config.policy {
+                    OverflowPolicy::Block => {
+                        if !waited {
+                            waited = true;
+                            counters.blocked.fetch_add(1, Ordering::Relaxed);
+                            telemetry::delivery_overflow(self.kind, OverflowPolicy::Block);
+                        }
+                    }
+                    OverflowPolicy::DropOldest => {
+                        state.items.pop_front();
+                        state.items.push_back(item);
+                        counters.dropped_oldest.fetch_add(1, Ordering::Relaxed);
+                        telemetry::delivery_overflow(self.kind, OverflowPolicy::DropOldest);
+                        tracing::debug!("delivery buffer full, dropped the oldest message");
+                        return true;
+                    }
+                    OverflowPolicy::DropNewest => {
+                        counters.dropped_newest.fetch_add(1, Ordering::Relaxed);
+                        telemetry::delivery_overflow(self.kind, OverflowPolicy::DropNewest);
+                        tracing::debug!("delivery buffer full, dropped the newest message");
+                        return true;
+                    }
+                    OverflowPolicy::Disconnect => {
+                        counters.disconnects.fetch_add(1, Ordering::Relaxed);
+                        telemetry::delivery_overflow(self.kind, OverflowPolicy::Disconnect);
+                        tracing::warn!(
+                            capacity = self.config.capacity,
+                            "delivery buffer full, disconnecting"
+                        );
+                        return false;
+                    }
+                }
+            }
+
+            space_ready.await;
+        }
+    }
+}
+
+impl<T> DeliverySender<T> {
+    fn close(&self) {
+        self.queue.state.lock().unwrap().closed = true;
+        self.queue.item_ready.notify_one();
+    }
+}
+
+impl<T> Drop for DeliverySender<T> {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+impl<T> Queue<T> {
+    async fn recv(&self) -> Option<T> {
+        loop {
+            let item_ready = self.item_ready.notified();
+
+            {
+                let mut state = self.state.lock().unwrap();
+                if let Some(item) = state.items.pop_front() {
+                    self.space_ready.notify_one();
+                    return Some(item);
+                }
+                if state.closed {
+                    return None;
+                }
+            }
+
+            item_ready.await;
+        }
+    }
+}
@@ -1,7 +1,40 @@
 use thiserror::Error;
 
+use crate::subscription::EndReason;
+
 #[derive(Error, Debug)]
 pub enum SovaError {
     #[error("Authentication is required.")]
     AuthenticationRequired,
+    #[error("Challenge signing failed: {0}")]
+    Signer(#[source] Box<dyn std::error::Error + Send + Sync>),
+    #[error("Remote signer error: {0}")]
+    RemoteSigner(String),
+    #[error("Signature does not match the signer public key.")]
+    InvalidSignature,
+    #[error("Invalid bundle signature: {0}")]
+    InvalidBundleSignature(String),
+    #[error("Invalid metadata entry: {0}")]
+    InvalidMetadata(String),
+    #[error("The call was cancelled.")]
+    Cancelled,
+    #[error("TLS configuration error: {0}")]
+    Tls(String),
+    #[error("No healthy endpoint is available.")]
+    NoHealthyEndpoint,
+    #[error("Configuration error: {0}")]
+    Config(String),
+    #[error("Invalid message from the engine: {0}")]
+    InvalidMessage(String),
+    #[error("Invalid bag of cells: {0}")]
+    InvalidBoc(String),
+    #[error("Invalid address: {0}")]
+    InvalidAddress(String),
+    #[error("{0} subscription(s) did not stop before the shutdown deadline.")]
+    ShutdownTimedOut(usize),
+    #[error("The {kind} subscription ended: {reason:?}")]
+    SubscriptionEnded {
+        kind: &'static str,
+        reason: EndReason,
+    },
 }
@@ -0,0 +1,71 @@
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+
+use tonic::metadata::MetadataValue;
+use tonic::service::interceptor::InterceptedService;
+use tonic::service::Interceptor;
+use tonic::transport::Channel;
+use tonic::{Request, Status};
+
+use crate::types::AccessToken;
+
+/// Channel type used by clients wrapped in an [`AuthInterceptor`].
+pub type AuthChannel = InterceptedService<Channel, AuthInterceptor>;
+
+/// Access token shared between [`SovaAuth`](crate::auth::SovaAuth) and every client that
+/// authenticates with it. Clones point at the same token, so a refresh is picked up by all of
+/// them on their next call.
+#[derive(Clone, Debug, Default)]
+pub struct TokenSource {
+    token: Arc<RwLock<Option<AccessToken>>>,
+}
+
+impl TokenSource {
+    pub fn new(token: Option<AccessToken>) -> Self {
+        Self {
+            token: Arc::new(RwLock::new(token)),
+        }
+    }
+
+    pub fn get(&self) -> Option<AccessToken> {
+        self.token.read().unwrap().clone()
+    }
+
+    pub fn set(&self, token: Option<AccessToken>) {
+        *self.token.write().unwrap() = token;
+    }
+}
+
+/// Adds the `authorization: Bearer` header from a [`TokenSource`] to every outgoing request.
+///
+/// ```ignore
+/// let interceptor = AuthInterceptor::new(auth.token_source());
+/// let client = SearcherServiceClient::with_interceptor(channel, interceptor);
+/// ```
+#[derive(Clone, Debug)]
+pub struct AuthInterceptor {
+    tokens: TokenSource,
+}
+
+impl AuthInterceptor {
+    pub fn new(tokens: TokenSource) -> Self {
+        Self { tokens }
+    }
+
+    pub fn token_source(&self) -> &TokenSource {
+        &self.tokens
+    }
+}
+
+impl Interceptor for AuthInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        if let Some(access_token) = self.tokens.get() {
+            let value = MetadataValue::from_str(&format!("Bearer {}", access_token.value()))
+                .map_err(|_| Status::unauthenticated("access token is not valid metadata"))?;
+
+            request.metadata_mut().insert("authorization", value);
+        }
+
+        Ok(request)
+    }
+}
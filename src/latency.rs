@@ -0,0 +1,189 @@
+//! Receive timestamps and rolling one-way latency percentiles for mempool subscriptions.
+//!
+//! Every [`MempoolEvent`] from a decoded subscription carries its monotonic and wall-clock
+//! [receive time](ReceivedAt), and raw subscriptions pass it along with each packet. The one-way
+//! latency from the engine's `server_ts` is kept as rolling percentiles in [`LatencyStats`], and
+//! exported as `sova_mempool_latency_seconds` with the `metrics` feature.
+//!
+//! Samples are kept per subscription, identified by its [`SubscriptionId`], and also summed up
+//! per subscription kind, the same label the metrics use.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::subscription::SubscriptionId;
+use crate::telemetry;
+use crate::types::MempoolEvent;
+
+/// Samples kept per window by [`LatencyStats::default`].
+pub const DEFAULT_WINDOW: usize = 1024;
+
+/// When a packet was read off the stream, before it was decoded or handed to the callback.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReceivedAt {
+    /// Monotonic time, for measuring how long the packet waited locally.
+    pub instant: Instant,
+    /// Wall-clock time, comparable with the engine's `server_ts`.
+    pub wall: SystemTime,
+}
+
+impl ReceivedAt {
+    pub fn now() -> Self {
+        Self {
+            instant: Instant::now(),
+            wall: SystemTime::now(),
+        }
+    }
+}
+
+/// One-way latency percentiles over the most recent samples of a subscription.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LatencySummary {
+    pub samples: usize,
+    pub min: Duration,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+impl LatencySummary {
+    fn from_samples(samples: &VecDeque<Duration>) -> Option<Self> {
+        let mut sorted = samples.iter().copied().collect::<Vec<_>>();
+        sorted.sort_unstable();
+
+        // Nearest-rank percentile.
+        let percentile = |p: usize| sorted[(sorted.len() * p).div_ceil(100).max(1) - 1];
+
+        Some(Self {
+            samples: sorted.len(),
+            min: *sorted.first()?,
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            max: *sorted.last()?,
+        })
+    }
+}
+
+/// Rolling windows of one-way latencies, one per subscription and one per subscription kind
+/// (e.g. `mempool_addresses`) across its subscriptions. Windows of ended subscriptions are kept
+/// until they are [removed](Self::remove). Clones share the same samples.
+#[derive(Clone, Debug)]
+pub struct LatencyStats {
+    window: usize,
+    samples: Arc<Mutex<Samples>>,
+}
+
+#[derive(Debug, Default)]
+struct Samples {
+    subscriptions: BTreeMap<SubscriptionId, VecDeque<Duration>>,
+    kinds: BTreeMap<String, VecDeque<Duration>>,
+}
+
+impl Default for LatencyStats {
+    fn default() -> Self {
+        Self::new(DEFAULT_WINDOW)
+    }
+}
+
+impl LatencyStats {
+    /// Keeps the last `window` samples per subscription and per kind.
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: window.max(1),
+            samples: Arc::default(),
+        }
+    }
+
+    pub fn record(&self, subscription: SubscriptionId, kind: &str, latency: Duration) {
+        let mut samples = self.samples.lock().unwrap();
+        let window = self.window;
+        let push = |samples: &mut VecDeque<Duration>| {
+            if samples.len() == window {
+                samples.pop_front();
+            }
+            samples.push_back(latency);
+        };
+
+        push(samples.subscriptions.entry(subscription).or_default());
+        push(samples.kinds.entry(kind.to_owned()).or_default());
+    }
+
+    pub fn summary(&self, subscription: SubscriptionId) -> Option<LatencySummary> {
+        LatencySummary::from_samples(
+            self.samples
+                .lock()
+                .unwrap()
+                .subscriptions
+                .get(&subscription)?,
+        )
+    }
+
+    pub fn summaries(&self) -> BTreeMap<SubscriptionId, LatencySummary> {
+        summaries(&self.samples.lock().unwrap().subscriptions)
+    }
+
+    /// The most recent samples of every subscription of `kind`.
+    pub fn kind_summary(&self, kind: &str) -> Option<LatencySummary> {
+        LatencySummary::from_samples(self.samples.lock().unwrap().kinds.get(kind)?)
+    }
+
+    pub fn kind_summaries(&self) -> BTreeMap<String, LatencySummary> {
+        summaries(&self.samples.lock().unwrap().kinds)
+    }
+
+    /// Drops the window of `subscription`, e.g. once it has ended. Its samples stay in its kind.
+    pub fn remove(&self, subscription: SubscriptionId) {
+        self.samples
+            .lock()
+            .unwrap()
+            .subscriptions
+            .remove(&subscription);
+    }
+
+    pub fn clear(&self) {
+        let mut samples = self.samples.lock().unwrap();
+        samples.subscriptions.clear();
+        samples.kinds.clear();
+    }
+
+    /// Stamps `event` with its receive time and records its one-way latency, if it has one.
+    pub(crate) fn observe(
+        &self,
+        subscription: SubscriptionId,
+        kind: &'static str,
+        event: &mut MempoolEvent,
+        received: ReceivedAt,
+    ) {
+        event.received = Some(received);
+        self.observe_packet(subscription, kind, event.server_time, received);
+    }
+
+    /// Records the one-way latency of a packet sent at `server_time`, if it has one.
+    pub(crate) fn observe_packet(
+        &self,
+        subscription: SubscriptionId,
+        kind: &'static str,
+        server_time: Option<SystemTime>,
+        received: ReceivedAt,
+    ) {
+        let latency =
+            server_time.and_then(|server_time| received.wall.duration_since(server_time).ok());
+
+        if let Some(latency) = latency {
+            self.record(subscription, kind, latency);
+            telemetry::mempool_latency(kind, latency);
+        }
+    }
+}
+
+fn summaries<K: Clone + Ord>(
+    windows: &BTreeMap<K, VecDeque<Duration>>,
+) -> BTreeMap<K, LatencySummary> {
+    windows
+        .iter()
+        .filter_map(|(key, samples)| Some((key.clone(), LatencySummary::from_samples(samples)?)))
+        .collect()
+}
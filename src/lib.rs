@@ -1,7 +1,28 @@
 pub mod auth;
 pub mod block_engine;
+pub mod boc;
+pub mod bundle_signing;
+pub mod bundle_stats;
+pub mod call_options;
 pub mod client;
+pub mod config;
+pub mod delivery;
 pub mod error;
+pub mod interceptor;
+pub mod latency;
 mod pem;
+pub mod pool;
 pub mod proto;
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub mod proto_serde;
+pub mod resubmit;
 pub mod searcher;
+pub mod shard;
+pub mod signer;
+pub mod strategy;
+pub mod subscription;
+pub mod telemetry;
+pub mod tip_advisor;
+pub mod tls;
+pub mod types;
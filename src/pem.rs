@@ -1,179 +1,51 @@
-pub const MAINNET_CA_PEM: &str = "-----BEGIN CERTIFICATE-----
-MIIFJjCCBA6gAwIBAgISBRp/GN0E7YIKXVMVHXSkkNiGMA0GCSqGSIb3DQEBCwUA
-MDMxCzAJBgNVBAYTAlVTMRYwFAYDVQQKEw1MZXQncyBFbmNyeXB0MQwwCgYDVQQD
-EwNSMTAwHhcNMjUwNDI3MjExMjUyWhcNMjUwNzI2MjExMjUxWjAeMRwwGgYDVQQD
-ExNlbmdpbmUuc292YS5uZXR3b3JrMIIBIjANBgkqhkiG9w0BAQEFAAOCAQ8AMIIB
-CgKCAQEA21zNhtXpJ01haX+BzUZROgXncLWrlX6Agss+IxI/sJrHJS6dt3WMDPmD
-KOVK4ddazQKx1SSCjb+BBloEraTT0x6uxonzWX85QpLMxoSv3ZjL4Jk9Wnm4Om11
-etPg3pWqjX4LsqbiNSpJVLuTlcbK9f5kZIVUTG3ob1DtYjzLvjSFqLi4f4mA3Fin
-xkj6NXHghE8GgxtGxQlA1V+bhaZ/H43KkqOGIhgL5VBfDHKA04JTudxkb9NfU1KG
-1gz3Nqsn/ycNPprwhDDSkNQGyHt0tDbcIC30pCkMDjuuW/jS6P5lUR2fVrGbzmgk
-b2ruWxU8iYib7iBC0t7xSEjeCDiFwwIDAQABo4ICRzCCAkMwDgYDVR0PAQH/BAQD
-AgWgMB0GA1UdJQQWMBQGCCsGAQUFBwMBBggrBgEFBQcDAjAMBgNVHRMBAf8EAjAA
-MB0GA1UdDgQWBBQ7DKJ5cjK9PC9ZgDNJajSiYcZQiDAfBgNVHSMEGDAWgBS7vMNH
-peS8qcbDpHIMEI2iNeHI6DBXBggrBgEFBQcBAQRLMEkwIgYIKwYBBQUHMAGGFmh0
-dHA6Ly9yMTAuby5sZW5jci5vcmcwIwYIKwYBBQUHMAKGF2h0dHA6Ly9yMTAuaS5s
-ZW5jci5vcmcvMB4GA1UdEQQXMBWCE2VuZ2luZS5zb3ZhLm5ldHdvcmswEwYDVR0g
-BAwwCjAIBgZngQwBAgEwLQYDVR0fBCYwJDAioCCgHoYcaHR0cDovL3IxMC5jLmxl
-bmNyLm9yZy83LmNybDCCAQUGCisGAQQB1nkCBAIEgfYEgfMA8QB2AA3h8jAr0w3B
-QGISCepVLvxHdHyx1+kw7w5CHrR+Tqo0AAABlnlOFKkAAAQDAEcwRQIgRfTT7wKI
-f+W+Vv8gPghPi7B6wEF66iILsEqpWY0aFU4CIQDtKrYqBkrDo74JOqhTxThMTepo
-xvPhqHNRnJQCnskkQQB3AN3cyjSV1+EWBeeVMvrHn/g9HFDf2wA6FBJ2Ciysu8gq
-AAABlnlOFOwAAAQDAEgwRgIhAIC1CPt8oGYvOtnVwYrtTU+gyJiPcrkVhQJ8ONM5
-4yiYAiEAhbbuorugEEAbX/Ny7WzOH0ej/NyZ36E+SmZt3sSLfm8wDQYJKoZIhvcN
-AQELBQADggEBAFX/3yYcycPAAdjPvbVblty7pDnnFN4aordqt/s67pDrQMP1T/Yk
-6aLZrDPq/mPsCjzPbjDM6RRSH9We3FjFTQe/D/WYQ8PNWB1ZaOit15eDwZmBZlun
-bdNoWAhtF8p39OWHl1UwJx0ZQlLoiWmf6irKLjmGMDeWIeKMIBPngszTX0xdacfe
-vII/CFcgAvG4M1CJHbU48l6qbGFqEmINEbXZDIBqhFsyRsDwSehdxZonsWzGDzza
-ck5EE34Cq8HBY2fK6z1St1YWtSAg08KCnzNj3buL9atCnCkxO7SBSfaT43O8/gkB
-LRryOljSvaJJLIpRD7xdb/Qtdpy2CnYvA2s=
------END CERTIFICATE-----
------BEGIN CERTIFICATE-----
-MIIFBTCCAu2gAwIBAgIQS6hSk/eaL6JzBkuoBI110DANBgkqhkiG9w0BAQsFADBP
-MQswCQYDVQQGEwJVUzEpMCcGA1UEChMgSW50ZXJuZXQgU2VjdXJpdHkgUmVzZWFy
-Y2ggR3JvdXAxFTATBgNVBAMTDElTUkcgUm9vdCBYMTAeFw0yNDAzMTMwMDAwMDBa
-Fw0yNzAzMTIyMzU5NTlaMDMxCzAJBgNVBAYTAlVTMRYwFAYDVQQKEw1MZXQncyBF
-bmNyeXB0MQwwCgYDVQQDEwNSMTAwggEiMA0GCSqGSIb3DQEBAQUAA4IBDwAwggEK
-AoIBAQDPV+XmxFQS7bRH/sknWHZGUCiMHT6I3wWd1bUYKb3dtVq/+vbOo76vACFL
-YlpaPAEvxVgD9on/jhFD68G14BQHlo9vH9fnuoE5CXVlt8KvGFs3Jijno/QHK20a
-/6tYvJWuQP/py1fEtVt/eA0YYbwX51TGu0mRzW4Y0YCF7qZlNrx06rxQTOr8IfM4
-FpOUurDTazgGzRYSespSdcitdrLCnF2YRVxvYXvGLe48E1KGAdlX5jgc3421H5KR
-mudKHMxFqHJV8LDmowfs/acbZp4/SItxhHFYyTr6717yW0QrPHTnj7JHwQdqzZq3
-DZb3EoEmUVQK7GH29/Xi8orIlQ2NAgMBAAGjgfgwgfUwDgYDVR0PAQH/BAQDAgGG
-MB0GA1UdJQQWMBQGCCsGAQUFBwMCBggrBgEFBQcDATASBgNVHRMBAf8ECDAGAQH/
-AgEAMB0GA1UdDgQWBBS7vMNHpeS8qcbDpHIMEI2iNeHI6DAfBgNVHSMEGDAWgBR5
-tFnme7bl5AFzgAiIyBpY9umbbjAyBggrBgEFBQcBAQQmMCQwIgYIKwYBBQUHMAKG
-Fmh0dHA6Ly94MS5pLmxlbmNyLm9yZy8wEwYDVR0gBAwwCjAIBgZngQwBAgEwJwYD
-VR0fBCAwHjAcoBqgGIYWaHR0cDovL3gxLmMubGVuY3Iub3JnLzANBgkqhkiG9w0B
-AQsFAAOCAgEAkrHnQTfreZ2B5s3iJeE6IOmQRJWjgVzPw139vaBw1bGWKCIL0vIo
-zwzn1OZDjCQiHcFCktEJr59L9MhwTyAWsVrdAfYf+B9haxQnsHKNY67u4s5Lzzfd
-u6PUzeetUK29v+PsPmI2cJkxp+iN3epi4hKu9ZzUPSwMqtCceb7qPVxEbpYxY1p9
-1n5PJKBLBX9eb9LU6l8zSxPWV7bK3lG4XaMJgnT9x3ies7msFtpKK5bDtotij/l0
-GaKeA97pb5uwD9KgWvaFXMIEt8jVTjLEvwRdvCn294GPDF08U8lAkIv7tghluaQh
-1QnlE4SEN4LOECj8dsIGJXpGUk3aU3KkJz9icKy+aUgA+2cP21uh6NcDIS3XyfaZ
-QjmDQ993ChII8SXWupQZVBiIpcWO4RqZk3lr7Bz5MUCwzDIA359e57SSq5CCkY0N
-4B6Vulk7LktfwrdGNVI5BsC9qqxSwSKgRJeZ9wygIaehbHFHFhcBaMDKpiZlBHyz
-rsnnlFXCb5s8HKn5LsUgGvB24L7sGNZP2CX7dhHov+YhD+jozLW2p9W4959Bz2Ei
-RmqDtmiXLnzqTpXbI+suyCsohKRg6Un0RC47+cpiVwHiXZAW+cn8eiNIjqbVgXLx
-KPpdzvvtTnOPlC7SQZSYmdunr3Bf9b77AiC/ZidstK36dRILKz7OA54=
------END CERTIFICATE-----
------BEGIN CERTIFICATE-----
-MIIFJjCCBA6gAwIBAgISBRp/GN0E7YIKXVMVHXSkkNiGMA0GCSqGSIb3DQEBCwUA
-MDMxCzAJBgNVBAYTAlVTMRYwFAYDVQQKEw1MZXQncyBFbmNyeXB0MQwwCgYDVQQD
-EwNSMTAwHhcNMjUwNDI3MjExMjUyWhcNMjUwNzI2MjExMjUxWjAeMRwwGgYDVQQD
-ExNlbmdpbmUuc292YS5uZXR3b3JrMIIBIjANBgkqhkiG9w0BAQEFAAOCAQ8AMIIB
-CgKCAQEA21zNhtXpJ01haX+BzUZROgXncLWrlX6Agss+IxI/sJrHJS6dt3WMDPmD
-KOVK4ddazQKx1SSCjb+BBloEraTT0x6uxonzWX85QpLMxoSv3ZjL4Jk9Wnm4Om11
-etPg3pWqjX4LsqbiNSpJVLuTlcbK9f5kZIVUTG3ob1DtYjzLvjSFqLi4f4mA3Fin
-xkj6NXHghE8GgxtGxQlA1V+bhaZ/H43KkqOGIhgL5VBfDHKA04JTudxkb9NfU1KG
-1gz3Nqsn/ycNPprwhDDSkNQGyHt0tDbcIC30pCkMDjuuW/jS6P5lUR2fVrGbzmgk
-b2ruWxU8iYib7iBC0t7xSEjeCDiFwwIDAQABo4ICRzCCAkMwDgYDVR0PAQH/BAQD
-AgWgMB0GA1UdJQQWMBQGCCsGAQUFBwMBBggrBgEFBQcDAjAMBgNVHRMBAf8EAjAA
-MB0GA1UdDgQWBBQ7DKJ5cjK9PC9ZgDNJajSiYcZQiDAfBgNVHSMEGDAWgBS7vMNH
-peS8qcbDpHIMEI2iNeHI6DBXBggrBgEFBQcBAQRLMEkwIgYIKwYBBQUHMAGGFmh0
-dHA6Ly9yMTAuby5sZW5jci5vcmcwIwYIKwYBBQUHMAKGF2h0dHA6Ly9yMTAuaS5s
-ZW5jci5vcmcvMB4GA1UdEQQXMBWCE2VuZ2luZS5zb3ZhLm5ldHdvcmswEwYDVR0g
-BAwwCjAIBgZngQwBAgEwLQYDVR0fBCYwJDAioCCgHoYcaHR0cDovL3IxMC5jLmxl
-bmNyLm9yZy83LmNybDCCAQUGCisGAQQB1nkCBAIEgfYEgfMA8QB2AA3h8jAr0w3B
-QGISCepVLvxHdHyx1+kw7w5CHrR+Tqo0AAABlnlOFKkAAAQDAEcwRQIgRfTT7wKI
-f+W+Vv8gPghPi7B6wEF66iILsEqpWY0aFU4CIQDtKrYqBkrDo74JOqhTxThMTepo
-xvPhqHNRnJQCnskkQQB3AN3cyjSV1+EWBeeVMvrHn/g9HFDf2wA6FBJ2Ciysu8gq
-AAABlnlOFOwAAAQDAEgwRgIhAIC1CPt8oGYvOtnVwYrtTU+gyJiPcrkVhQJ8ONM5
-4yiYAiEAhbbuorugEEAbX/Ny7WzOH0ej/NyZ36E+SmZt3sSLfm8wDQYJKoZIhvcN
-AQELBQADggEBAFX/3yYcycPAAdjPvbVblty7pDnnFN4aordqt/s67pDrQMP1T/Yk
-6aLZrDPq/mPsCjzPbjDM6RRSH9We3FjFTQe/D/WYQ8PNWB1ZaOit15eDwZmBZlun
-bdNoWAhtF8p39OWHl1UwJx0ZQlLoiWmf6irKLjmGMDeWIeKMIBPngszTX0xdacfe
-vII/CFcgAvG4M1CJHbU48l6qbGFqEmINEbXZDIBqhFsyRsDwSehdxZonsWzGDzza
-ck5EE34Cq8HBY2fK6z1St1YWtSAg08KCnzNj3buL9atCnCkxO7SBSfaT43O8/gkB
-LRryOljSvaJJLIpRD7xdb/Qtdpy2CnYvA2s=
------END CERTIFICATE-----";
+// Let's Encrypt roots. The engine endpoints are issued from these, so renewing their leaf and
+// intermediate certificates does not require an SDK release.
 
-pub const TESTNET_CA_PEM: &str = "-----BEGIN CERTIFICATE-----
-MIIFEjCCA/qgAwIBAgISBYky0t1xdN2ndgM/8Z8MSeyYMA0GCSqGSIb3DQEBCwUA
-MDMxCzAJBgNVBAYTAlVTMRYwFAYDVQQKEw1MZXQncyBFbmNyeXB0MQwwCgYDVQQD
-EwNSMTAwHhcNMjUwNzEzMjI0NjAyWhcNMjUxMDExMjI0NjAxWjAmMSQwIgYDVQQD
-Ext0ZXN0bmV0LWVuZ2luZS5zb3ZhLm5ldHdvcmswggEiMA0GCSqGSIb3DQEBAQUA
-A4IBDwAwggEKAoIBAQC+09nNUYL8a+8mnp84JbtLpghDvKpmLg0c1Plo6h9Y4JOz
-A60mKAGF+yHu7oAiraMpiOneGH35lYMn5we08G8MlwaYj2w6Z3Bu1yib5al/Eof7
-qiE/bQcRhvcdwssw3e3tsHW4Nr7F2rmeKgbL4kubH50DoriojRLjKHUscFFxqtjL
-f5vjBjN+vjdpiAj6KMcH4joTR9XfivuxLsnMKDD4pz8a6WTuWPAQnUdm5v3XMCBm
-5NRAlFjdsEn7p24hc9H4zYi6TMJIOeokbhNPwTflF0uj3dyzBUr8Jz30UdVdF+VS
-4pMB4HDMnj3QZnfP3+TCqpEJEy/16zSB8gq2tCyXAgMBAAGjggIrMIICJzAOBgNV
-HQ8BAf8EBAMCBaAwHQYDVR0lBBYwFAYIKwYBBQUHAwEGCCsGAQUFBwMCMAwGA1Ud
-EwEB/wQCMAAwHQYDVR0OBBYEFJauwpamDiVJSsBa6Agfx7GOBxq8MB8GA1UdIwQY
-MBaAFLu8w0el5LypxsOkcgwQjaI14cjoMDMGCCsGAQUFBwEBBCcwJTAjBggrBgEF
-BQcwAoYXaHR0cDovL3IxMC5pLmxlbmNyLm9yZy8wJgYDVR0RBB8wHYIbdGVzdG5l
-dC1lbmdpbmUuc292YS5uZXR3b3JrMBMGA1UdIAQMMAowCAYGZ4EMAQIBMC4GA1Ud
-HwQnMCUwI6AhoB+GHWh0dHA6Ly9yMTAuYy5sZW5jci5vcmcvMjUuY3JsMIIBBAYK
-KwYBBAHWeQIEAgSB9QSB8gDwAHYAzPsPaoVxCWX+lZtTzumyfCLphVwNl422qX5U
-wP5MDbAAAAGYBi0PVQAABAMARzBFAiEA0pdhhN/kaNJoldqJO/5T+5SwikT9dFeT
-72BvxF/3ByACIFVNCWNSHKybRiPJ41giUG83hboBOcMSPhCZ8HOGPqx8AHYA3dzK
-NJXX4RYF55Uy+sef+D0cUN/bADoUEnYKLKy7yCoAAAGYBi0PjwAABAMARzBFAiEA
-pbZ6g3gfLqBI+ZRppl+eDbDexo7rDQYGeadKnhGgt88CIHdo7YHo//8MiteN+8kI
-DPFEq7zOQJnFbAXW0h8/BIB6MA0GCSqGSIb3DQEBCwUAA4IBAQAnQ3OjX/3XWboz
-j+OQ/yoa2ITAo6Y75vvCMBYzQ2lyxVXEAE0K2eyklfDt+rAGT6Gy8v+BFxqBISZP
-TB3bIDcuUPEHDbe9lOfS9CTysNUQtRTpugmg9N/ErkHeW2seis4tKaRUmdKlq3CV
-RASV+snl/BllsvCqO/ikSqBX/JdReAyA6EoT7OewEesB5Ih2B5ghiDjZmRGeeevC
-VuKpTlNRTKVQ0s+DXj41VALARINH6Uwg/wbPQy8tDwTWtrDg6dBULKpEZ3BvHTvF
-419F+njSBPrcGBrLBJLOMSuU16QtMYkpqC8apPcpwBgLjDFOD2HSg1D2M8ioBSsE
-1IrEEVZN
+pub const ISRG_ROOT_X1_PEM: &str = "-----BEGIN CERTIFICATE-----
+MIIFazCCA1OgAwIBAgIRAIIQz7DSQONZRGPgu2OCiwAwDQYJKoZIhvcNAQELBQAw
+TzELMAkGA1UEBhMCVVMxKTAnBgNVBAoTIEludGVybmV0IFNlY3VyaXR5IFJlc2Vh
+cmNoIEdyb3VwMRUwEwYDVQQDEwxJU1JHIFJvb3QgWDEwHhcNMTUwNjA0MTEwNDM4
+WhcNMzUwNjA0MTEwNDM4WjBPMQswCQYDVQQGEwJVUzEpMCcGA1UEChMgSW50ZXJu
+ZXQgU2VjdXJpdHkgUmVzZWFyY2ggR3JvdXAxFTATBgNVBAMTDElTUkcgUm9vdCBY
+MTCCAiIwDQYJKoZIhvcNAQEBBQADggIPADCCAgoCggIBAK3oJHP0FDfzm54rVygc
+h77ct984kIxuPOZXoHj3dcKi/vVqbvYATyjb3miGbESTtrFj/RQSa78f0uoxmyF+
+0TM8ukj13Xnfs7j/EvEhmkvBioZxaUpmZmyPfjxwv60pIgbz5MDmgK7iS4+3mX6U
+A5/TR5d8mUgjU+g4rk8Kb4Mu0UlXjIB0ttov0DiNewNwIRt18jA8+o+u3dpjq+sW
+T8KOEUt+zwvo/7V3LvSye0rgTBIlDHCNAymg4VMk7BPZ7hm/ELNKjD+Jo2FR3qyH
+B5T0Y3HsLuJvW5iB4YlcNHlsdu87kGJ55tukmi8mxdAQ4Q7e2RCOFvu396j3x+UC
+B5iPNgiV5+I3lg02dZ77DnKxHZu8A/lJBdiB3QW0KtZB6awBdpUKD9jf1b0SHzUv
+KBds0pjBqAlkd25HN7rOrFleaJ1/ctaJxQZBKT5ZPt0m9STJEadao0xAH0ahmbWn
+OlFuhjuefXKnEgV4We0+UXgVCwOPjdAvBbI+e0ocS3MFEvzG6uBQE3xDk3SzynTn
+jh8BCNAw1FtxNrQHusEwMFxIt4I7mKZ9YIqioymCzLq9gwQbooMDQaHWBfEbwrbw
+qHyGO0aoSCqI3Haadr8faqU9GY/rOPNk3sgrDQoo//fb4hVC1CLQJ13hef4Y53CI
+rU7m2Ys6xt0nUW7/vGT1M0NPAgMBAAGjQjBAMA4GA1UdDwEB/wQEAwIBBjAPBgNV
+HRMBAf8EBTADAQH/MB0GA1UdDgQWBBR5tFnme7bl5AFzgAiIyBpY9umbbjANBgkq
+hkiG9w0BAQsFAAOCAgEAVR9YqbyyqFDQDLHYGmkgJykIrGF1XIpu+ILlaS/V9lZL
+ubhzEFnTIZd+50xx+7LSYK05qAvqFyFWhfFQDlnrzuBZ6brJFe+GnY+EgPbk6ZGQ
+3BebYhtF8GaV0nxvwuo77x/Py9auJ/GpsMiu/X1+mvoiBOv/2X/qkSsisRcOj/KK
+NFtY2PwByVS5uCbMiogziUwthDyC3+6WVwW6LLv3xLfHTjuCvjHIInNzktHCgKQ5
+ORAzI4JMPJ+GslWYHb4phowim57iaztXOoJwTdwJx4nLCgdNbOhdjsnvzqvHu7Ur
+TkXWStAmzOVyyghqpZXjFaH3pO3JLF+l+/+sKAIuvtd7u+Nxe5AW0wdeRlN8NwdC
+jNPElpzVmbUq4JUagEiuTDkHzsxHpFKVK7q4+63SM1N95R1NbdWhscdCb+ZAJzVc
+oyi3B43njTOQ5yOf+1CceWxG1bQVs5ZufpsMljq4Ui0/1lvh+wjChP4kqKOJ2qxq
+4RgqsahDYVvTH9w7jXbyLeiNdd8XM2w9U/t7y0Ff/9yi0GE44Za4rF2LN9d11TPA
+mRGunUHBcnWEvgJBQl9nJEiU0Zsnvgc/ubhPgXRR4Xq37Z0j4r7g1SgEEzwxA57d
+emyPxgcYxn/eR44/KJ4EBs+lVDR3veyJm+kXQ99b21/+jh5Xos1AnX5iItreGCc=
 -----END CERTIFICATE-----
------BEGIN CERTIFICATE-----
-MIIFBTCCAu2gAwIBAgIQS6hSk/eaL6JzBkuoBI110DANBgkqhkiG9w0BAQsFADBP
-MQswCQYDVQQGEwJVUzEpMCcGA1UEChMgSW50ZXJuZXQgU2VjdXJpdHkgUmVzZWFy
-Y2ggR3JvdXAxFTATBgNVBAMTDElTUkcgUm9vdCBYMTAeFw0yNDAzMTMwMDAwMDBa
-Fw0yNzAzMTIyMzU5NTlaMDMxCzAJBgNVBAYTAlVTMRYwFAYDVQQKEw1MZXQncyBF
-bmNyeXB0MQwwCgYDVQQDEwNSMTAwggEiMA0GCSqGSIb3DQEBAQUAA4IBDwAwggEK
-AoIBAQDPV+XmxFQS7bRH/sknWHZGUCiMHT6I3wWd1bUYKb3dtVq/+vbOo76vACFL
-YlpaPAEvxVgD9on/jhFD68G14BQHlo9vH9fnuoE5CXVlt8KvGFs3Jijno/QHK20a
-/6tYvJWuQP/py1fEtVt/eA0YYbwX51TGu0mRzW4Y0YCF7qZlNrx06rxQTOr8IfM4
-FpOUurDTazgGzRYSespSdcitdrLCnF2YRVxvYXvGLe48E1KGAdlX5jgc3421H5KR
-mudKHMxFqHJV8LDmowfs/acbZp4/SItxhHFYyTr6717yW0QrPHTnj7JHwQdqzZq3
-DZb3EoEmUVQK7GH29/Xi8orIlQ2NAgMBAAGjgfgwgfUwDgYDVR0PAQH/BAQDAgGG
-MB0GA1UdJQQWMBQGCCsGAQUFBwMCBggrBgEFBQcDATASBgNVHRMBAf8ECDAGAQH/
-AgEAMB0GA1UdDgQWBBS7vMNHpeS8qcbDpHIMEI2iNeHI6DAfBgNVHSMEGDAWgBR5
-tFnme7bl5AFzgAiIyBpY9umbbjAyBggrBgEFBQcBAQQmMCQwIgYIKwYBBQUHMAKG
-Fmh0dHA6Ly94MS5pLmxlbmNyLm9yZy8wEwYDVR0gBAwwCjAIBgZngQwBAgEwJwYD
-VR0fBCAwHjAcoBqgGIYWaHR0cDovL3gxLmMubGVuY3Iub3JnLzANBgkqhkiG9w0B
-AQsFAAOCAgEAkrHnQTfreZ2B5s3iJeE6IOmQRJWjgVzPw139vaBw1bGWKCIL0vIo
-zwzn1OZDjCQiHcFCktEJr59L9MhwTyAWsVrdAfYf+B9haxQnsHKNY67u4s5Lzzfd
-u6PUzeetUK29v+PsPmI2cJkxp+iN3epi4hKu9ZzUPSwMqtCceb7qPVxEbpYxY1p9
-1n5PJKBLBX9eb9LU6l8zSxPWV7bK3lG4XaMJgnT9x3ies7msFtpKK5bDtotij/l0
-GaKeA97pb5uwD9KgWvaFXMIEt8jVTjLEvwRdvCn294GPDF08U8lAkIv7tghluaQh
-1QnlE4SEN4LOECj8dsIGJXpGUk3aU3KkJz9icKy+aUgA+2cP21uh6NcDIS3XyfaZ
-QjmDQ993ChII8SXWupQZVBiIpcWO4RqZk3lr7Bz5MUCwzDIA359e57SSq5CCkY0N
-4B6Vulk7LktfwrdGNVI5BsC9qqxSwSKgRJeZ9wygIaehbHFHFhcBaMDKpiZlBHyz
-rsnnlFXCb5s8HKn5LsUgGvB24L7sGNZP2CX7dhHov+YhD+jozLW2p9W4959Bz2Ei
-RmqDtmiXLnzqTpXbI+suyCsohKRg6Un0RC47+cpiVwHiXZAW+cn8eiNIjqbVgXLx
-KPpdzvvtTnOPlC7SQZSYmdunr3Bf9b77AiC/ZidstK36dRILKz7OA54=
+";
+
+pub const ISRG_ROOT_X2_PEM: &str = "-----BEGIN CERTIFICATE-----
+MIICGzCCAaGgAwIBAgIQQdKd0XLq7qeAwSxs6S+HUjAKBggqhkjOPQQDAzBPMQsw
+CQYDVQQGEwJVUzEpMCcGA1UEChMgSW50ZXJuZXQgU2VjdXJpdHkgUmVzZWFyY2gg
+R3JvdXAxFTATBgNVBAMTDElTUkcgUm9vdCBYMjAeFw0yMDA5MDQwMDAwMDBaFw00
+MDA5MTcxNjAwMDBaME8xCzAJBgNVBAYTAlVTMSkwJwYDVQQKEyBJbnRlcm5ldCBT
+ZWN1cml0eSBSZXNlYXJjaCBHcm91cDEVMBMGA1UEAxMMSVNSRyBSb290IFgyMHYw
+EAYHKoZIzj0CAQYFK4EEACIDYgAEzZvVn4CDCuwJSvMWSj5cz3es3mcFDR0HttwW
++1qLFNvicWDEukWVEYmO6gbf9yoWHKS5xcUy4APgHoIYOIvXRdgKam7mAHf7AlF9
+ItgKbppbd9/w+kHsOdx1ymgHDB/qo0IwQDAOBgNVHQ8BAf8EBAMCAQYwDwYDVR0T
+AQH/BAUwAwEB/zAdBgNVHQ4EFgQUfEKWrt5LSDv6kviejM9ti6lyN5UwCgYIKoZI
+zj0EAwMDaAAwZQIwe3lORlCEwkSHRhtFcP9Ymd70/aTSVaYgLXTWNLxBo1BfASdW
+tL4ndQavEi51mI38AjEAi/V3bNTIZargCyzuFJ0nN6T5U6VR5CmD1/iQMVtCnwr1
+/q4AaOeMSQ+2b1tbFfLn
 -----END CERTIFICATE-----
------BEGIN CERTIFICATE-----
-MIIFEzCCA/ugAwIBAgISBtWn/9j3PsGQRAGk8Cdl5x35MA0GCSqGSIb3DQEBCwUA
-MDMxCzAJBgNVBAYTAlVTMRYwFAYDVQQKEw1MZXQncyBFbmNyeXB0MQwwCgYDVQQD
-EwNSMTAwHhcNMjUwNTE0MDYzNzIxWhcNMjUwODEyMDYzNzIwWjAmMSQwIgYDVQQD
-Ext0ZXN0bmV0LWVuZ2luZS5zb3ZhLm5ldHdvcmswggEiMA0GCSqGSIb3DQEBAQUA
-A4IBDwAwggEKAoIBAQCgc9hbhnqDSb6hAGNe8XYOQsdA6KHy38gKH6HXRhNUebiZ
-nFTMzkJU2ZJl6e9h+yyLfKx335E4IgIPIq6odsI7V+Lc24LtpvZ4QyR8LIUjYwlp
-koFvBOmW3AbbQF13idQxeSAVABmiKErnTGXsr9Uq4bF3czEw9fC7v6WolfOQzhuF
-0Ag6a0ttejFL7PrMOtgItR33wduu2Fd+aWn5PjZwPFCBdmV/lOUat1MvkP3eVYZa
-1GZma1nmE/ygcw2NIx/ekHTVnltP1ysi2QPPm4wKKFKlbFxKN76fo0c5Vw1U2Wj5
-nITGGXvD2rlMb+i1fXuGwBaOThhRyf6r4dyOSPbzAgMBAAGjggIsMIICKDAOBgNV
-HQ8BAf8EBAMCBaAwHQYDVR0lBBYwFAYIKwYBBQUHAwEGCCsGAQUFBwMCMAwGA1Ud
-EwEB/wQCMAAwHQYDVR0OBBYEFK2ALmOAx9w9y8E2K0jmRyGUXqo/MB8GA1UdIwQY
-MBaAFLu8w0el5LypxsOkcgwQjaI14cjoMDMGCCsGAQUFBwEBBCcwJTAjBggrBgEF
-BQcwAoYXaHR0cDovL3IxMC5pLmxlbmNyLm9yZy8wJgYDVR0RBB8wHYIbdGVzdG5l
-dC1lbmdpbmUuc292YS5uZXR3b3JrMBMGA1UdIAQMMAowCAYGZ4EMAQIBMC8GA1Ud
-HwQoMCYwJKAioCCGHmh0dHA6Ly9yMTAuYy5sZW5jci5vcmcvMTIyLmNybDCCAQQG
-CisGAQQB1nkCBAIEgfUEgfIA8AB2AO08S9boBsKkogBX28sk4jgB31Ev7cSGxXAP
-IN23Pj/gAAABls24omUAAAQDAEcwRQIgTLimx/k+zhLz9FA/oX1P47jxSArXj4c3
-Kl+qqDV2AzECIQDP/3xfK/tVh/tZvaS4ZLCfAhYydRhN5k+SAg0Ajv188gB2AA3h
-8jAr0w3BQGISCepVLvxHdHyx1+kw7w5CHrR+Tqo0AAABls24qjgAAAQDAEcwRQIh
-AJr1daQqwBOwfc0nIwFBFiTo/uG9mk6fW82WHcujTYFOAiB+L9MskOtTz7cLEaZ1
-i4UwBSdbIBIm1DZoyvcZoPkeqDANBgkqhkiG9w0BAQsFAAOCAQEAEEl6Hr8Ki3n/
-HEbRkiHmCMESQThs+XZ6sqimpwB2+PvDKlYOf4D57e2m+qIkYYfW0hOEf/+JYoUz
-nTHC1Zc/Tg1aecoOeXNNcD2/WIhBfEeDpxzURgB/a53E3OYDhKNiJaMzBcTL+CYm
-XYElnsgw+gKQdQe/c5yITiBiezcgFSPT5PKx2JL2SJMEOj6/hNlBEVOzQ2pAj22/
-ytqfR4w7Oa5GmagU+tXhpX9txA7b4/eFQ0F7CNZy0M4F95SuQWRODLOZV6w4dOqI
-31HfikbI67j7c0SFw3NKGEbf/wwSE5mkeURmk0zIdx/5WMhY689kp+kAbP1Eby6u
-1SfdtQd3zg==
------END CERTIFICATE-----";
+";
@@ -0,0 +1,661 @@
+//! Multi-region engine endpoints.
+//!
+//! [`SearcherPool`] connects to several [`EngineEndpoint`]s, probes their round-trip time, and
+//! sends each bundle to the fastest healthy endpoint or
+//! [broadcasts](SearcherPool::broadcast_bundle) it to all of them. Subscriptions fail over to
+//! another healthy endpoint when their stream breaks.
+
+use std::future::{self, Future};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+
+use futures_util::future::join_all;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tonic::Streaming;
+
+use crate::call_options::CallOptions;
+use crate::delivery::{Concurrency, Delivery, Handler};
+use crate::error::SovaError;
+use crate::interceptor::TokenSource;
+use crate::latency::{LatencyStats, ReceivedAt};
+use crate::proto;
+use crate::proto::searcher::{mempool_subscription, SendBundleResponse};
+use crate::searcher::SovaSearcher;
+use crate::subscription::{EndReason, SubscriptionHandle, SubscriptionId, SubscriptionSet};
+use crate::telemetry;
+use crate::tls::{TlsMode, CA_BUNDLE_ENV};
+use crate::types::{BundleUpdate, MempoolEvent};
+
+/// A single block engine endpoint.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EngineEndpoint {
+    pub url: String,
+    pub tls: TlsMode,
+}
+
+impl EngineEndpoint {
+    pub fn new(url: &str, tls: TlsMode) -> Self {
+        Self {
+            url: url.to_owned(),
+            tls,
+        }
+    }
+
+    /// The mainnet engine, trusting the bundle in [`CA_BUNDLE_ENV`] if set and the Let's Encrypt
+    /// roots otherwise.
+    pub fn mainnet() -> Self {
+        Self::new("https://engine.sova.network:30020", Self::preset_tls())
+    }
+
+    /// The testnet engine, with the same trust as [`mainnet`](Self::mainnet).
+    pub fn testnet() -> Self {
+        Self::new(
+            "https://testnet-engine.sova.network:30020",
+            Self::preset_tls(),
+        )
+    }
+
+    fn preset_tls() -> TlsMode {
+        TlsMode::from_env(CA_BUNDLE_ENV).unwrap_or_else(TlsMode::lets_encrypt)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PoolConfig {
+    /// Deadline for a single RTT probe.
+    pub probe_timeout: Duration,
+    /// Pause before re-probing when every endpoint is unhealthy.
+    pub retry_delay: Duration,
+    /// Deadline for bundles sent through the pool.
+    pub request_timeout: Option<Duration>,
+    /// Probes every endpoint at this interval for as long as the pool is in use.
+    pub health_check_interval: Option<Duration>,
+}
+
+impl PoolConfig {
+    fn call_options(&self) -> CallOptions {
+        match self.request_timeout {
+            Some(timeout) => CallOptions::new().timeout(timeout),
+            None => CallOptions::new(),
+        }
+    }
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            probe_timeout: Duration::from_secs(2),
+            retry_delay: Duration::from_secs(1),
+            request_timeout: None,
+            health_check_interval: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EndpointStatus {
+    pub url: String,
+    pub healthy: bool,
+    /// Round trip time of the last successful probe.
+    pub rtt: Option<Duration>,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct EndpointHealth {
+    healthy: bool,
+    rtt: Option<Duration>,
+}
+
+struct PoolMember {
+    endpoint: EngineEndpoint,
+    /// `None` until the endpoint has been reached once.
+    searcher: Mutex<Option<SovaSearcher>>,
+    health: Mutex<EndpointHealth>,
+}
+
+impl PoolMember {
+    fn searcher(&self) -> Option<SovaSearcher> {
+        self.searcher.lock().unwrap().clone()
+    }
+
+    /// The connected searcher, connecting first if the endpoint was never reached.
+    async fn connect(&self, tokens: &TokenSource, config: &PoolConfig) -> Option<SovaSearcher> {
+        if let Some(searcher) = self.searcher() {
+            return Some(searcher);
+        }
+
+        let connect = connect_searcher(&self.endpoint, tokens, config);
+        let searcher = tokio::time::timeout(config.probe_timeout, connect)
+            .await
+            .ok()?
+            .ok()?;
+        tracing::info!(endpoint = %self.endpoint.url, "connected to endpoint");
+
+        Some(
+            self.searcher
+                .lock()
+                .unwrap()
+                .get_or_insert(searcher)
+                .clone(),
+        )
+    }
+}
+
+struct PoolInner {
+    members: Vec<PoolMember>,
+    tokens: TokenSource,
+    config: PoolConfig,
+    health_changed: watch::Sender<()>,
+    latency: LatencyStats,
+    subscriptions: SubscriptionSet,
+}
+
+/// Searchers connected to several block engine endpoints.
+///
+/// Bundles go to the healthy endpoint with the lowest measured RTT, falling back to the next one
+/// on failure, or to every endpoint at once with [`broadcast_bundle`](Self::broadcast_bundle).
+/// Subscriptions started through the pool move to another endpoint when their stream breaks or
+/// their endpoint is marked unhealthy by a probe.
+#[derive(Clone)]
+pub struct SearcherPool {
+    inner: Arc<PoolInner>,
+}
+
+impl SearcherPool {
+    /// Connects to every endpoint and measures its RTT. Fails only if no endpoint is reachable.
+    pub async fn connect(
+        endpoints: Vec<EngineEndpoint>,
+        tokens: TokenSource,
+        config: PoolConfig,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::connect_with_subscriptions(endpoints, tokens, config, SubscriptionSet::default())
+            .await
+    }
+
+    pub(crate) async fn connect_with_subscriptions(
+        endpoints: Vec<EngineEndpoint>,
+        tokens: TokenSource,
+        config: PoolConfig,
+        subscriptions: SubscriptionSet,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let connections = endpoints
+            .iter()
+            .map(|endpoint| connect_searcher(endpoint, &tokens, &config));
+
+        let members = endpoints
+            .iter()
+            .cloned()
+            .zip(join_all(connections).await)
+            .map(|(endpoint, searcher)| PoolMember {
+                endpoint,
+                searcher: Mutex::new(searcher.ok()),
+                health: Mutex::default(),
+            })
+            .collect::<Vec<_>>();
+
+        if members.iter().all(|member| member.searcher().is_none()) {
+            return Err(Box::new(SovaError::NoHealthyEndpoint));
+        }
+
+        let pool = Self {
+            inner: Arc::new(PoolInner {
+                members,
+                tokens,
+                config,
+                health_changed: watch::channel(()).0,
+                latency: LatencyStats::default(),
+                subscriptions,
+            }),
+        };
+        pool.probe().await;
+
+        if let Some(interval) = pool.inner.config.health_check_interval {
+            spawn_health_checks(Arc::downgrade(&pool.inner), interval);
+        }
+
+        Ok(pool)
+    }
+
+    /// Measures the RTT of every endpoint with a `get_tip_addresses` call, first connecting to
+    /// endpoints that could not be reached before. Only endpoints that cannot be reached or do
+    /// not answer in time are marked unhealthy.
+    pub async fn probe(&self) {
+        let timeout = self.inner.config.probe_timeout;
+        let options = CallOptions::new().timeout(timeout);
+
+        let probes = self.inner.members.iter().map(|member| {
+            let options = options.clone();
+
+            async move {
+                let Some(searcher) = member.connect(&self.inner.tokens, &self.inner.config).await
+                else {
+                    return EndpointHealth::default();
+                };
+
+                let started = Instant::now();
+                let reachable = match searcher.get_tip_addresses_with_options(options).await {
+                    Ok(_) => true,
+                    Err(error) => !is_endpoint_failure(error.as_ref()),
+                };
+
+                EndpointHealth {
+                    healthy: reachable,
+                    rtt: reachable.then(|| started.elapsed()),
+                }
+            }
+        });
+
+        for (member, health) in self.inner.members.iter().zip(join_all(probes).await) {
+            *member.health.lock().unwrap() = health;
+        }
+        self.inner.health_changed.send_replace(());
+    }
+
+    /// Probes every endpoint each `interval` until the returned task is aborted. See
+    /// [`PoolConfig::health_check_interval`] for checks that stop with the pool.
+    pub fn spawn_health_checks(&self, interval: Duration) -> JoinHandle<()> {
+        let pool = self.clone();
+
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                pool.probe().await;
+            }
+        })
+    }
+
+    pub fn status(&self) -> Vec<EndpointStatus> {
+        self.inner
+            .members
+            .iter()
+            .map(|member| {
+                let health = *member.health.lock().unwrap();
+
+                EndpointStatus {
+                    url: member.endpoint.url.clone(),
+                    healthy: health.healthy,
+                    rtt: health.rtt,
+                }
+            })
+            .collect()
+    }
+
+    /// The healthy endpoint with the lowest RTT.
+    pub fn fastest(&self) -> Option<&EngineEndpoint> {
+        self.ranked()
+            .first()
+            .map(|&index| &self.inner.members[index].endpoint)
+    }
+
+    /// Sends the bundle to the fastest healthy endpoint, trying the others in RTT order if it
+    /// cannot be reached or does not answer in time. Any other error, such as a rejected
+    /// bundle, is returned right away. When no endpoint is healthy, they are probed again first.
+    pub async fn send_bundle(
+        &self,
+        bundle: proto::dto::Bundle,
+    ) -> Result<SendBundleResponse, Box<dyn std::error::Error>> {
+        let mut last_error: Box<dyn std::error::Error> = Box::new(SovaError::NoHealthyEndpoint);
+
+        let mut ranked = self.ranked();
+        if ranked.is_empty() {
+            self.probe().await;
+            ranked = self.ranked();
+        }
+
+        for index in ranked {
+            let Some(searcher) = self.inner.members[index].searcher() else {
+                continue;
+            };
+
+            match searcher.send_bundle(bundle.clone()).await {
+                Ok(response) => return Ok(response),
+                Err(error) if !is_endpoint_failure(error.as_ref()) => return Err(error),
+                Err(error) => {
+                    tracing::warn!(
+                        endpoint = %self.inner.members[index].endpoint.url,
+                        %error,
+                        "send_bundle failed, trying the next endpoint"
+                    );
+                    self.mark_unhealthy(index);
+                    last_error = error;
+                }
+            }
+        }
+
+        Err(last_error)
+    }
+
+    /// Sends the bundle to every connected endpoint in parallel. Results are in endpoint order.
+    pub async fn broadcast_bundle(
+        &self,
+        bundle: proto::dto::Bundle,
+    ) -> Vec<Result<SendBundleResponse, Box<dyn std::error::Error>>> {
+        let sends = self.inner.members.iter().map(|member| {
+            let bundle = bundle.clone();
+
+            async move {
+                match member.searcher() {
+                    Some(searcher) => searcher.send_bundle(bundle).await,
+                    None => Err(Box::new(SovaError::NoHealthyEndpoint) as _),
+                }
+            }
+        });
+
+        join_all(sends).await
+    }
+
+    /// One-way latency of packets delivered by [`subscribe`](Self::subscribe), by
+    /// [subscription](SubscriptionHandle::id) and by kind, across every endpoint.
+    pub fn latency_stats(&self) -> &LatencyStats {
+        &self.inner.latency
+    }
+
+    /// Subscriptions started through the pool that are still running.
+    pub fn subscriptions(&self) -> Vec<SubscriptionHandle> {
+        self.inner.subscriptions.active()
+    }
+
+    /// Cancels every subscription started through the pool and waits up to `deadline` for them
+    /// to stop.
+    pub async fn shutdown(&self, deadline: Duration) -> Result<(), SovaError> {
+        self.inner.subscriptions.shutdown(deadline).await
+    }
+
+    /// Events carry their receive time, and their one-way latency is recorded in
+    /// [`latency_stats`](Self::latency_stats). Messages that do not decode are dropped from their
+    /// event and counted. Streams that break because their endpoint fails are reopened on
+    /// another endpoint; the subscription ends when it is cancelled or the engine rejects it,
+    /// e.g. for an expired token, with [`EndReason::Failed`].
+    pub fn subscribe<F>(
+        &self,
+        subscription: mempool_subscription::Subscription,
+        on_data: F,
+    ) -> SubscriptionHandle
+    where
+        F: Fn(MempoolEvent) + Send + 'static,
+    {
+        self.subscribe_with_options(subscription, CallOptions::default(), on_data)
+    }
+
+    /// Like [`subscribe`](Self::subscribe). The options apply to every stream the pool opens
+    /// for the subscription, and their [`DeliveryConfig`](crate::delivery::DeliveryConfig)
+    /// buffers events across fail-overs. The subscription also ends when the buffer overflows
+    /// under [`OverflowPolicy::Disconnect`](crate::delivery::OverflowPolicy::Disconnect).
+    pub fn subscribe_with_options<F>(
+        &self,
+        subscription: mempool_subscription::Subscription,
+        options: CallOptions,
+        on_data: F,
+    ) -> SubscriptionHandle
+    where
+        F: Fn(MempoolEvent) + Send + 'static,
+    {
+        let id = SubscriptionId::next();
+        let kind = telemetry::subscription_kind(&subscription);
+        let latency = self.inner.latency.clone();
+
+        self.spawn_failover(
+            id,
+            kind,
+            options,
+            move |searcher, options| {
+                let subscription = subscription.clone();
+
+                async move { searcher.open_mempool(subscription, &options).await }
+            },
+            move |packet, received| {
+                let mut event = MempoolEvent::from_packet_lossy(packet, |error| {
+                    telemetry::decode_failure(kind, &error)
+                });
+                latency.observe(id, kind, &mut event, received);
+                on_data(event);
+            },
+        )
+    }
+
+    /// Results that do not decode into a [`BundleUpdate`] are skipped. The subscription ends as
+    /// described for [`subscribe`](Self::subscribe).
+    pub fn subscribe_bundle_results<F>(&self, on_data: F) -> SubscriptionHandle
+    where
+        F: Fn(BundleUpdate) + Send + 'static,
+    {
+        self.subscribe_bundle_results_with_options(CallOptions::default(), on_data)
+    }
+
+    /// Like [`subscribe_bundle_results`](Self::subscribe_bundle_results), with options applied
+    /// as for [`subscribe_with_options`](Self::subscribe_with_options).
+    pub fn subscribe_bundle_results_with_options<F>(
+        &self,
+        options: CallOptions,
+        on_data: F,
+    ) -> SubscriptionHandle
+    where
+        F: Fn(BundleUpdate) + Send + 'static,
+    {
+        self.spawn_failover(
+            SubscriptionId::next(),
+            "bundle_results",
+            options,
+            |searcher, options| async move { searcher.open_bundle_results(&options).await },
+            move |result, _| match BundleUpdate::try_from(result) {
+                Ok(update) => {
+                    telemetry::bundle_result(&update.outcome);
+                    on_data(update);
+                }
+                Err(error) => telemetry::decode_failure("bundle_results", &error),
+            },
+        )
+    }
+
+    fn spawn_failover<T, O, Fut, F>(
+        &self,
+        id: SubscriptionId,
+        kind: &'static str,
+        options: CallOptions,
+        open: O,
+        on_data: F,
+    ) -> SubscriptionHandle
+    where
+        T: Send + 'static,
+        O: Fn(SovaSearcher, CallOptions) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<Streaming<T>, Box<dyn std::error::Error>>> + Send,
+        F: Fn(T, ReceivedAt) + Send + 'static,
+    {
+        let pool = self.clone();
+        let cancel = options
+            .cancellation_token()
+            .map(CancellationToken::child_token)
+            .unwrap_or_default();
+        let (handle, ended) = SubscriptionHandle::new(id, kind, cancel.clone());
+        let options = options.cancellation(cancel);
+
+        tokio::spawn(async move {
+            let mut delivery = Delivery::new(
+                options.delivery_config().cloned(),
+                kind,
+                Handler::new(Concurrency::Sequential, move |(message, received)| {
+                    on_data(message, received);
+                    future::ready(())
+                }),
+            );
+            let retry_delay = pool.inner.config.retry_delay;
+            let mut opened_before = false;
+
+            let reason = 'failover: loop {
+                let Some((index, searcher)) = pool.pick() else {
+                    let retry = async {
+                        tokio::time::sleep(retry_delay).await;
+                        pool.probe().await;
+                    };
+                    if options.run(retry).await.is_err() {
+                        break EndReason::Cancelled;
+                    }
+                    continue;
+                };
+
+                let endpoint = pool.inner.members[index].endpoint.url.clone();
+                let opened = open(searcher, options.clone())
+                    .await
+                    .map_err(|error| (is_endpoint_failure(error.as_ref()), end_reason(error)));
+                let mut stream = match opened {
+                    Ok(stream) => stream,
+                    Err((true, _)) => {
+                        tracing::warn!(kind, endpoint, "could not open subscription, failing over");
+                        pool.mark_unhealthy(index);
+                        continue;
+                    }
+                    Err((false, reason)) => break reason,
+                };
+                if opened_before {
+                    tracing::info!(kind, endpoint, "subscription moved to another endpoint");
+                    telemetry::stream_reconnected(kind);
+                }
+                opened_before = true;
+
+                let mut health_changed = pool.inner.health_changed.subscribe();
+                loop {
+                    tokio::select! {
+                        message = options.run(stream.message()) => match message {
+                            Ok(Ok(Some(message))) => {
+                                telemetry::packet_received(kind);
+                                let item = (message, ReceivedAt::now());
+                                match delivery.deliver(item, &options).await {
+                                    Ok(true) => {}
+                                    Ok(false) => break 'failover EndReason::Overflow,
+                                    Err(_) => break 'failover EndReason::Cancelled,
+                                }
+                            }
+                            Ok(Ok(None)) => {
+                                tracing::warn!(kind, endpoint, "stream closed by the server");
+                                if options.run(tokio::time::sleep(retry_delay)).await.is_err() {
+                                    break 'failover EndReason::Cancelled;
+                                }
+                                break;
+                            }
+                            Ok(Err(status)) => {
+                                tracing::warn!(
+                                    kind,
+                                    endpoint,
+                                    code = ?status.code(),
+                                    message = status.message(),
+                                    "stream failed"
+                                );
+                                if !is_endpoint_failure(&status) {
+                                    break 'failover end_reason(Box::new(status));
+                                }
+                                pool.mark_unhealthy(index);
+                                break;
+                            }
+                            Err(_) => break 'failover EndReason::Cancelled,
+                        },
+                        Ok(()) = health_changed.changed() => {
+                            if !pool.is_healthy(index) {
+                                break;
+                            }
+                        }
+                    }
+                }
+            };
+
+            // Callbacks still running or buffered finish before the subscription reports its end.
+            delivery.finish().await;
+            tracing::info!(kind, ?reason, "subscription stopped");
+            ended.send_replace(Some(reason));
+        });
+        self.inner.subscriptions.insert(&handle);
+
+        handle
+    }
+
+    fn ranked(&self) -> Vec<usize> {
+        let mut ranked = self
+            .inner
+            .members
+            .iter()
+            .enumerate()
+            .filter(|(_, member)| member.searcher().is_some())
+            .filter_map(|(index, member)| {
+                let health = *member.health.lock().unwrap();
+                health
+                    .healthy
+                    .then(|| (index, health.rtt.unwrap_or(Duration::MAX)))
+            })
+            .collect::<Vec<_>>();
+        ranked.sort_by_key(|&(_, rtt)| rtt);
+
+        ranked.into_iter().map(|(index, _)| index).collect()
+    }
+
+    fn pick(&self) -> Option<(usize, SovaSearcher)> {
+        self.ranked().into_iter().find_map(|index| {
+            let searcher = self.inner.members[index].searcher()?;
+            Some((index, searcher))
+        })
+    }
+
+    fn is_healthy(&self, index: usize) -> bool {
+        self.inner.members[index].health.lock().unwrap().healthy
+    }
+
+    fn mark_unhealthy(&self, index: usize) {
+        self.inner.members[index].health.lock().unwrap().healthy = false;
+        self.inner.health_changed.send_replace(());
+    }
+}
+
+async fn connect_searcher(
+    endpoint: &EngineEndpoint,
+    tokens: &TokenSource,
+    config: &PoolConfig,
+) -> Result<SovaSearcher, Box<dyn std::error::Error>> {
+    SovaSearcher::new_with_token_source(&endpoint.url, endpoint.tls.clone(), tokens.clone())
+        .await
+        .map(|searcher| searcher.with_default_options(config.call_options()))
+}
+
+/// Probes the pool each `interval`, until the last handle to it is dropped.
+fn spawn_health_checks(pool: Weak<PoolInner>, interval: Duration) {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(interval).await;
+            let Some(inner) = pool.upgrade() else {
+                break;
+            };
+            SearcherPool { inner }.probe().await;
+        }
+    });
+}
+
+/// Why a subscription ends after `error`, which is not an endpoint failure.
+fn end_reason(error: Box<dyn std::error::Error>) -> EndReason {
+    if matches!(error.downcast_ref(), Some(SovaError::Cancelled)) {
+        return EndReason::Cancelled;
+    }
+
+    match error.downcast::<tonic::Status>() {
+        Ok(status) => EndReason::Failed {
+            code: status.code(),
+            message: status.message().to_owned(),
+        },
+        Err(error) => EndReason::Failed {
+            code: tonic::Code::Unknown,
+            message: error.to_string(),
+        },
+    }
+}
+
+/// Whether `error` means the endpoint could not serve the call, as opposed to the engine
+/// answering it with an error.
+fn is_endpoint_failure(error: &(dyn std::error::Error + 'static)) -> bool {
+    let Some(status) = error.downcast_ref::<tonic::Status>() else {
+        return error.is::<tonic::transport::Error>();
+    };
+
+    matches!(
+        status.code(),
+        tonic::Code::Unavailable | tonic::Code::DeadlineExceeded
+    ) || std::error::Error::source(status)
+        .is_some_and(|source| source.is::<tonic::transport::Error>())
+}
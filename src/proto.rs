@@ -1,5 +1,15 @@
 pub mod auth {
     tonic::include_proto!("auth");
+
+    // `Debug` is skipped for `Token` in build.rs so its value never ends up in logs.
+    impl std::fmt::Debug for Token {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("Token")
+                .field("value", &"<redacted>")
+                .field("expires_at_utc", &self.expires_at_utc)
+                .finish()
+        }
+    }
 }
 
 pub mod searcher {
@@ -0,0 +1,376 @@
+//! Sending a bundle again for the following blocks when it loses the auction or is interrupted.
+//! See [`SovaSearcher::send_bundle_with_resubmission`].
+
+use std::time::{Duration, SystemTime};
+
+use tokio::sync::mpsc::error::TryRecvError;
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+use tokio::time::Instant;
+
+use crate::delivery::Concurrency;
+use crate::proto::dto::Bundle;
+use crate::searcher::SovaSearcher;
+use crate::telemetry;
+use crate::types::{BundleOutcome, BundleUpdate};
+
+/// Tip paid by each attempt, in nanotons.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TipSchedule {
+    start: u64,
+    escalation: Escalation,
+    max: Option<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Escalation {
+    Fixed,
+    Add(u64),
+    Multiply(f64),
+    Steps(Vec<u64>),
+}
+
+impl TipSchedule {
+    /// The same tip for every attempt.
+    pub fn fixed(tip: u64) -> Self {
+        Self::new(tip, Escalation::Fixed)
+    }
+
+    /// `start`, then `step` more for each further attempt.
+    pub fn linear(start: u64, step: u64) -> Self {
+        Self::new(start, Escalation::Add(step))
+    }
+
+    /// `start`, then multiplied by `factor` for each further attempt.
+    pub fn multiply(start: u64, factor: f64) -> Self {
+        Self::new(start, Escalation::Multiply(factor))
+    }
+
+    /// One tip per attempt; attempts past the end repeat the last tip.
+    pub fn steps(tips: Vec<u64>) -> Self {
+        Self::new(tips.first().copied().unwrap_or(0), Escalation::Steps(tips))
+    }
+
+    /// Caps every tip at `max`.
+    pub fn max(mut self, max: u64) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    /// The tip for `attempt`, counting from 1.
+    pub fn tip(&self, attempt: u32) -> u64 {
+        let step = attempt.saturating_sub(1);
+        let tip = match &self.escalation {
+            Escalation::Fixed => self.start,
+            Escalation::Add(increment) => self
+                .start
+                .saturating_add(increment.saturating_mul(step as u64)),
+            Escalation::Multiply(factor) => {
+                (self.start as f64 * factor.powi(step.min(i32::MAX as u32) as i32)) as u64
+            }
+            Escalation::Steps(tips) => tips
+                .get(step as usize)
+                .or(tips.last())
+                .copied()
+                .unwrap_or(0),
+        };
+
+        self.max.map_or(tip, |max| tip.min(max))
+    }
+
+    fn new(start: u64, escalation: Escalation) -> Self {
+        Self {
+            start,
+            escalation,
+            max: None,
+        }
+    }
+}
+
+/// When and how to send a bundle again.
+///
+/// An attempt whose result is [`HigherTipWon`](BundleOutcome::HigherTipWon) or
+/// [`Expired`](BundleOutcome::Expired), or that gets no result in time, is followed by another
+/// one with the next tip from the schedule. Resubmission stops once the bundle is included, a
+/// conflicting bundle wins, the simulation fails, the attempts run out, the bundle expires or
+/// the bundle results subscription ends.
+/// A late result for an earlier attempt also stops it if that attempt was included or beaten by
+/// a conflicting bundle.
+#[derive(Clone, Debug)]
+pub struct ResubmitPolicy {
+    max_attempts: u32,
+    tips: TipSchedule,
+    valid_until: Option<SystemTime>,
+    result_timeout: Duration,
+    retry_simulation_failures: bool,
+}
+
+impl ResubmitPolicy {
+    pub fn new(max_attempts: u32, tips: TipSchedule) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            tips,
+            valid_until: None,
+            result_timeout: Duration::from_secs(30),
+            retry_simulation_failures: false,
+        }
+    }
+
+    /// No attempt is made after `valid_until`. Pass the earliest `valid_until` of the bundle's
+    /// wallet messages; once it passes they are rejected anyway. Defaults to the bundle's
+    /// `expiration_ns`, and is set as `expiration_ns` on bundles that have none.
+    ///
+    /// The messages themselves are not parsed, so without this or an `expiration_ns` attempts
+    /// go on until they run out, even after the messages have expired.
+    pub fn valid_until(mut self, valid_until: SystemTime) -> Self {
+        self.valid_until = Some(valid_until);
+        self
+    }
+
+    /// How long to wait for the result of an attempt before sending the next one. Defaults to
+    /// 30 seconds.
+    pub fn result_timeout(mut self, timeout: Duration) -> Self {
+        self.result_timeout = timeout;
+        self
+    }
+
+    /// Sends another attempt after a [`SimulationFailed`](BundleOutcome::SimulationFailed)
+    /// result instead of stopping, for bundles whose simulation can succeed on a later block.
+    /// A higher tip rarely fixes a failing simulation, so this is off by default.
+    pub fn retry_simulation_failures(mut self, retry: bool) -> Self {
+        self.retry_simulation_failures = retry;
+        self
+    }
+
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    pub fn tips(&self) -> &TipSchedule {
+        &self.tips
+    }
+}
+
+/// One submission of the bundle.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Attempt {
+    /// Counting from 1.
+    pub number: u32,
+    pub tip: u64,
+    pub bundle_id: String,
+    /// `None` if no result arrived before the result timeout or the expiry, unless it arrived
+    /// while a later attempt was pending.
+    pub outcome: Option<BundleOutcome>,
+}
+
+/// Why resubmission stopped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StopReason {
+    Included,
+    /// A conflicting bundle won the auction, so this one can no longer land.
+    ConflictingSuccess,
+    /// The bundle failed simulation, and the policy does not
+    /// [retry](ResubmitPolicy::retry_simulation_failures) it.
+    SimulationFailed,
+    AttemptsExhausted,
+    /// The bundle's `valid_until` passed.
+    Expired,
+    /// The bundle results subscription ended, so further attempts would get no results.
+    ResultsUnavailable,
+}
+
+/// Every attempt made, in order, and why resubmission stopped.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Resubmission {
+    pub attempts: Vec<Attempt>,
+    pub stop: StopReason,
+}
+
+impl Resubmission {
+    pub fn is_included(&self) -> bool {
+        self.stop == StopReason::Included
+    }
+}
+
+#[tracing::instrument(
+    name = "resubmission",
+    skip_all,
+    fields(endpoint = searcher.endpoint(), max_attempts = policy.max_attempts)
+)]
+pub(crate) async fn run<B, F>(
+    searcher: &SovaSearcher,
+    policy: &ResubmitPolicy,
+    mut build: B,
+    mut on_attempt: F,
+) -> Result<Resubmission, Box<dyn std::error::Error>>
+where
+    B: FnMut(u64) -> Bundle,
+    F: FnMut(&Attempt),
+{
+    let (tx, mut results) = mpsc::unbounded_channel();
+    let subscription = searcher
+        .subscribe_bundle_results_async(Concurrency::Sequential, move |update| {
+            let _ = tx.send(update);
+            std::future::ready(())
+        })
+        .await?;
+
+    let mut attempts = Vec::new();
+    let stop = 'resubmit: loop {
+        // Results for earlier attempts that arrived after their timeout.
+        loop {
+            match results.try_recv() {
+                Ok(update) => {
+                    if let Some(stop) = late_result(&mut attempts, update, &mut on_attempt) {
+                        break 'resubmit stop;
+                    }
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => break 'resubmit StopReason::ResultsUnavailable,
+            }
+        }
+
+        let number = attempts.len() as u32 + 1;
+        if number > policy.max_attempts {
+            break StopReason::AttemptsExhausted;
+        }
+
+        let tip = policy.tips.tip(number);
+        let mut bundle = build(tip);
+        let valid_until = policy.valid_until.or_else(|| {
+            let expiration = bundle.expiration_ns.clone()?;
+            SystemTime::try_from(expiration).ok()
+        });
+        let Some(time_left) = remaining(valid_until) else {
+            break StopReason::Expired;
+        };
+        if bundle.expiration_ns.is_none() {
+            bundle.expiration_ns = valid_until.map(Into::into);
+        }
+
+        if number > 1 {
+            telemetry::bundle_resubmitted();
+        }
+        let bundle_id = match searcher.send_bundle(bundle).await {
+            Ok(response) => response.id,
+            Err(error) => {
+                subscription.cancel();
+                return Err(error);
+            }
+        };
+        tracing::info!(attempt = number, tip, bundle_id, "bundle submitted");
+
+        // `None` waits without a deadline, for timeouts too long to add to the current time.
+        let deadline = Instant::now().checked_add(policy.result_timeout.min(time_left));
+        let mut outcome = None;
+        let mut late_stop = None;
+        loop {
+            let update = match recv_until(&mut results, deadline).await {
+                Ok(Some(update)) => update,
+                Ok(None) => break,
+                Err(ResultsClosed) => {
+                    late_stop = Some(StopReason::ResultsUnavailable);
+                    break;
+                }
+            };
+            if update.bundle_id == bundle_id {
+                outcome = Some(update.outcome);
+                break;
+            }
+            late_stop = late_result(&mut attempts, update, &mut on_attempt);
+            if late_stop.is_some() {
+                break;
+            }
+        }
+
+        let attempt = Attempt {
+            number,
+            tip,
+            bundle_id,
+            outcome,
+        };
+        on_attempt(&attempt);
+        let stop = late_stop.or_else(|| match &attempt.outcome {
+            Some(BundleOutcome::SimulationFailed(_)) if !policy.retry_simulation_failures => {
+                Some(StopReason::SimulationFailed)
+            }
+            Some(outcome) => final_outcome(outcome),
+            None => remaining(valid_until)
+                .is_none()
+                .then_some(StopReason::Expired),
+        });
+        attempts.push(attempt);
+
+        if let Some(stop) = stop {
+            break stop;
+        }
+    };
+
+    subscription.cancel();
+    if stop == StopReason::ResultsUnavailable {
+        let reason = subscription.join().await;
+        tracing::warn!(?reason, "bundle results subscription ended");
+    }
+    tracing::info!(attempts = attempts.len(), ?stop, "resubmission finished");
+
+    Ok(Resubmission { attempts, stop })
+}
+
+/// Time left until `valid_until`, or `None` once it has passed. Unlimited without an expiry.
+fn remaining(valid_until: Option<SystemTime>) -> Option<Duration> {
+    match valid_until {
+        Some(valid_until) => valid_until
+            .duration_since(SystemTime::now())
+            .ok()
+            .filter(|remaining| !remaining.is_zero()),
+        None => Some(Duration::MAX),
+    }
+}
+
+/// Records a result that arrived for an earlier attempt after its timeout, reporting the attempt
+/// again. Returns why resubmission should stop, if it should.
+fn late_result<F>(
+    attempts: &mut [Attempt],
+    update: BundleUpdate,
+    on_attempt: &mut F,
+) -> Option<StopReason>
+where
+    F: FnMut(&Attempt),
+{
+    let attempt = attempts
+        .iter_mut()
+        .find(|attempt| attempt.bundle_id == update.bundle_id && attempt.outcome.is_none())?;
+    tracing::info!(attempt = attempt.number, outcome = ?update.outcome, "late bundle result");
+    let stop = final_outcome(&update.outcome);
+    attempt.outcome = Some(update.outcome);
+    on_attempt(attempt);
+
+    stop
+}
+
+/// Outcomes after which no attempt can land.
+fn final_outcome(outcome: &BundleOutcome) -> Option<StopReason> {
+    match outcome {
+        BundleOutcome::Included => Some(StopReason::Included),
+        BundleOutcome::ConflictingBundle(_) => Some(StopReason::ConflictingSuccess),
+        _ => None,
+    }
+}
+
+/// The results subscription ended.
+struct ResultsClosed;
+
+/// The next result, or `None` once `deadline` passes.
+async fn recv_until(
+    results: &mut UnboundedReceiver<BundleUpdate>,
+    deadline: Option<Instant>,
+) -> Result<Option<BundleUpdate>, ResultsClosed> {
+    let update = match deadline {
+        Some(deadline) => match tokio::time::timeout_at(deadline, results.recv()).await {
+            Ok(update) => update,
+            Err(_) => return Ok(None),
+        },
+        None => results.recv().await,
+    };
+
+    update.map(Some).ok_or(ResultsClosed)
+}
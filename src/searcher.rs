@@ -1,15 +1,32 @@
-use std::str::FromStr;
-use tonic::transport::{Certificate, Channel, ClientTlsConfig};
-
+use std::future::{self, Future};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use ed25519_dalek::VerifyingKey;
+use tonic::Streaming;
+use tracing::{Instrument, Span};
+
+use crate::bundle_signing;
+use crate::call_options::CallOptions;
+use crate::delivery::Concurrency;
+use crate::error::SovaError;
+use crate::interceptor::{AuthChannel, AuthInterceptor, TokenSource};
+use crate::latency::{LatencyStats, ReceivedAt};
 use crate::proto;
+use crate::resubmit::{self, Attempt, Resubmission, ResubmitPolicy};
+use crate::shard::ShardCover;
+use crate::signer::ChallengeSigner;
+use crate::subscription::{self, SubscriptionHandle, SubscriptionId, SubscriptionSet};
+use crate::telemetry::{self, RpcTimer};
+use crate::tls::{self, TlsMode};
+use crate::types::{AccessToken, BundleUpdate, MempoolEvent, ShardId, TipAccounts};
 
-use crate::proto::auth::Token;
 use crate::proto::searcher::searcher_service_client::SearcherServiceClient;
 use crate::proto::searcher::{
     mempool_subscription, AddressSubscriptionV0, ExternalOutMessageBodyOpcodeSubscriptionV0,
-    GetTipAddressesRequest, GetTipAddressesResponse, InternalMessageBodyOpcodeSubscriptionV0,
-    MempoolSubscription, SendBundleResponse, SubscribeBundleResultsRequest,
-    WorkchainShardSubscriptionV0, WorkchainSubscriptionV0,
+    GetTipAddressesRequest, InternalMessageBodyOpcodeSubscriptionV0, MempoolSubscription,
+    SendBundleResponse, SubscribeBundleResultsRequest, WorkchainShardSubscriptionV0,
+    WorkchainSubscriptionV0,
 };
 
 pub use crate::proto::{
@@ -17,126 +34,439 @@ pub use crate::proto::{
     searcher::{bundle_result, bundle_result_auction_failed, bundle_result_interrupted},
 };
 
+/// Cheap to clone: clones share the connection, access token, latency stats and subscriptions,
+/// and every method takes `&self`, so one searcher can send bundles from many tasks at once.
+#[derive(Clone)]
 pub struct SovaSearcher {
-    searcher_client: SearcherServiceClient<Channel>,
-    access_token: Option<Token>,
+    searcher_client: SearcherServiceClient<AuthChannel>,
+    tokens: TokenSource,
+    endpoint: String,
+    latency: LatencyStats,
+    subscriptions: SubscriptionSet,
+    bundle_signer: Option<(Arc<dyn ChallengeSigner>, VerifyingKey)>,
+    default_options: CallOptions,
 }
 
 impl SovaSearcher {
-    pub async fn new(
-        url: &'static str,
-        ca_pem: Option<&str>,
-        domain_name: Option<&str>,
-    ) -> Result<Self, Box<dyn std::error::Error>> {
-        Self::new_with_access_token(url, ca_pem, domain_name, None).await
+    pub async fn new(url: &str, tls: TlsMode) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::new_with_access_token(url, tls, None).await
     }
 
     pub async fn new_with_access_token(
-        url: &'static str,
-        ca_pem: Option<&str>,
-        domain_name: Option<&str>,
-        access_token: Option<Token>,
+        url: &str,
+        tls: TlsMode,
+        access_token: Option<AccessToken>,
     ) -> Result<Self, Box<dyn std::error::Error>> {
-        let searcher_client = if let (Some(ca_pem), Some(domain_name)) = (ca_pem, domain_name) {
-            let ca = Certificate::from_pem(ca_pem);
+        Self::new_with_token_source(url, tls, TokenSource::new(access_token)).await
+    }
 
-            let tls = ClientTlsConfig::new()
-                .ca_certificate(ca)
-                .domain_name(domain_name);
+    pub async fn new_with_token_source(
+        url: &str,
+        tls: TlsMode,
+        tokens: TokenSource,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let channel = tls::connect(url, &tls).await?;
 
-            let channel = Channel::from_static(url).tls_config(tls)?.connect().await?;
-
-            SearcherServiceClient::new(channel)
-        } else {
-            SearcherServiceClient::connect(url).await?
-        };
+        let searcher_client =
+            SearcherServiceClient::with_interceptor(channel, AuthInterceptor::new(tokens.clone()));
 
         Ok(Self {
             searcher_client,
-            access_token,
+            tokens,
+            endpoint: url.to_owned(),
+            latency: LatencyStats::default(),
+            subscriptions: SubscriptionSet::default(),
+            bundle_signer: None,
+            default_options: CallOptions::default(),
         })
     }
 
-    pub fn set_access_token(&mut self, token: Token) {
-        self.access_token = Some(token);
+    /// Shares `subscriptions` with the client that created this searcher, so its shutdown
+    /// covers them too.
+    pub(crate) fn with_subscriptions(mut self, subscriptions: SubscriptionSet) -> Self {
+        self.subscriptions = subscriptions;
+        self
+    }
+
+    /// Signs every bundle sent through this searcher with `signer`, usually the key it
+    /// authenticates with (see [`SovaAuth::signer`](crate::auth::SovaAuth::signer)). The
+    /// signature goes along as request metadata, see [`bundle_signing`]. The signer's public key
+    /// is fetched once here rather than on every send.
+    pub async fn with_bundle_signer(
+        mut self,
+        signer: Arc<dyn ChallengeSigner>,
+    ) -> Result<Self, SovaError> {
+        let public_key = signer.public_key().await.map_err(SovaError::Signer)?;
+        self.bundle_signer = Some((signer, public_key));
+        Ok(self)
+    }
+
+    /// Options for [`send_bundle`](Self::send_bundle) and
+    /// [`get_tip_addresses`](Self::get_tip_addresses), such as a profile's request timeout.
+    /// Subscriptions are not affected, since a timeout would end their stream.
+    pub fn with_default_options(mut self, options: CallOptions) -> Self {
+        self.default_options = options;
+        self
+    }
+
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// One-way latency of mempool packets, by [subscription](SubscriptionHandle::id) and by
+    /// kind, shared by clones of this searcher.
+    pub fn latency_stats(&self) -> &LatencyStats {
+        &self.latency
+    }
+
+    /// Subscriptions started through this searcher or its clones that are still running.
+    pub fn subscriptions(&self) -> Vec<SubscriptionHandle> {
+        self.subscriptions.active()
+    }
+
+    /// Cancels every subscription started through this searcher or its clones and waits up to
+    /// `deadline` for their callbacks to finish.
+    pub async fn shutdown(&self, deadline: Duration) -> Result<(), SovaError> {
+        self.subscriptions.shutdown(deadline).await
+    }
+
+    pub fn set_access_token(&self, token: AccessToken) {
+        self.tokens.set(Some(token));
+    }
+
+    pub fn token_source(&self) -> &TokenSource {
+        &self.tokens
     }
 
     pub async fn subscribe_bundle_results<F>(
-        &mut self,
+        &self,
         on_data: F,
-    ) -> Result<(), Box<dyn std::error::Error>>
+    ) -> Result<SubscriptionHandle, Box<dyn std::error::Error>>
     where
-        F: Fn(proto::searcher::BundleResult) + Send + 'static,
+        F: Fn(BundleUpdate) + Send + 'static,
     {
-        let mut request = tonic::Request::new(SubscribeBundleResultsRequest {});
-
-        if let Some(access_token) = &self.access_token {
-            request.metadata_mut().insert(
-                "authorization",
-                tonic::metadata::MetadataValue::from_str(&format!(
-                    "Bearer {}",
-                    access_token.value
-                ))?,
-            );
-        }
-        let mut stream = self
-            .searcher_client
-            .subscribe_bundle_results(request)
-            .await?
-            .into_inner();
-        tokio::spawn(async move {
-            while let Some(response) = stream.message().await.unwrap_or(None) {
-                on_data(response);
+        self.subscribe_bundle_results_with_options(CallOptions::default(), on_data)
+            .await
+    }
+
+    /// Results that do not decode into a [`BundleUpdate`] are skipped; use
+    /// [`subscribe_bundle_results_raw_with_options`][raw] to see every message.
+    ///
+    /// [raw]: Self::subscribe_bundle_results_raw_with_options
+    pub async fn subscribe_bundle_results_with_options<F>(
+        &self,
+        options: CallOptions,
+        on_data: F,
+    ) -> Result<SubscriptionHandle, Box<dyn std::error::Error>>
+    where
+        F: Fn(BundleUpdate) + Send + 'static,
+    {
+        self.subscribe_bundle_results_async_with_options(
+            options,
+            Concurrency::Sequential,
+            move |update| {
+                on_data(update);
+                future::ready(())
+            },
+        )
+        .await
+    }
+
+    pub async fn subscribe_bundle_results_async<F, Fut>(
+        &self,
+        concurrency: Concurrency,
+        on_data: F,
+    ) -> Result<SubscriptionHandle, Box<dyn std::error::Error>>
+    where
+        F: FnMut(BundleUpdate) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.subscribe_bundle_results_async_with_options(
+            CallOptions::default(),
+            concurrency,
+            on_data,
+        )
+        .await
+    }
+
+    /// Like [`subscribe_bundle_results_with_options`](Self::subscribe_bundle_results_with_options),
+    /// with an async callback run according to `concurrency`.
+    pub async fn subscribe_bundle_results_async_with_options<F, Fut>(
+        &self,
+        options: CallOptions,
+        concurrency: Concurrency,
+        mut on_data: F,
+    ) -> Result<SubscriptionHandle, Box<dyn std::error::Error>>
+    where
+        F: FnMut(BundleUpdate) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.start_bundle_results(options, concurrency, move |result, _| {
+            let callback = match BundleUpdate::try_from(result) {
+                Ok(update) => {
+                    telemetry::bundle_result(&update.outcome);
+                    Some(on_data(update))
+                }
+                Err(error) => {
+                    telemetry::decode_failure("bundle_results", &error);
+                    None
+                }
+            };
+
+            async move {
+                if let Some(callback) = callback {
+                    callback.await;
+                }
             }
-        });
+        })
+        .await
+    }
 
-        Ok(())
+    pub async fn subscribe_bundle_results_raw_with_options<F>(
+        &self,
+        options: CallOptions,
+        on_data: F,
+    ) -> Result<SubscriptionHandle, Box<dyn std::error::Error>>
+    where
+        F: Fn(proto::searcher::BundleResult) + Send + 'static,
+    {
+        self.start_bundle_results(options, Concurrency::Sequential, move |result, _| {
+            on_data(result);
+            future::ready(())
+        })
+        .await
+    }
+
+    async fn start_bundle_results<F, Fut>(
+        &self,
+        options: CallOptions,
+        concurrency: Concurrency,
+        on_data: F,
+    ) -> Result<SubscriptionHandle, Box<dyn std::error::Error>>
+    where
+        F: FnMut(proto::searcher::BundleResult, ReceivedAt) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let span = tracing::info_span!(
+            "subscription",
+            kind = "bundle_results",
+            endpoint = %self.endpoint
+        );
+        let stream = self
+            .open_bundle_results(&options)
+            .instrument(span.clone())
+            .await?;
+        let handle = subscription::spawn_stream(
+            stream,
+            options,
+            concurrency,
+            span,
+            SubscriptionId::next(),
+            "bundle_results",
+            on_data,
+        );
+        self.subscriptions.insert(&handle);
+
+        Ok(handle)
     }
 
     pub async fn subscribe<F>(
-        &mut self,
+        &self,
         subscription: mempool_subscription::Subscription,
         on_data: F,
-    ) -> Result<(), Box<dyn std::error::Error>>
+    ) -> Result<SubscriptionHandle, Box<dyn std::error::Error>>
     where
-        F: Fn(proto::dto::MempoolPacket) + Send + 'static,
+        F: Fn(MempoolEvent) + Send + 'static,
     {
-        let mut request = tonic::Request::new(MempoolSubscription {
-            subscription: Some(subscription),
-        });
-
-        if let Some(access_token) = &self.access_token {
-            request.metadata_mut().insert(
-                "authorization",
-                tonic::metadata::MetadataValue::from_str(&format!(
-                    "Bearer {}",
-                    access_token.value
-                ))?,
-            );
-        }
+        self.subscribe_with_options(subscription, CallOptions::default(), on_data)
+            .await
+    }
+
+    /// Events carry their receive time, and their one-way latency is recorded in
+    /// [`latency_stats`](Self::latency_stats). Messages that do not decode are dropped from their
+    /// event and counted; use [`subscribe_raw_with_options`](Self::subscribe_raw_with_options)
+    /// to see every packet as sent.
+    pub async fn subscribe_with_options<F>(
+        &self,
+        subscription: mempool_subscription::Subscription,
+        options: CallOptions,
+        on_data: F,
+    ) -> Result<SubscriptionHandle, Box<dyn std::error::Error>>
+    where
+        F: Fn(MempoolEvent) + Send + 'static,
+    {
+        self.subscribe_async_with_options(
+            subscription,
+            options,
+            Concurrency::Sequential,
+            move |event| {
+                on_data(event);
+                future::ready(())
+            },
+        )
+        .await
+    }
 
-        let mut stream = self
-            .searcher_client
-            .subscribe_mempool(request)
-            .await?
-            .into_inner();
+    /// Subscribes with an async callback, e.g. one that builds and sends a bundle for each
+    /// event. `concurrency` sets how many callbacks may run at once.
+    pub async fn subscribe_async<F, Fut>(
+        &self,
+        subscription: mempool_subscription::Subscription,
+        concurrency: Concurrency,
+        on_data: F,
+    ) -> Result<SubscriptionHandle, Box<dyn std::error::Error>>
+    where
+        F: FnMut(MempoolEvent) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.subscribe_async_with_options(
+            subscription,
+            CallOptions::default(),
+            concurrency,
+            on_data,
+        )
+        .await
+    }
 
-        tokio::spawn(async move {
-            while let Some(response) = stream.message().await.unwrap_or(None) {
-                on_data(response);
-            }
-        });
+    /// Like [`subscribe_with_options`](Self::subscribe_with_options), with an async callback run
+    /// according to `concurrency`.
+    pub async fn subscribe_async_with_options<F, Fut>(
+        &self,
+        subscription: mempool_subscription::Subscription,
+        options: CallOptions,
+        concurrency: Concurrency,
+        mut on_data: F,
+    ) -> Result<SubscriptionHandle, Box<dyn std::error::Error>>
+    where
+        F: FnMut(MempoolEvent) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let id = SubscriptionId::next();
+        let kind = telemetry::subscription_kind(&subscription);
+        let latency = self.latency.clone();
+
+        self.start_mempool(
+            id,
+            subscription,
+            options,
+            concurrency,
+            move |packet, received| {
+                let mut event = MempoolEvent::from_packet_lossy(packet, |error| {
+                    telemetry::decode_failure(kind, &error)
+                });
+                latency.observe(id, kind, &mut event, received);
+                on_data(event)
+            },
+        )
+        .await
+    }
+
+    /// Packets are passed as sent, along with their receive time. Their one-way latency is
+    /// recorded in [`latency_stats`](Self::latency_stats) like for decoded subscriptions.
+    pub async fn subscribe_raw_with_options<F>(
+        &self,
+        subscription: mempool_subscription::Subscription,
+        options: CallOptions,
+        on_data: F,
+    ) -> Result<SubscriptionHandle, Box<dyn std::error::Error>>
+    where
+        F: Fn(proto::dto::MempoolPacket, ReceivedAt) + Send + 'static,
+    {
+        let id = SubscriptionId::next();
+        let kind = telemetry::subscription_kind(&subscription);
+        let latency = self.latency.clone();
+
+        self.start_mempool(
+            id,
+            subscription,
+            options,
+            Concurrency::Sequential,
+            move |packet, received| {
+                let server_time = packet
+                    .server_ts
+                    .clone()
+                    .and_then(|timestamp| SystemTime::try_from(timestamp).ok());
+                latency.observe_packet(id, kind, server_time, received);
+                on_data(packet, received);
+                future::ready(())
+            },
+        )
+        .await
+    }
+
+    async fn start_mempool<F, Fut>(
+        &self,
+        id: SubscriptionId,
+        subscription: mempool_subscription::Subscription,
+        options: CallOptions,
+        concurrency: Concurrency,
+        on_data: F,
+    ) -> Result<SubscriptionHandle, Box<dyn std::error::Error>>
+    where
+        F: FnMut(proto::dto::MempoolPacket, ReceivedAt) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let kind = telemetry::subscription_kind(&subscription);
+        let span = tracing::info_span!("subscription", kind, endpoint = %self.endpoint);
+        let stream = self
+            .open_mempool(subscription, &options)
+            .instrument(span.clone())
+            .await?;
+        let handle =
+            subscription::spawn_stream(stream, options, concurrency, span, id, kind, on_data);
+        self.subscriptions.insert(&handle);
+
+        Ok(handle)
+    }
 
-        Ok(())
+    pub(crate) async fn open_bundle_results(
+        &self,
+        options: &CallOptions,
+    ) -> Result<Streaming<proto::searcher::BundleResult>, Box<dyn std::error::Error>> {
+        let request = options.request(SubscribeBundleResultsRequest {})?;
+        let timer = RpcTimer::start("subscribe_bundle_results");
+        let response = options
+            .run_call(
+                self.searcher_client
+                    .clone()
+                    .subscribe_bundle_results(request),
+            )
+            .await;
+        timer.finish(matches!(response, Ok(Ok(_))));
+        log_failure(&response, "opening the subscription");
+        let response = response??;
+        tracing::debug!("subscription opened");
+
+        Ok(response.into_inner())
+    }
+
+    pub(crate) async fn open_mempool(
+        &self,
+        subscription: mempool_subscription::Subscription,
+        options: &CallOptions,
+    ) -> Result<Streaming<proto::dto::MempoolPacket>, Box<dyn std::error::Error>> {
+        let request = options.request(MempoolSubscription {
+            subscription: Some(subscription),
+        })?;
+        let timer = RpcTimer::start("subscribe_mempool");
+        let response = options
+            .run_call(self.searcher_client.clone().subscribe_mempool(request))
+            .await;
+        timer.finish(matches!(response, Ok(Ok(_))));
+        log_failure(&response, "opening the subscription");
+        let response = response??;
+        tracing::debug!("subscription opened");
+
+        Ok(response.into_inner())
     }
 
     pub async fn subscribe_by_addresses<F>(
-        &mut self,
+        &self,
         addresses: Vec<String>,
         on_data: F,
-    ) -> Result<(), Box<dyn std::error::Error>>
+    ) -> Result<SubscriptionHandle, Box<dyn std::error::Error>>
     where
-        F: Fn(proto::dto::MempoolPacket) + Send + 'static,
+        F: Fn(MempoolEvent) + Send + 'static,
     {
         self.subscribe(
             mempool_subscription::Subscription::Addresses(AddressSubscriptionV0 {
@@ -148,12 +478,12 @@ impl SovaSearcher {
     }
 
     pub async fn subscribe_by_workchain<F>(
-        &mut self,
+        &self,
         workchain_id: i32,
         on_data: F,
-    ) -> Result<(), Box<dyn std::error::Error>>
+    ) -> Result<SubscriptionHandle, Box<dyn std::error::Error>>
     where
-        F: Fn(proto::dto::MempoolPacket) + Send + 'static,
+        F: Fn(MempoolEvent) + Send + 'static,
     {
         self.subscribe(
             mempool_subscription::Subscription::Workchain(WorkchainSubscriptionV0 { workchain_id }),
@@ -163,18 +493,18 @@ impl SovaSearcher {
     }
 
     pub async fn subscribe_by_workchain_shard<F>(
-        &mut self,
+        &self,
         workchain_id: i32,
-        shard: Vec<u8>,
+        shard: ShardId,
         on_data: F,
-    ) -> Result<(), Box<dyn std::error::Error>>
+    ) -> Result<SubscriptionHandle, Box<dyn std::error::Error>>
     where
-        F: Fn(proto::dto::MempoolPacket) + Send + 'static,
+        F: Fn(MempoolEvent) + Send + 'static,
     {
         self.subscribe(
             mempool_subscription::Subscription::WorkchainShard(WorkchainShardSubscriptionV0 {
                 workchain_id,
-                shard,
+                shard: shard.to_bytes(),
             }),
             on_data,
         )
@@ -182,21 +512,21 @@ impl SovaSearcher {
     }
 
     pub async fn subscribe_by_external_out_msg_body_opcode<F>(
-        &mut self,
+        &self,
         workchain_id: i32,
-        shard: Option<Vec<u8>>,
-        opcode: i32,
+        shard: Option<ShardId>,
+        opcode: u32,
         on_data: F,
-    ) -> Result<(), Box<dyn std::error::Error>>
+    ) -> Result<SubscriptionHandle, Box<dyn std::error::Error>>
     where
-        F: Fn(proto::dto::MempoolPacket) + Send + 'static,
+        F: Fn(MempoolEvent) + Send + 'static,
     {
         self.subscribe(
             mempool_subscription::Subscription::ExternalOutMessageBodyOpcode(
                 ExternalOutMessageBodyOpcodeSubscriptionV0 {
                     workchain_id,
-                    shard,
-                    opcode,
+                    shard: shard.map(ShardId::to_bytes),
+                    opcode: opcode as i32,
                 },
             ),
             on_data,
@@ -205,21 +535,21 @@ impl SovaSearcher {
     }
 
     pub async fn subscribe_by_internal_msg_body_opcode<F>(
-        &mut self,
+        &self,
         workchain_id: i32,
-        shard: Option<Vec<u8>>,
-        opcode: i32,
+        shard: Option<ShardId>,
+        opcode: u32,
         on_data: F,
-    ) -> Result<(), Box<dyn std::error::Error>>
+    ) -> Result<SubscriptionHandle, Box<dyn std::error::Error>>
     where
-        F: Fn(proto::dto::MempoolPacket) + Send + 'static,
+        F: Fn(MempoolEvent) + Send + 'static,
     {
         self.subscribe(
             mempool_subscription::Subscription::InternalMessageBodyOpcode(
                 InternalMessageBodyOpcodeSubscriptionV0 {
                     workchain_id,
-                    shard,
-                    opcode,
+                    shard: shard.map(ShardId::to_bytes),
+                    opcode: opcode as i32,
                 },
             ),
             on_data,
@@ -227,44 +557,141 @@ impl SovaSearcher {
         .await
     }
 
+    /// Opens each of `subscriptions`, usually built by `cover`, e.g. with
+    /// [`ShardCover::workchain_shard_subscriptions`], and delivers only the messages addressed to
+    /// the cover's addresses. Events left without messages are dropped. If any subscription
+    /// fails to open, the ones already opened are cancelled.
+    pub async fn subscribe_by_shard_cover<F>(
+        &self,
+        cover: &ShardCover,
+        subscriptions: Vec<mempool_subscription::Subscription>,
+        on_data: F,
+    ) -> Result<Vec<SubscriptionHandle>, Box<dyn std::error::Error>>
+    where
+        F: Fn(MempoolEvent) + Send + Sync + 'static,
+    {
+        let cover = Arc::new(cover.clone());
+        let on_data = Arc::new(on_data);
+        let mut handles = Vec::with_capacity(subscriptions.len());
+
+        for subscription in subscriptions {
+            let cover = cover.clone();
+            let on_data = on_data.clone();
+            let handle = self
+                .subscribe(subscription, move |event| {
+                    if let Some(event) = cover.filter(event) {
+                        on_data(event);
+                    }
+                })
+                .await;
+
+            match handle {
+                Ok(handle) => handles.push(handle),
+                Err(error) => {
+                    handles.iter().for_each(SubscriptionHandle::cancel);
+                    return Err(error);
+                }
+            }
+        }
+
+        Ok(handles)
+    }
+
     pub async fn send_bundle(
-        &mut self,
+        &self,
         bundle: proto::dto::Bundle,
     ) -> Result<SendBundleResponse, Box<dyn std::error::Error>> {
-        let mut request = tonic::Request::new(bundle);
-
-        if let Some(access_token) = &self.access_token {
-            request.metadata_mut().insert(
-                "authorization",
-                tonic::metadata::MetadataValue::from_str(&format!(
-                    "Bearer {}",
-                    access_token.value
-                ))?,
-            );
+        self.send_bundle_with_options(bundle, self.default_options.clone())
+            .await
+    }
+
+    #[tracing::instrument(
+        name = "send_bundle",
+        skip_all,
+        fields(
+            endpoint = %self.endpoint,
+            messages = bundle.message.len(),
+            bundle_id = tracing::field::Empty,
+        )
+    )]
+    pub async fn send_bundle_with_options(
+        &self,
+        bundle: proto::dto::Bundle,
+        options: CallOptions,
+    ) -> Result<SendBundleResponse, Box<dyn std::error::Error>> {
+        let signature = match &self.bundle_signer {
+            Some((signer, public_key)) => {
+                Some(bundle_signing::sign_bundle_as(signer.as_ref(), *public_key, &bundle).await?)
+            }
+            None => None,
+        };
+        let mut request = options.request(bundle)?;
+        if let Some(signature) = signature {
+            signature.to_metadata(request.metadata_mut());
         }
 
-        let response = self.searcher_client.send_bundle(request).await?;
+        let timer = RpcTimer::start("send_bundle");
+        let response = options
+            .run_call(self.searcher_client.clone().send_bundle(request))
+            .await;
+        let sent = matches!(response, Ok(Ok(_)));
+        timer.finish(sent);
+        telemetry::bundle_sent(sent);
+        log_failure(&response, "send_bundle");
+        let response = response??.into_inner();
 
-        Ok(response.into_inner())
+        Span::current().record("bundle_id", response.id.as_str());
+        tracing::info!("bundle accepted");
+
+        Ok(response)
     }
 
-    pub async fn get_tip_addresses(
-        &mut self,
-    ) -> Result<GetTipAddressesResponse, Box<dyn std::error::Error>> {
-        let mut request = tonic::Request::new(GetTipAddressesRequest::default());
+    /// Sends the bundle built by `build` for the tip of each attempt, sending it again for the
+    /// next blocks as `policy` allows. Each attempt is reported to `on_attempt` once its result
+    /// arrives on the bundle results stream, or its wait times out, and again if its result
+    /// arrives while a later attempt is pending. Fails only if a send fails.
+    pub async fn send_bundle_with_resubmission<B, F>(
+        &self,
+        policy: &ResubmitPolicy,
+        build: B,
+        on_attempt: F,
+    ) -> Result<Resubmission, Box<dyn std::error::Error>>
+    where
+        B: FnMut(u64) -> Bundle,
+        F: FnMut(&Attempt),
+    {
+        resubmit::run(self, policy, build, on_attempt).await
+    }
 
-        if let Some(access_token) = &self.access_token {
-            request.metadata_mut().insert(
-                "authorization",
-                tonic::metadata::MetadataValue::from_str(&format!(
-                    "Bearer {}",
-                    access_token.value
-                ))?,
-            );
-        }
+    pub async fn get_tip_addresses(&self) -> Result<TipAccounts, Box<dyn std::error::Error>> {
+        self.get_tip_addresses_with_options(self.default_options.clone())
+            .await
+    }
 
-        let response = self.searcher_client.get_tip_addresses(request).await?;
+    #[tracing::instrument(name = "get_tip_addresses", skip_all, fields(endpoint = %self.endpoint))]
+    pub async fn get_tip_addresses_with_options(
+        &self,
+        options: CallOptions,
+    ) -> Result<TipAccounts, Box<dyn std::error::Error>> {
+        let request = options.request(GetTipAddressesRequest::default())?;
+
+        let timer = RpcTimer::start("get_tip_addresses");
+        let response = options
+            .run_call(self.searcher_client.clone().get_tip_addresses(request))
+            .await;
+        timer.finish(matches!(response, Ok(Ok(_))));
+        log_failure(&response, "get_tip_addresses");
+        let tips = TipAccounts::from(response??.into_inner());
+        tracing::debug!(tips = tips.len(), "tip addresses received");
+
+        Ok(tips)
+    }
+}
 
-        Ok(response.into_inner())
+fn log_failure<T>(response: &Result<Result<T, tonic::Status>, SovaError>, call: &str) {
+    match response {
+        Ok(Ok(_)) => {}
+        Ok(Err(status)) => telemetry::log_error(call, status),
+        Err(error) => telemetry::log_error(call, error),
     }
 }
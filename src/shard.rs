@@ -0,0 +1,277 @@
+//! Routing account watchlists to shard subscriptions.
+//!
+//! A [`ShardCover`] maps a set of [`Address`]es to the shards that hold them at a given split
+//! depth. Subscribing to those shards instead of every address, then
+//! [filtering](ShardCover::matches) locally, keeps a large watchlist to a few streams.
+
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+use base64::Engine;
+
+use crate::boc;
+use crate::error::SovaError;
+use crate::proto::searcher::{
+    mempool_subscription::Subscription, ExternalOutMessageBodyOpcodeSubscriptionV0,
+    InternalMessageBodyOpcodeSubscriptionV0, WorkchainShardSubscriptionV0,
+};
+use crate::types::{MempoolEvent, MempoolMessage, ShardId};
+
+/// The masterchain is never split.
+const MASTERCHAIN: i32 = -1;
+
+/// A TON account address. Parses both the raw `workchain:hex` form and the base64
+/// user-friendly form, and displays as raw.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Address {
+    pub workchain: i32,
+    pub account: [u8; 32],
+}
+
+impl Address {
+    pub fn new(workchain: i32, account: [u8; 32]) -> Self {
+        Self { workchain, account }
+    }
+
+    /// The shard holding this account at split `depth`.
+    pub fn shard(&self, depth: u8) -> ShardId {
+        if self.workchain == MASTERCHAIN {
+            return ShardId::ROOT;
+        }
+        ShardId::for_account(&self.account, depth)
+    }
+
+    fn parse_friendly(address: &str) -> Option<Self> {
+        let bytes = if address.contains(['-', '_']) {
+            base64::engine::general_purpose::URL_SAFE.decode(address)
+        } else {
+            base64::engine::general_purpose::STANDARD.decode(address)
+        }
+        .ok()?;
+        let bytes: [u8; 36] = bytes.try_into().ok()?;
+        if crc16(&bytes[..34]).to_be_bytes() != bytes[34..] {
+            return None;
+        }
+
+        Some(Self::new(
+            i32::from(bytes[1] as i8),
+            bytes[2..34].try_into().unwrap(),
+        ))
+    }
+}
+
+impl FromStr for Address {
+    type Err = SovaError;
+
+    fn from_str(address: &str) -> Result<Self, Self::Err> {
+        let invalid = || SovaError::InvalidAddress(address.to_owned());
+
+        let Some((workchain, account)) = address.split_once(':') else {
+            return Self::parse_friendly(address).ok_or_else(invalid);
+        };
+        let account = hex::decode(account).map_err(|_| invalid())?;
+
+        Ok(Self::new(
+            workchain.parse().map_err(|_| invalid())?,
+            account.try_into().map_err(|_| invalid())?,
+        ))
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.workchain, hex::encode(self.account))
+    }
+}
+
+impl fmt::Debug for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Address({self})")
+    }
+}
+
+/// CRC-16/XMODEM, the checksum of user-friendly addresses.
+fn crc16(bytes: &[u8]) -> u16 {
+    let mut crc = 0u16;
+    for &byte in bytes {
+        crc ^= u16::from(byte) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// The destination of an external message serialized as a bag of cells.
+pub fn message_destination(boc: &[u8]) -> Result<Address, SovaError> {
+    let (data, len) = boc::root_data(boc)?;
+    let mut bits = Bits {
+        data,
+        len,
+        position: 0,
+    };
+    let invalid = |reason: &str| SovaError::InvalidBoc(format!("external message {reason}"));
+
+    // ext_in_msg_info$10 src:MsgAddressExt dest:MsgAddressInt
+    if bits.read(2)? != 0b10 {
+        return Err(invalid("has no ext_in_msg_info header"));
+    }
+    match bits.read(2)? {
+        0b00 => {}
+        0b01 => {
+            let len = bits.read(9)? as usize;
+            bits.skip(len)?;
+        }
+        _ => return Err(invalid("has an invalid source")),
+    }
+
+    // addr_std$10 anycast:(Maybe Anycast) workchain_id:int8 address:bits256
+    if bits.read(2)? != 0b10 {
+        return Err(invalid("destination is not a standard address"));
+    }
+    if bits.read(1)? == 1 {
+        let depth = bits.read(5)? as usize;
+        bits.skip(depth)?;
+    }
+    let workchain = i32::from(bits.read(8)? as u8 as i8);
+    let mut account = [0u8; 32];
+    for byte in &mut account {
+        *byte = bits.read(8)? as u8;
+    }
+
+    Ok(Address::new(workchain, account))
+}
+
+struct Bits<'a> {
+    data: &'a [u8],
+    len: usize,
+    position: usize,
+}
+
+impl Bits<'_> {
+    fn read(&mut self, count: usize) -> Result<u64, SovaError> {
+        if self.position + count > self.len {
+            return Err(SovaError::InvalidBoc("cell data too short".into()));
+        }
+        let mut value = 0;
+        for _ in 0..count {
+            let bit = (self.data[self.position / 8] >> (7 - self.position % 8)) & 1;
+            value = (value << 1) | u64::from(bit);
+            self.position += 1;
+        }
+        Ok(value)
+    }
+
+    fn skip(&mut self, count: usize) -> Result<(), SovaError> {
+        if self.position + count > self.len {
+            return Err(SovaError::InvalidBoc("cell data too short".into()));
+        }
+        self.position += count;
+        Ok(())
+    }
+}
+
+/// The shards holding a set of addresses at one split depth, and the addresses themselves for
+/// filtering what those shard subscriptions deliver.
+#[derive(Clone, Debug, Default)]
+pub struct ShardCover {
+    depth: u8,
+    addresses: HashSet<Address>,
+    shards: BTreeSet<(i32, ShardId)>,
+}
+
+impl ShardCover {
+    /// Covers `addresses` with shards at split `depth`, capped at
+    /// [`ShardId::MAX_SPLIT_DEPTH`]. Use the current split depth of the workchain so that each
+    /// shard matches the shards the engine reports messages in.
+    pub fn new(addresses: impl IntoIterator<Item = Address>, depth: u8) -> Self {
+        let depth = depth.min(ShardId::MAX_SPLIT_DEPTH);
+        let addresses: HashSet<_> = addresses.into_iter().collect();
+        let shards = addresses
+            .iter()
+            .map(|address| (address.workchain, address.shard(depth)))
+            .collect();
+
+        Self {
+            depth,
+            addresses,
+            shards,
+        }
+    }
+
+    pub fn depth(&self) -> u8 {
+        self.depth
+    }
+
+    pub fn addresses(&self) -> &HashSet<Address> {
+        &self.addresses
+    }
+
+    /// The distinct `(workchain, shard)` pairs holding the addresses, in order.
+    pub fn shards(&self) -> impl Iterator<Item = (i32, ShardId)> + '_ {
+        self.shards.iter().copied()
+    }
+
+    pub fn contains(&self, address: &Address) -> bool {
+        self.addresses.contains(address)
+    }
+
+    /// Whether `message` is addressed to one of the addresses. Messages whose destination
+    /// cannot be read do not match.
+    pub fn matches(&self, message: &MempoolMessage) -> bool {
+        message
+            .destination()
+            .is_ok_and(|destination| self.contains(&destination))
+    }
+
+    /// Keeps the messages addressed to one of the addresses, or returns `None` if none are.
+    pub fn filter(&self, mut event: MempoolEvent) -> Option<MempoolEvent> {
+        event.messages.retain(|message| self.matches(message));
+        (!event.messages.is_empty()).then_some(event)
+    }
+
+    /// One workchain-shard subscription per shard.
+    pub fn workchain_shard_subscriptions(&self) -> Vec<Subscription> {
+        self.shards()
+            .map(|(workchain_id, shard)| {
+                Subscription::WorkchainShard(WorkchainShardSubscriptionV0 {
+                    workchain_id,
+                    shard: shard.to_bytes(),
+                })
+            })
+            .collect()
+    }
+
+    /// One external out message body opcode subscription per shard.
+    pub fn external_out_msg_body_opcode_subscriptions(&self, opcode: u32) -> Vec<Subscription> {
+        self.shards()
+            .map(|(workchain_id, shard)| {
+                Subscription::ExternalOutMessageBodyOpcode(
+                    ExternalOutMessageBodyOpcodeSubscriptionV0 {
+                        workchain_id,
+                        shard: Some(shard.to_bytes()),
+                        opcode: opcode as i32,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// One internal message body opcode subscription per shard.
+    pub fn internal_msg_body_opcode_subscriptions(&self, opcode: u32) -> Vec<Subscription> {
+        self.shards()
+            .map(|(workchain_id, shard)| {
+                Subscription::InternalMessageBodyOpcode(InternalMessageBodyOpcodeSubscriptionV0 {
+                    workchain_id,
+                    shard: Some(shard.to_bytes()),
+                    opcode: opcode as i32,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,145 @@
+//! Signers used by [`SovaAuth`](crate::auth::SovaAuth) to answer the auth challenge, and
+//! optionally to [sign bundles](crate::bundle_signing), so the private key can live in memory
+//! or behind a separate signing daemon.
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+
+use crate::error::SovaError;
+
+pub type SignerError = Box<dyn std::error::Error + Send + Sync>;
+
+#[tonic::async_trait]
+pub trait ChallengeSigner: Send + Sync {
+    /// Public key announced to the auth service when requesting a challenge.
+    async fn public_key(&self) -> Result<VerifyingKey, SignerError>;
+
+    /// Signs the raw challenge bytes returned by the auth service.
+    async fn sign_challenge(&self, challenge: &[u8]) -> Result<Signature, SignerError>;
+}
+
+#[tonic::async_trait]
+impl ChallengeSigner for SigningKey {
+    async fn public_key(&self) -> Result<VerifyingKey, SignerError> {
+        Ok(self.verifying_key())
+    }
+
+    async fn sign_challenge(&self, challenge: &[u8]) -> Result<Signature, SignerError> {
+        Ok(self.sign(challenge))
+    }
+}
+
+#[cfg(unix)]
+pub use self::unix::UnixSocketSigner;
+
+#[cfg(unix)]
+mod unix {
+    use std::path::{Path, PathBuf};
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::UnixStream;
+    use tokio::sync::OnceCell;
+
+    use super::*;
+
+    const OP_PUBLIC_KEY: u8 = 0x01;
+    const OP_SIGN: u8 = 0x02;
+    const STATUS_OK: u8 = 0x00;
+    const MAX_FRAME_LEN: usize = 64 * 1024;
+
+    /// Signer backed by a signing daemon listening on a Unix domain socket.
+    ///
+    /// Every call opens a fresh connection and exchanges a single frame. Requests are
+    /// `[op: u8][len: u32 BE][payload]`, where `op` is `0x01` to fetch the public key (empty
+    /// payload) or `0x02` to sign the payload. Responses are `[status: u8][len: u32 BE][payload]`;
+    /// status `0x00` carries the 32-byte public key or the 64-byte signature, any other status
+    /// carries a UTF-8 error message.
+    ///
+    /// Signatures returned by the daemon are verified against its public key before use.
+    pub struct UnixSocketSigner {
+        path: PathBuf,
+        public_key: OnceCell<VerifyingKey>,
+    }
+
+    impl UnixSocketSigner {
+        pub fn new(path: impl AsRef<Path>) -> Self {
+            Self {
+                path: path.as_ref().to_owned(),
+                public_key: OnceCell::new(),
+            }
+        }
+
+        /// Pins the expected public key instead of asking the daemon for it.
+        pub fn with_public_key(path: impl AsRef<Path>, public_key: VerifyingKey) -> Self {
+            Self {
+                path: path.as_ref().to_owned(),
+                public_key: OnceCell::new_with(Some(public_key)),
+            }
+        }
+
+        async fn call(&self, op: u8, payload: &[u8]) -> Result<Vec<u8>, SignerError> {
+            let mut stream = UnixStream::connect(&self.path).await?;
+
+            let mut frame = Vec::with_capacity(5 + payload.len());
+            frame.push(op);
+            frame.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+            frame.extend_from_slice(payload);
+            stream.write_all(&frame).await?;
+
+            let status = stream.read_u8().await?;
+            let len = stream.read_u32().await? as usize;
+            if len > MAX_FRAME_LEN {
+                return Err(Box::new(SovaError::RemoteSigner(format!(
+                    "response of {len} bytes exceeds the frame limit"
+                ))));
+            }
+
+            let mut response = vec![0u8; len];
+            stream.read_exact(&mut response).await?;
+
+            if status != STATUS_OK {
+                return Err(Box::new(SovaError::RemoteSigner(
+                    String::from_utf8_lossy(&response).into_owned(),
+                )));
+            }
+
+            Ok(response)
+        }
+    }
+
+    #[tonic::async_trait]
+    impl ChallengeSigner for UnixSocketSigner {
+        async fn public_key(&self) -> Result<VerifyingKey, SignerError> {
+            let public_key = self
+                .public_key
+                .get_or_try_init(|| async {
+                    let bytes: [u8; 32] = self
+                        .call(OP_PUBLIC_KEY, &[])
+                        .await?
+                        .try_into()
+                        .map_err(|_| SovaError::RemoteSigner("malformed public key".into()))?;
+
+                    Ok::<_, SignerError>(VerifyingKey::from_bytes(&bytes)?)
+                })
+                .await?;
+
+            Ok(*public_key)
+        }
+
+        async fn sign_challenge(&self, challenge: &[u8]) -> Result<Signature, SignerError> {
+            let public_key = self.public_key().await?;
+
+            let bytes: [u8; 64] = self
+                .call(OP_SIGN, challenge)
+                .await?
+                .try_into()
+                .map_err(|_| SovaError::RemoteSigner("malformed signature".into()))?;
+            let signature = Signature::from_bytes(&bytes);
+
+            public_key
+                .verify(challenge, &signature)
+                .map_err(|_| SovaError::InvalidSignature)?;
+
+            Ok(signature)
+        }
+    }
+}
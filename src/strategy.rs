@@ -0,0 +1,411 @@
+//! A runtime for searcher strategies: [`StrategyRunner`] subscribes to each [`Strategy`]'s
+//! mempool filters, keeps the tip accounts fresh, routes bundle results back to the strategy that
+//! sent the bundle, and shuts everything down together.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use futures_util::future::select_all;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
+
+use crate::bundle_stats::BundleStats;
+use crate::call_options::CallOptions;
+use crate::delivery::Concurrency;
+use crate::error::SovaError;
+use crate::proto::dto::Bundle;
+use crate::proto::searcher::mempool_subscription;
+use crate::searcher::SovaSearcher;
+use crate::subscription::{EndReason, SubscriptionHandle};
+use crate::types::{BundleUpdate, MempoolEvent, TipAccounts};
+
+/// How long a sent bundle waits for its result before the runner forgets it.
+const RESULT_RETENTION: Duration = Duration::from_secs(60 * 60);
+
+/// How long a result for an unknown bundle is held in case the send that produced it has not
+/// returned yet.
+const EARLY_RESULT_WINDOW: Duration = Duration::from_secs(30);
+
+/// A searcher strategy run by [`StrategyRunner`]. Callbacks may run concurrently, so keep
+/// mutable state behind a lock.
+#[tonic::async_trait]
+pub trait Strategy: Send + Sync + 'static {
+    /// Name used in logs.
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+
+    /// Mempool filters to subscribe to. Each one is a separate subscription.
+    fn filters(&self) -> Vec<mempool_subscription::Subscription>;
+
+    /// Called for every event matching one of the filters. Send bundles through
+    /// [`StrategyContext::send_bundle`] to have their results routed back here.
+    async fn on_packet(&self, ctx: &StrategyContext, event: MempoolEvent);
+
+    /// Called with the result of a bundle this strategy sent.
+    async fn on_bundle_result(&self, _ctx: &StrategyContext, _update: BundleUpdate) {}
+
+    /// Called once after the subscriptions stopped and every callback finished.
+    async fn on_shutdown(&self, _ctx: &StrategyContext) {}
+}
+
+/// State shared between a strategy and the runner.
+#[derive(Clone)]
+pub struct StrategyContext {
+    strategy: usize,
+    searcher: SovaSearcher,
+    tips: Arc<RwLock<TipAccounts>>,
+    routes: Arc<Mutex<Routes>>,
+    results: mpsc::UnboundedSender<BundleUpdate>,
+    stats: Option<(String, BundleStats)>,
+    shutdown: CancellationToken,
+}
+
+impl StrategyContext {
+    /// The latest tip accounts, refreshed by the runner in the background.
+    pub fn tips(&self) -> TipAccounts {
+        self.tips.read().unwrap().clone()
+    }
+
+    pub fn searcher(&self) -> &SovaSearcher {
+        &self.searcher
+    }
+
+    /// Sends the bundle and tracks its id, so the result reaches
+    /// [`Strategy::on_bundle_result`]. Returns the bundle id.
+    pub async fn send_bundle(&self, bundle: Bundle) -> Result<String, Box<dyn std::error::Error>> {
+        self.send(bundle, None).await
+    }
+
+    /// Like [`send_bundle`](Self::send_bundle), also recording the `tip` the bundle pays in
+    /// the runner's [`BundleStats`].
+    pub async fn send_bundle_with_tip(
+        &self,
+        bundle: Bundle,
+        tip: u64,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        self.send(bundle, Some(tip)).await
+    }
+
+    async fn send(
+        &self,
+        bundle: Bundle,
+        tip: Option<u64>,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let id = self.searcher.send_bundle(bundle).await?.id;
+        if let Some((name, stats)) = &self.stats {
+            stats.track(id.clone(), name.clone(), tip);
+        }
+
+        let mut routes = self.routes.lock().unwrap();
+        routes
+            .sent
+            .insert(id.clone(), (self.strategy, Instant::now()));
+        if let Some((update, _)) = routes.early.remove(&id) {
+            // The result arrived before the send returned.
+            let _ = self.results.send(update);
+        }
+
+        Ok(id)
+    }
+
+    /// Bundles sent by any strategy that are still waiting for a result.
+    pub fn pending_bundles(&self) -> usize {
+        self.routes.lock().unwrap().sent.len()
+    }
+
+    /// Whether the runner is shutting down.
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutdown.is_cancelled()
+    }
+}
+
+/// The strategy that sent each bundle, and results for bundles not known yet.
+#[derive(Default)]
+struct Routes {
+    sent: HashMap<String, (usize, Instant)>,
+    early: HashMap<String, (BundleUpdate, Instant)>,
+}
+
+impl Routes {
+    /// The strategy that sent the bundle of `update`. Results for unknown bundles are held back
+    /// until their send returns, see [`StrategyContext::send_bundle`].
+    fn route(&mut self, update: BundleUpdate, now: Instant) -> Option<(usize, BundleUpdate)> {
+        match self.sent.remove(&update.bundle_id) {
+            Some((strategy, _)) => Some((strategy, update)),
+            None => {
+                self.early.insert(update.bundle_id.clone(), (update, now));
+                None
+            }
+        }
+    }
+
+    /// Forgets bundles that never got a result, and returns the held back results that no send
+    /// claimed.
+    fn prune(&mut self, now: Instant) -> Vec<BundleUpdate> {
+        self.sent
+            .retain(|_, (_, at)| now.duration_since(*at) <= RESULT_RETENTION);
+
+        let expired = self
+            .early
+            .iter()
+            .filter(|(_, (_, at))| now.duration_since(*at) > EARLY_RESULT_WINDOW)
+            .map(|(id, _)| id.clone())
+            .collect::<Vec<_>>();
+        expired
+            .into_iter()
+            .filter_map(|id| self.early.remove(&id))
+            .map(|(update, _)| update)
+            .collect()
+    }
+}
+
+struct Started {
+    contexts: Vec<StrategyContext>,
+    subscriptions: Vec<SubscriptionHandle>,
+    in_flight: mpsc::Sender<()>,
+    finished: mpsc::Receiver<()>,
+}
+
+/// Runs several [`Strategy`] implementations on one [`SovaSearcher`].
+pub struct StrategyRunner {
+    searcher: SovaSearcher,
+    strategies: Vec<Arc<dyn Strategy>>,
+    concurrency: Concurrency,
+    tip_refresh_interval: Duration,
+    stats: Option<BundleStats>,
+    shutdown: CancellationToken,
+}
+
+impl StrategyRunner {
+    pub fn new(searcher: SovaSearcher) -> Self {
+        Self {
+            searcher,
+            strategies: Vec::new(),
+            concurrency: Concurrency::Sequential,
+            tip_refresh_interval: Duration::from_secs(60),
+            stats: None,
+            shutdown: CancellationToken::new(),
+        }
+    }
+
+    pub fn strategy(mut self, strategy: impl Strategy) -> Self {
+        self.strategies.push(Arc::new(strategy));
+        self
+    }
+
+    /// How many [`Strategy::on_packet`] calls each subscription may run at once. Defaults to
+    /// [`Concurrency::Sequential`].
+    pub fn concurrency(mut self, concurrency: Concurrency) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    pub fn tip_refresh_interval(mut self, interval: Duration) -> Self {
+        self.tip_refresh_interval = interval;
+        self
+    }
+
+    /// Records every bundle result in `stats`, tagging bundles sent through
+    /// [`StrategyContext::send_bundle`] with the strategy name, and with their tip when sent
+    /// through [`StrategyContext::send_bundle_with_tip`].
+    pub fn bundle_stats(mut self, stats: BundleStats) -> Self {
+        self.stats = Some(stats);
+        self
+    }
+
+    /// Cancelling this token makes [`run`](Self::run) shut down.
+    pub fn shutdown_token(&self) -> CancellationToken {
+        self.shutdown.clone()
+    }
+
+    /// Starts every strategy and runs until the shutdown token is cancelled or a subscription
+    /// ends on its own. Then stops the subscriptions, waits for running callbacks and calls
+    /// [`Strategy::on_shutdown`].
+    ///
+    /// Fails if the tip accounts cannot be fetched, a subscription cannot be opened, or a
+    /// subscription ends without being cancelled.
+    pub async fn run(self) -> Result<(), Box<dyn std::error::Error>> {
+        let result = self.start().await;
+        if result.is_err() {
+            self.shutdown.cancel();
+        }
+
+        let Started {
+            contexts,
+            subscriptions,
+            in_flight,
+            mut finished,
+        } = result?;
+        let ended = select_all(
+            subscriptions
+                .iter()
+                .map(|handle| Box::pin(async move { (handle.kind(), handle.join().await) })),
+        );
+        let failure = tokio::select! {
+            _ = self.shutdown.cancelled() => None,
+            ((kind, reason), _, _) = ended => (reason != EndReason::Cancelled).then(|| {
+                tracing::warn!(kind, ?reason, "subscription ended, shutting down strategies");
+                SovaError::SubscriptionEnded { kind, reason }
+            }),
+        };
+        self.shutdown.cancel();
+        tracing::info!("shutting down strategies");
+
+        // Each callback and subscription holds a sender; the channel closes once all are gone.
+        drop(in_flight);
+        finished.recv().await;
+
+        for (strategy, ctx) in self.strategies.iter().zip(&contexts) {
+            strategy
+                .on_shutdown(ctx)
+                .instrument(tracing::info_span!("strategy", name = strategy.name()))
+                .await;
+        }
+
+        match failure {
+            Some(error) => Err(Box::new(error)),
+            None => Ok(()),
+        }
+    }
+
+    async fn start(&self) -> Result<Started, Box<dyn std::error::Error>> {
+        let searcher = &self.searcher;
+        let tips = Arc::new(RwLock::new(searcher.get_tip_addresses().await?));
+        let routes = Arc::new(Mutex::new(Routes::default()));
+        let (results_tx, results) = mpsc::unbounded_channel();
+        let options = CallOptions::new().cancellation(self.shutdown.clone());
+        let (in_flight, finished) = mpsc::channel(1);
+
+        self.spawn_tip_refresh(Arc::clone(&tips));
+
+        let contexts = self
+            .strategies
+            .iter()
+            .enumerate()
+            .map(|(index, strategy)| StrategyContext {
+                strategy: index,
+                searcher: self.searcher.clone(),
+                tips: Arc::clone(&tips),
+                routes: Arc::clone(&routes),
+                results: results_tx.clone(),
+                stats: self
+                    .stats
+                    .clone()
+                    .map(|stats| (strategy.name().to_owned(), stats)),
+                shutdown: self.shutdown.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        let mut subscriptions = vec![
+            searcher
+                .subscribe_bundle_results_with_options(options.clone(), move |update| {
+                    let _ = results_tx.send(update);
+                })
+                .await?,
+        ];
+        self.spawn_result_routing(results, routes, contexts.clone(), in_flight.clone());
+
+        for (strategy, ctx) in self.strategies.iter().zip(&contexts) {
+            for filter in strategy.filters() {
+                let strategy = Arc::clone(strategy);
+                let ctx = ctx.clone();
+                let guard = in_flight.clone();
+
+                let handle = searcher
+                    .subscribe_async_with_options(
+                        filter,
+                        options.clone(),
+                        self.concurrency,
+                        move |event| {
+                            let (strategy, ctx, guard) =
+                                (Arc::clone(&strategy), ctx.clone(), guard.clone());
+                            let span = tracing::info_span!("strategy", name = strategy.name());
+
+                            async move {
+                                strategy.on_packet(&ctx, event).await;
+                                drop(guard);
+                            }
+                            .instrument(span)
+                        },
+                    )
+                    .await?;
+                subscriptions.push(handle);
+            }
+        }
+
+        Ok(Started {
+            contexts,
+            subscriptions,
+            in_flight,
+            finished,
+        })
+    }
+
+    /// Hands each bundle result to the strategy that sent the bundle, one at a time, until
+    /// shutdown.
+    fn spawn_result_routing(
+        &self,
+        mut results: mpsc::UnboundedReceiver<BundleUpdate>,
+        routes: Arc<Mutex<Routes>>,
+        contexts: Vec<StrategyContext>,
+        guard: mpsc::Sender<()>,
+    ) {
+        let strategies = self.strategies.clone();
+        let stats = self.stats.clone();
+        let shutdown = self.shutdown.clone();
+
+        tokio::spawn(async move {
+            loop {
+                let update = tokio::select! {
+                    biased;
+                    _ = shutdown.cancelled() => break,
+                    Some(update) = results.recv() => update,
+                };
+
+                let now = Instant::now();
+                let (routed, unclaimed) = {
+                    let mut routes = routes.lock().unwrap();
+                    (routes.route(update, now), routes.prune(now))
+                };
+                if let Some(stats) = &stats {
+                    // Results are recorded once routed, so that their strategy is tracked.
+                    for update in routed.iter().map(|(_, update)| update).chain(&unclaimed) {
+                        stats.record(update);
+                    }
+                }
+
+                if let Some((index, update)) = routed {
+                    let strategy = &strategies[index];
+                    strategy
+                        .on_bundle_result(&contexts[index], update)
+                        .instrument(tracing::info_span!("strategy", name = strategy.name()))
+                        .await;
+                }
+            }
+            drop(guard);
+        });
+    }
+
+    fn spawn_tip_refresh(&self, tips: Arc<RwLock<TipAccounts>>) {
+        let searcher = self.searcher.clone();
+        let interval = self.tip_refresh_interval;
+        let shutdown = self.shutdown.clone();
+
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = shutdown.cancelled() => break,
+                    _ = tokio::time::sleep(interval) => {}
+                }
+
+                match searcher.get_tip_addresses().await {
+                    Ok(latest) => *tips.write().unwrap() = latest,
+                    Err(error) => tracing::warn!(%error, "failed to refresh tip accounts"),
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,237 @@
+//! Handles for running subscriptions: stop them, check whether they are still running and find
+//! out why they ended.
+
+use std::fmt;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures_util::future::join_all;
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+use tonic::Streaming;
+use tracing::{Instrument, Span};
+
+use crate::call_options::CallOptions;
+use crate::delivery::{Concurrency, Delivery, Handler};
+use crate::error::SovaError;
+use crate::latency::ReceivedAt;
+use crate::telemetry;
+
+/// Why a subscription stopped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EndReason {
+    /// The server ended the stream.
+    ServerClosed,
+    /// The stream failed with an error status.
+    Failed { code: tonic::Code, message: String },
+    /// The subscription was cancelled through its handle, its call options or a shutdown.
+    Cancelled,
+    /// The delivery buffer overflowed under [`OverflowPolicy::Disconnect`].
+    ///
+    /// [`OverflowPolicy::Disconnect`]: crate::delivery::OverflowPolicy::Disconnect
+    Overflow,
+    /// The subscription task stopped without reporting a reason, e.g. because a callback
+    /// panicked.
+    Aborted,
+}
+
+/// Identifies a subscription within the process, e.g. in
+/// [`LatencyStats`](crate::latency::LatencyStats).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SubscriptionId(u64);
+
+impl SubscriptionId {
+    /// An id no other subscription in the process has.
+    pub fn next() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+
+        Self(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl fmt::Display for SubscriptionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// A running subscription. Dropping the handle leaves the subscription running; clones control
+/// the same subscription.
+#[derive(Clone, Debug)]
+pub struct SubscriptionHandle {
+    id: SubscriptionId,
+    kind: &'static str,
+    cancel: CancellationToken,
+    ended: watch::Receiver<Option<EndReason>>,
+}
+
+impl SubscriptionHandle {
+    pub(crate) fn new(
+        id: SubscriptionId,
+        kind: &'static str,
+        cancel: CancellationToken,
+    ) -> (Self, watch::Sender<Option<EndReason>>) {
+        let (ended_tx, ended) = watch::channel(None);
+
+        (
+            Self {
+                id,
+                kind,
+                cancel,
+                ended,
+            },
+            ended_tx,
+        )
+    }
+
+    pub fn id(&self) -> SubscriptionId {
+        self.id
+    }
+
+    /// The subscription kind used in metrics and logs, e.g. `"bundle_results"`.
+    pub fn kind(&self) -> &'static str {
+        self.kind
+    }
+
+    /// Stops the subscription. Callbacks already running are allowed to finish.
+    pub fn cancel(&self) {
+        self.cancel.cancel();
+    }
+
+    /// Whether the subscription is still running, including callbacks that have not finished.
+    pub fn is_alive(&self) -> bool {
+        self.ended.borrow().is_none() && self.ended.has_changed().is_ok()
+    }
+
+    /// Waits for the subscription and its callbacks to finish and returns why it ended.
+    pub async fn join(&self) -> EndReason {
+        let mut ended = self.ended.clone();
+        let reason = match ended.wait_for(Option::is_some).await {
+            Ok(reason) => reason.clone(),
+            Err(_) => None,
+        };
+
+        reason.unwrap_or(EndReason::Aborted)
+    }
+}
+
+/// The subscriptions started through one client, shared by its clones.
+#[derive(Clone, Default)]
+pub(crate) struct SubscriptionSet(Arc<Mutex<Vec<SubscriptionHandle>>>);
+
+impl SubscriptionSet {
+    pub(crate) fn insert(&self, handle: &SubscriptionHandle) {
+        let mut handles = self.0.lock().unwrap();
+        handles.retain(SubscriptionHandle::is_alive);
+        handles.push(handle.clone());
+    }
+
+    pub(crate) fn active(&self) -> Vec<SubscriptionHandle> {
+        let mut handles = self.0.lock().unwrap();
+        handles.retain(SubscriptionHandle::is_alive);
+        handles.clone()
+    }
+
+    /// Cancels every subscription and waits up to `deadline` for them to finish.
+    pub(crate) async fn shutdown(&self, deadline: Duration) -> Result<(), SovaError> {
+        let handles = self.active();
+        for handle in &handles {
+            handle.cancel();
+        }
+
+        let joined = tokio::time::timeout(deadline, join_all(handles.iter().map(|h| h.join())));
+        if joined.await.is_err() {
+            let remaining = handles.iter().filter(|handle| handle.is_alive()).count();
+            tracing::warn!(remaining, "subscriptions did not stop before the deadline");
+            return Err(SovaError::ShutdownTimedOut(remaining));
+        }
+
+        Ok(())
+    }
+}
+
+/// Forwards stream messages and their receive time to `on_data` from a spawned task, through
+/// the delivery buffer if one is configured, until the stream ends, fails, overflows or is
+/// cancelled.
+pub(crate) fn spawn_stream<T, F, Fut>(
+    mut stream: Streaming<T>,
+    options: CallOptions,
+    concurrency: Concurrency,
+    span: Span,
+    id: SubscriptionId,
+    kind: &'static str,
+    mut on_data: F,
+) -> SubscriptionHandle
+where
+    T: Send + 'static,
+    F: FnMut(T, ReceivedAt) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let cancel = options
+        .cancellation_token()
+        .map(CancellationToken::child_token)
+        .unwrap_or_default();
+    let (handle, ended) = SubscriptionHandle::new(id, kind, cancel.clone());
+    let options = options.cancellation(cancel);
+
+    tokio::spawn(
+        async move {
+            let mut delivery = Delivery::new(
+                options.delivery_config().cloned(),
+                kind,
+                Handler::new(concurrency, move |(message, received)| {
+                    on_data(message, received)
+                }),
+            );
+            let mut packets = 0u64;
+
+            let reason = loop {
+                match options.run(stream.message()).await {
+                    Ok(Ok(Some(message))) => {
+                        let received = ReceivedAt::now();
+                        packets += 1;
+                        telemetry::packet_received(kind);
+
+                        match delivery.deliver((message, received), &options).await {
+                            Ok(true) => {}
+                            Ok(false) => break EndReason::Overflow,
+                            Err(error) => {
+                                tracing::info!(packets, %error, "subscription stopped");
+                                break EndReason::Cancelled;
+                            }
+                        }
+                    }
+                    Ok(Ok(None)) => {
+                        tracing::info!(packets, "stream closed by the server");
+                        break EndReason::ServerClosed;
+                    }
+                    Ok(Err(status)) => {
+                        tracing::warn!(
+                            packets,
+                            code = ?status.code(),
+                            message = status.message(),
+                            "stream failed"
+                        );
+                        break EndReason::Failed {
+                            code: status.code(),
+                            message: status.message().to_owned(),
+                        };
+                    }
+                    Err(error) => {
+                        tracing::info!(packets, %error, "subscription stopped");
+                        break EndReason::Cancelled;
+                    }
+                }
+            };
+
+            drop(stream);
+            delivery.finish().await;
+            ended.send_replace(Some(reason));
+        }
+        .instrument(span),
+    );
+
+    handle
+}
@@ -0,0 +1,163 @@
+//! Metrics recorded through the [`metrics`](https://docs.rs/metrics) facade when the `metrics`
+//! feature is enabled. Install any `metrics` recorder, e.g. `metrics-exporter-prometheus`, to
+//! export them. Without the feature every call here compiles to nothing.
+//!
+//! Spans and events are emitted through [`tracing`](https://docs.rs/tracing) regardless of
+//! features. They never include token values or keys.
+
+use std::time::{Duration, Instant};
+
+use crate::delivery::OverflowPolicy;
+use crate::error::SovaError;
+use crate::proto::searcher::mempool_subscription;
+use crate::types::BundleOutcome;
+
+/// Histogram of RPC durations in seconds, labelled by `method` and `status` (`ok` or `error`).
+pub const RPC_DURATION_SECONDS: &str = "sova_rpc_duration_seconds";
+/// Messages received per stream, labelled by `subscription`.
+pub const SUBSCRIPTION_PACKETS_TOTAL: &str = "sova_subscription_packets_total";
+/// Streams reopened after a failure, labelled by `subscription`.
+pub const STREAM_RECONNECTS_TOTAL: &str = "sova_stream_reconnects_total";
+/// Token requests, labelled by `operation` (`authenticate` or `refresh`) and `status`.
+pub const TOKEN_REFRESHES_TOTAL: &str = "sova_token_refreshes_total";
+/// Bundles submitted, labelled by `status`.
+pub const BUNDLES_SENT_TOTAL: &str = "sova_bundles_sent_total";
+/// Bundles sent again by a resubmission policy after an earlier attempt failed.
+pub const BUNDLE_RESUBMISSIONS_TOTAL: &str = "sova_bundle_resubmissions_total";
+/// Bundle results received, labelled by `outcome` (see [`BundleOutcome::kind`]).
+pub const BUNDLE_RESULTS_TOTAL: &str = "sova_bundle_results_total";
+/// Subscription messages skipped because they do not decode, labelled by `subscription`.
+pub const DECODE_FAILURES_TOTAL: &str = "sova_decode_failures_total";
+/// Subscription messages that found the delivery buffer full, labelled by `subscription` and
+/// `policy` (see [`OverflowPolicy::name`]).
+pub const DELIVERY_OVERFLOWS_TOTAL: &str = "sova_delivery_overflows_total";
+/// Histogram of mempool packet one-way latency in seconds, from `server_ts` to local receipt,
+/// labelled by `subscription`.
+pub const MEMPOOL_LATENCY_SECONDS: &str = "sova_mempool_latency_seconds";
+
+/// Label for a mempool subscription filter.
+pub(crate) fn subscription_kind(subscription: &mempool_subscription::Subscription) -> &'static str {
+    match subscription {
+        mempool_subscription::Subscription::Addresses(_) => "mempool_addresses",
+        mempool_subscription::Subscription::Workchain(_) => "mempool_workchain",
+        mempool_subscription::Subscription::WorkchainShard(_) => "mempool_workchain_shard",
+        mempool_subscription::Subscription::ExternalOutMessageBodyOpcode(_) => {
+            "mempool_external_out_opcode"
+        }
+        mempool_subscription::Subscription::InternalMessageBodyOpcode(_) => {
+            "mempool_internal_opcode"
+        }
+    }
+}
+
+pub(crate) fn mempool_latency(subscription: &'static str, latency: Duration) {
+    #[cfg(feature = "metrics")]
+    metrics::histogram!(MEMPOOL_LATENCY_SECONDS, "subscription" => subscription)
+        .record(latency.as_secs_f64());
+    #[cfg(not(feature = "metrics"))]
+    let _ = (subscription, latency);
+}
+
+pub(crate) fn delivery_overflow(subscription: &'static str, policy: OverflowPolicy) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(
+        DELIVERY_OVERFLOWS_TOTAL,
+        "subscription" => subscription,
+        "policy" => policy.name()
+    )
+    .increment(1);
+    #[cfg(not(feature = "metrics"))]
+    let _ = (subscription, policy);
+}
+
+/// Logs a failed call at `warn`. Statuses are reduced to their code and message so response
+/// metadata never reaches the logs.
+pub(crate) fn log_error(call: &str, error: &(dyn std::error::Error + 'static)) {
+    match error.downcast_ref::<tonic::Status>() {
+        Some(status) => tracing::warn!(
+            code = ?status.code(),
+            message = status.message(),
+            "{call} failed"
+        ),
+        None => tracing::warn!(%error, "{call} failed"),
+    }
+}
+
+fn status(ok: bool) -> &'static str {
+    if ok {
+        "ok"
+    } else {
+        "error"
+    }
+}
+
+/// Times one RPC from creation until [`finish`](Self::finish).
+pub(crate) struct RpcTimer {
+    method: &'static str,
+    started: Instant,
+}
+
+impl RpcTimer {
+    pub(crate) fn start(method: &'static str) -> Self {
+        Self {
+            method,
+            started: Instant::now(),
+        }
+    }
+
+    pub(crate) fn finish(self, ok: bool) {
+        #[cfg(feature = "metrics")]
+        metrics::histogram!(RPC_DURATION_SECONDS, "method" => self.method, "status" => status(ok))
+            .record(self.started.elapsed().as_secs_f64());
+        #[cfg(not(feature = "metrics"))]
+        let _ = (self.method, self.started, status(ok));
+    }
+}
+
+pub(crate) fn packet_received(subscription: &'static str) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(SUBSCRIPTION_PACKETS_TOTAL, "subscription" => subscription).increment(1);
+    #[cfg(not(feature = "metrics"))]
+    let _ = subscription;
+}
+
+/// Logs and counts a subscription message skipped because it does not decode.
+pub(crate) fn decode_failure(subscription: &'static str, error: &SovaError) {
+    tracing::debug!(subscription, %error, "skipping a message that does not decode");
+    #[cfg(feature = "metrics")]
+    metrics::counter!(DECODE_FAILURES_TOTAL, "subscription" => subscription).increment(1);
+}
+
+pub(crate) fn stream_reconnected(subscription: &'static str) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(STREAM_RECONNECTS_TOTAL, "subscription" => subscription).increment(1);
+    #[cfg(not(feature = "metrics"))]
+    let _ = subscription;
+}
+
+pub(crate) fn token_refreshed(operation: &'static str, ok: bool) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(TOKEN_REFRESHES_TOTAL, "operation" => operation, "status" => status(ok))
+        .increment(1);
+    #[cfg(not(feature = "metrics"))]
+    let _ = (operation, status(ok));
+}
+
+pub(crate) fn bundle_sent(ok: bool) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(BUNDLES_SENT_TOTAL, "status" => status(ok)).increment(1);
+    #[cfg(not(feature = "metrics"))]
+    let _ = status(ok);
+}
+
+pub(crate) fn bundle_resubmitted() {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(BUNDLE_RESUBMISSIONS_TOTAL).increment(1);
+}
+
+pub(crate) fn bundle_result(outcome: &BundleOutcome) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(BUNDLE_RESULTS_TOTAL, "outcome" => outcome.kind()).increment(1);
+    #[cfg(not(feature = "metrics"))]
+    let _ = outcome;
+}
@@ -0,0 +1,180 @@
+//! Tip suggestions from recently observed auctions.
+//!
+//! [`TipAdvisor`] keeps the winning bids seen in the last results and suggests the tip that
+//! would have beaten a target share of them. It only looks at the order of observations, not
+//! at the clock, so replaying recorded results gives the same suggestions as live use.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use crate::resubmit::{Attempt, TipSchedule};
+use crate::types::BundleOutcome;
+
+/// How many winning bids [`TipAdvisor::default`] keeps.
+pub const DEFAULT_WINDOW: usize = 500;
+
+/// A recorded bundle result and the tip the bundle paid, if known.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Observation {
+    pub tip: Option<u64>,
+    pub outcome: BundleOutcome,
+}
+
+impl Observation {
+    pub fn new(tip: Option<u64>, outcome: BundleOutcome) -> Self {
+        Self { tip, outcome }
+    }
+
+    /// The result of a resubmission attempt, if it got one.
+    pub fn from_attempt(attempt: &Attempt) -> Option<Self> {
+        let outcome = attempt.outcome.clone()?;
+        Some(Self::new(Some(attempt.tip), outcome))
+    }
+
+    /// The winning bid this result reveals: the tip of an included bundle, the winning tip
+    /// [guessed](BundleOutcome::winning_tip) from a [`HigherTipWon`](BundleOutcome::HigherTipWon)
+    /// detail, or, when there is none, the tip that lost as a lower bound. Other outcomes say
+    /// nothing about the price.
+    pub fn winning_bid(&self) -> Option<u64> {
+        match &self.outcome {
+            BundleOutcome::Included => self.tip,
+            BundleOutcome::HigherTipWon(_) => self
+                .outcome
+                .winning_tip()
+                .or_else(|| self.tip.map(|tip| tip.saturating_add(1))),
+            _ => None,
+        }
+    }
+}
+
+/// Suggests tips from a model of recent winning bids. Clones share the model.
+#[derive(Clone)]
+pub struct TipAdvisor {
+    window: usize,
+    floor: u64,
+    ceiling: u64,
+    bids: Arc<Mutex<VecDeque<u64>>>,
+}
+
+impl Default for TipAdvisor {
+    fn default() -> Self {
+        Self::new(DEFAULT_WINDOW)
+    }
+}
+
+impl TipAdvisor {
+    /// Keeps the last `window` winning bids.
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: window.max(1),
+            floor: 0,
+            ceiling: u64::MAX,
+            bids: Arc::default(),
+        }
+    }
+
+    /// No suggestion is below `floor`, which is also suggested before any bid is observed.
+    pub fn floor(mut self, floor: u64) -> Self {
+        self.floor = floor;
+        self
+    }
+
+    /// No suggestion is above `ceiling`.
+    pub fn ceiling(mut self, ceiling: u64) -> Self {
+        self.ceiling = ceiling;
+        self
+    }
+
+    pub fn observe(&self, observation: &Observation) {
+        let Some(bid) = observation.winning_bid() else {
+            return;
+        };
+
+        let mut bids = self.bids.lock().unwrap();
+        if bids.len() == self.window {
+            bids.pop_front();
+        }
+        bids.push_back(bid);
+    }
+
+    /// Observes the result of a resubmission attempt, e.g. from the `on_attempt` callback of
+    /// [`send_bundle_with_resubmission`][resubmission].
+    ///
+    /// [resubmission]: crate::searcher::SovaSearcher::send_bundle_with_resubmission
+    pub fn observe_attempt(&self, attempt: &Attempt) {
+        if let Some(observation) = Observation::from_attempt(attempt) {
+            self.observe(&observation);
+        }
+    }
+
+    /// Winning bids currently in the model.
+    pub fn samples(&self) -> usize {
+        self.bids.lock().unwrap().len()
+    }
+
+    /// The smallest tip that would have matched `target` (0 to 1) of the recent winning bids,
+    /// within the floor and ceiling.
+    pub fn suggest(&self, target: f64) -> u64 {
+        let mut bids = Vec::from(self.bids.lock().unwrap().clone());
+        if bids.is_empty() {
+            return self.floor.min(self.ceiling);
+        }
+        bids.sort_unstable();
+
+        // Nearest-rank percentile.
+        let target = target.clamp(0.0, 1.0);
+        let rank = ((bids.len() as f64 * target).ceil() as usize).clamp(1, bids.len());
+
+        bids[rank - 1].clamp(self.floor, self.ceiling.max(self.floor))
+    }
+
+    /// A schedule paying the suggestion for each target in turn, e.g. `[0.5, 0.75, 0.9]` to
+    /// bid more aggressively on each resubmission.
+    pub fn schedule(&self, targets: &[f64]) -> TipSchedule {
+        TipSchedule::steps(targets.iter().map(|&target| self.suggest(target)).collect())
+    }
+
+    /// Replays recorded results in order on a fresh model with this configuration. Before
+    /// each result it suggests a tip for `target`, and counts it as winning if it is at least
+    /// the bid that won.
+    pub fn backtest<'a>(
+        &self,
+        observations: impl IntoIterator<Item = &'a Observation>,
+        target: f64,
+    ) -> Backtest {
+        let advisor = Self {
+            bids: Arc::default(),
+            ..self.clone()
+        };
+        let mut backtest = Backtest::default();
+        let mut tips = 0u128;
+
+        for observation in observations {
+            if let Some(bid) = observation.winning_bid() {
+                let tip = advisor.suggest(target);
+                backtest.auctions += 1;
+                backtest.won += u64::from(tip >= bid);
+                tips += u128::from(tip);
+            }
+            advisor.observe(observation);
+        }
+
+        if backtest.auctions > 0 {
+            backtest.win_rate = Some(backtest.won as f64 / backtest.auctions as f64);
+            backtest.average_tip = Some((tips / u128::from(backtest.auctions)) as u64);
+        }
+
+        backtest
+    }
+}
+
+/// How a [`TipAdvisor`] would have done on recorded results.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Backtest {
+    /// Results that revealed a winning bid.
+    pub auctions: u64,
+    /// Auctions where the suggested tip matched the winning bid.
+    pub won: u64,
+    pub win_rate: Option<f64>,
+    pub average_tip: Option<u64>,
+}
@@ -0,0 +1,422 @@
+//! TLS settings for engine connections.
+//!
+//! [`TlsMode`] trusts the system or bundled roots or a custom CA, can present a client
+//! certificate, and can pin server keys. Plaintext is only used when requested explicitly with
+//! [`TlsMode::InsecurePlaintext`]. The mainnet and testnet presets trust the Let's Encrypt roots,
+//! or the CA bundle named by `SOVA_CA_BUNDLE`.
+
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use base64::Engine;
+use sha2::{Digest, Sha256};
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::client::danger::{
+    HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier,
+};
+use tokio_rustls::rustls::client::WebPkiServerVerifier;
+use tokio_rustls::rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use tokio_rustls::rustls::{
+    CertificateError, ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme,
+};
+use tokio_rustls::TlsConnector;
+use tonic::transport::{Channel, Endpoint, Uri};
+
+use crate::error::SovaError;
+use crate::pem::{ISRG_ROOT_X1_PEM, ISRG_ROOT_X2_PEM};
+
+/// Environment variable read by [`TlsMode::from_env`] for the engine presets. It holds either a
+/// PEM bundle or the path to one.
+pub const CA_BUNDLE_ENV: &str = "SOVA_CA_BUNDLE";
+
+/// How a client secures its connection to the engine.
+///
+/// Every TLS mode verifies the certificate chain and the hostname taken from the endpoint URL,
+/// and requires an `https://` URL. Plaintext is only used with
+/// [`InsecurePlaintext`](TlsMode::InsecurePlaintext) and an `http://` URL.
+#[derive(Clone, PartialEq, Eq)]
+pub enum TlsMode {
+    /// Trust the operating system certificate store.
+    SystemRoots,
+    /// Trust the Mozilla root program bundled with the SDK.
+    WebPkiRoots,
+    /// Trust only the certificates in a PEM bundle.
+    CustomCa { ca_pem: String },
+    /// Trust only the certificates in a PEM file, read again on every connect.
+    CaBundleFile { path: PathBuf },
+    /// Present a client certificate. Without `ca_pem` the system roots are trusted.
+    MutualTls {
+        ca_pem: Option<String>,
+        cert_pem: String,
+        key_pem: String,
+    },
+    /// Additionally require a certificate in the chain whose SubjectPublicKeyInfo SHA-256 digest
+    /// is in `spki_sha256` (base64, optionally prefixed with `sha256/`). Without `ca_pem` the
+    /// system roots are trusted.
+    Pinned {
+        ca_pem: Option<String>,
+        spki_sha256: Vec<String>,
+    },
+    /// Unencrypted HTTP/2, for local testing against a mock engine.
+    InsecurePlaintext,
+}
+
+impl fmt::Debug for TlsMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SystemRoots => f.write_str("SystemRoots"),
+            Self::WebPkiRoots => f.write_str("WebPkiRoots"),
+            Self::CustomCa { .. } => f.write_str("CustomCa"),
+            Self::CaBundleFile { path } => {
+                f.debug_struct("CaBundleFile").field("path", path).finish()
+            }
+            Self::MutualTls { ca_pem, .. } => f
+                .debug_struct("MutualTls")
+                .field("custom_ca", &ca_pem.is_some())
+                .finish_non_exhaustive(),
+            Self::Pinned {
+                ca_pem,
+                spki_sha256,
+            } => f
+                .debug_struct("Pinned")
+                .field("custom_ca", &ca_pem.is_some())
+                .field("spki_sha256", spki_sha256)
+                .finish(),
+            Self::InsecurePlaintext => f.write_str("InsecurePlaintext"),
+        }
+    }
+}
+
+impl TlsMode {
+    pub fn custom_ca(ca_pem: &str) -> Self {
+        Self::CustomCa {
+            ca_pem: ca_pem.to_owned(),
+        }
+    }
+
+    pub fn ca_bundle_file(path: impl Into<PathBuf>) -> Self {
+        Self::CaBundleFile { path: path.into() }
+    }
+
+    /// The Let's Encrypt ISRG roots the engine certificates chain up to.
+    pub fn lets_encrypt() -> Self {
+        Self::CustomCa {
+            ca_pem: format!("{ISRG_ROOT_X1_PEM}{ISRG_ROOT_X2_PEM}"),
+        }
+    }
+
+    /// A CA bundle taken from the environment variable `var`, if it is set. See
+    /// [`from_ca_bundle`](Self::from_ca_bundle) for how the value is read.
+    pub fn from_env(var: &str) -> Option<Self> {
+        Self::from_ca_bundle(&std::env::var(var).ok()?)
+    }
+
+    /// A CA bundle given either inline or by path. A value starting with a PEM header is used as
+    /// the bundle itself, anything else as the path to a bundle file. Empty values give `None`.
+    pub fn from_ca_bundle(value: &str) -> Option<Self> {
+        let value = value.trim();
+
+        if value.is_empty() {
+            None
+        } else if value.starts_with("-----BEGIN") {
+            Some(Self::custom_ca(value))
+        } else {
+            Some(Self::ca_bundle_file(value))
+        }
+    }
+
+    pub fn is_plaintext(&self) -> bool {
+        matches!(self, Self::InsecurePlaintext)
+    }
+
+    fn client_config(&self) -> Result<ClientConfig, SovaError> {
+        let (roots, identity, pins) = match self {
+            Self::SystemRoots => (system_roots()?, None, None),
+            Self::WebPkiRoots => (webpki_roots(), None, None),
+            Self::CustomCa { ca_pem } => (roots_from_pem(ca_pem)?, None, None),
+            Self::CaBundleFile { path } => {
+                let ca_pem = std::fs::read_to_string(path).map_err(|error| {
+                    SovaError::Tls(format!("reading CA bundle {}: {error}", path.display()))
+                })?;
+                (roots_from_pem(&ca_pem)?, None, None)
+            }
+            Self::MutualTls {
+                ca_pem,
+                cert_pem,
+                key_pem,
+            } => (
+                custom_or_system_roots(ca_pem.as_deref())?,
+                Some((cert_pem, key_pem)),
+                None,
+            ),
+            Self::Pinned {
+                ca_pem,
+                spki_sha256,
+            } => (
+                custom_or_system_roots(ca_pem.as_deref())?,
+                None,
+                Some(spki_sha256),
+            ),
+            Self::InsecurePlaintext => {
+                return Err(SovaError::Tls("plaintext has no TLS config".into()))
+            }
+        };
+        let roots = Arc::new(roots);
+
+        let builder = match pins {
+            Some(pins) => ClientConfig::builder()
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(PinnedVerifier {
+                    inner: WebPkiServerVerifier::builder(roots)
+                        .build()
+                        .map_err(|error| SovaError::Tls(error.to_string()))?,
+                    pins: pins
+                        .iter()
+                        .map(|pin| pin.trim_start_matches("sha256/").to_owned())
+                        .collect(),
+                })),
+            None => ClientConfig::builder().with_root_certificates(roots),
+        };
+
+        let config = match identity {
+            Some((cert_pem, key_pem)) => {
+                let certs = rustls_pemfile::certs(&mut cert_pem.as_bytes())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|error| SovaError::Tls(error.to_string()))?;
+                let key = rustls_pemfile::private_key(&mut key_pem.as_bytes())
+                    .map_err(|error| SovaError::Tls(error.to_string()))?
+                    .ok_or_else(|| SovaError::Tls("no private key in key_pem".into()))?;
+
+                builder
+                    .with_client_auth_cert(certs, key)
+                    .map_err(|error| SovaError::Tls(error.to_string()))?
+            }
+            None => builder.with_no_client_auth(),
+        };
+
+        Ok(finish(config))
+    }
+}
+
+fn finish(mut config: ClientConfig) -> ClientConfig {
+    config.alpn_protocols = vec![b"h2".to_vec()];
+    config
+}
+
+fn roots_from_pem(ca_pem: &str) -> Result<RootCertStore, SovaError> {
+    let mut roots = RootCertStore::empty();
+
+    for cert in rustls_pemfile::certs(&mut ca_pem.as_bytes()) {
+        let cert = cert.map_err(|error| SovaError::Tls(error.to_string()))?;
+        roots
+            .add(cert)
+            .map_err(|error| SovaError::Tls(error.to_string()))?;
+    }
+
+    if roots.is_empty() {
+        return Err(SovaError::Tls("no certificates in CA bundle".into()));
+    }
+
+    Ok(roots)
+}
+
+fn custom_or_system_roots(ca_pem: Option<&str>) -> Result<RootCertStore, SovaError> {
+    match ca_pem {
+        Some(ca_pem) => roots_from_pem(ca_pem),
+        None => system_roots(),
+    }
+}
+
+fn webpki_roots() -> RootCertStore {
+    let mut roots = RootCertStore::empty();
+    roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    roots
+}
+
+fn system_roots() -> Result<RootCertStore, SovaError> {
+    let mut roots = RootCertStore::empty();
+    let certs = rustls_native_certs::load_native_certs()
+        .map_err(|error| SovaError::Tls(error.to_string()))?;
+    roots.add_parsable_certificates(certs);
+
+    if roots.is_empty() {
+        return Err(SovaError::Tls("no usable system root certificates".into()));
+    }
+
+    Ok(roots)
+}
+
+/// Opens a channel to `url` secured according to `tls`.
+#[tracing::instrument(skip_all, fields(endpoint = url, tls = ?tls), err(Display, level = "warn"))]
+pub(crate) async fn connect(
+    url: &str,
+    tls: &TlsMode,
+) -> Result<Channel, Box<dyn std::error::Error>> {
+    let uri: Uri = url.parse()?;
+
+    if tls.is_plaintext() {
+        if uri.scheme_str() != Some("http") {
+            return Err(Box::new(SovaError::Tls(format!(
+                "plaintext requires an http:// URL, got {url}"
+            ))));
+        }
+
+        let channel = Endpoint::from(uri).connect().await?;
+        tracing::debug!("connected");
+
+        return Ok(channel);
+    }
+
+    if uri.scheme_str() != Some("https") {
+        return Err(Box::new(SovaError::Tls(format!(
+            "TLS requires an https:// URL, got {url}; use TlsMode::InsecurePlaintext for plaintext"
+        ))));
+    }
+
+    let host = uri
+        .host()
+        .ok_or_else(|| SovaError::Tls(format!("missing host in {url}")))?
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_owned();
+    let port = uri.port_u16().unwrap_or(443);
+    let server_name =
+        ServerName::try_from(host.clone()).map_err(|error| SovaError::Tls(error.to_string()))?;
+    let connector = TlsConnector::from(Arc::new(tls.client_config()?));
+
+    // tonic only sees a plain connection from the connector; the origin keeps `:scheme` https.
+    let authority = uri
+        .authority()
+        .ok_or_else(|| SovaError::Tls(format!("missing host in {url}")))?
+        .clone();
+    let connect_uri = Uri::builder()
+        .scheme("http")
+        .authority(authority)
+        .path_and_query("/")
+        .build()?;
+
+    let channel = Endpoint::from(connect_uri)
+        .origin(uri)
+        .connect_with_connector(tower::service_fn(move |_: Uri| {
+            let connector = connector.clone();
+            let server_name = server_name.clone();
+            let host = host.clone();
+
+            async move {
+                let tcp = TcpStream::connect((host.as_str(), port)).await?;
+                tcp.set_nodelay(true)?;
+
+                connector.connect(server_name, tcp).await
+            }
+        }))
+        .await?;
+    tracing::debug!("connected");
+
+    Ok(channel)
+}
+
+/// Base64 SHA-256 digest of a DER certificate's SubjectPublicKeyInfo, as used for
+/// [`TlsMode::Pinned`].
+pub fn spki_sha256(cert_der: &[u8]) -> Option<String> {
+    let spki = subject_public_key_info(cert_der)?;
+
+    Some(base64::engine::general_purpose::STANDARD.encode(Sha256::digest(spki)))
+}
+
+// Certificate ::= SEQUENCE { tbsCertificate SEQUENCE { [0] version OPTIONAL, serialNumber,
+// signature, issuer, validity, subject, subjectPublicKeyInfo, ... }, ... }
+fn subject_public_key_info(cert_der: &[u8]) -> Option<&[u8]> {
+    let (_, certificate, _) = der_element(cert_der)?;
+    let (_, mut tbs, _) = der_element(certificate)?;
+
+    if tbs.first() == Some(&0xa0) {
+        tbs = der_element(tbs)?.2;
+    }
+    for _ in 0..5 {
+        tbs = der_element(tbs)?.2;
+    }
+
+    let (header_len, body, _) = der_element(tbs)?;
+    Some(&tbs[..header_len + body.len()])
+}
+
+// Splits one DER element off `input`: (header length, contents, remainder).
+fn der_element(input: &[u8]) -> Option<(usize, &[u8], &[u8])> {
+    let first_len = *input.get(1)?;
+
+    let (header_len, len) = if first_len & 0x80 == 0 {
+        (2, first_len as usize)
+    } else {
+        let octets = (first_len & 0x7f) as usize;
+        if octets == 0 || octets > 4 {
+            return None;
+        }
+        let len = input
+            .get(2..2 + octets)?
+            .iter()
+            .fold(0usize, |len, byte| (len << 8) | *byte as usize);
+        (2 + octets, len)
+    };
+
+    let body = input.get(header_len..header_len + len)?;
+    Some((header_len, body, &input[header_len + len..]))
+}
+
+#[derive(Debug)]
+struct PinnedVerifier {
+    inner: Arc<WebPkiServerVerifier>,
+    pins: Vec<String>,
+}
+
+impl ServerCertVerifier for PinnedVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, tokio_rustls::rustls::Error> {
+        let verified = self.inner.verify_server_cert(
+            end_entity,
+            intermediates,
+            server_name,
+            ocsp_response,
+            now,
+        )?;
+
+        let pinned = std::iter::once(end_entity)
+            .chain(intermediates)
+            .filter_map(|cert| spki_sha256(cert))
+            .any(|digest| self.pins.contains(&digest));
+
+        if pinned {
+            Ok(verified)
+        } else {
+            Err(CertificateError::ApplicationVerificationFailure.into())
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, tokio_rustls::rustls::Error> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, tokio_rustls::rustls::Error> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
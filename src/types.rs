@@ -0,0 +1,486 @@
+//! Domain types returned by the SDK, with conversions to and from the generated protos in
+//! [`proto`](crate::proto).
+
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+use sha2::{Digest, Sha256};
+
+use crate::auth::TokenInfo;
+use crate::boc;
+use crate::error::SovaError;
+use crate::latency::ReceivedAt;
+use crate::proto::auth::Token;
+use crate::proto::dto::{Bundle, ExternalMessage, MempoolExternalMessage, MempoolPacket};
+use crate::proto::searcher::{
+    bundle_result, bundle_result_auction_failed, bundle_result_interrupted, BundleResult,
+    BundleResultAuctionFailed, BundleResultInterrupted, BundleResultOk, GetTipAddressesResponse,
+};
+use crate::shard::{self, Address};
+
+/// A bearer token issued by the auth service. `Debug` never prints the value.
+#[derive(Clone, PartialEq, Eq)]
+pub struct AccessToken {
+    value: String,
+    expires_at: Option<SystemTime>,
+}
+
+impl AccessToken {
+    pub fn new(value: impl Into<String>, expires_at: Option<SystemTime>) -> Self {
+        Self {
+            value: value.into(),
+            expires_at,
+        }
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// `None` when the service did not report an expiry.
+    pub fn expires_at(&self) -> Option<SystemTime> {
+        self.expires_at
+    }
+
+    pub fn info(&self) -> TokenInfo {
+        TokenInfo::from(self)
+    }
+}
+
+impl fmt::Debug for AccessToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AccessToken")
+            .field("value", &"<redacted>")
+            .field("expires_at", &self.expires_at)
+            .finish()
+    }
+}
+
+impl From<Token> for AccessToken {
+    fn from(token: Token) -> Self {
+        Self {
+            value: token.value,
+            expires_at: token
+                .expires_at_utc
+                .and_then(|timestamp| SystemTime::try_from(timestamp).ok()),
+        }
+    }
+}
+
+impl From<AccessToken> for Token {
+    fn from(token: AccessToken) -> Self {
+        Self {
+            value: token.value,
+            expires_at_utc: token.expires_at.map(Into::into),
+        }
+    }
+}
+
+/// Addresses that bundles pay their tip to.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TipAccounts {
+    addresses: Vec<String>,
+}
+
+impl TipAccounts {
+    pub fn new(addresses: Vec<String>) -> Self {
+        Self { addresses }
+    }
+
+    pub fn addresses(&self) -> &[String] {
+        &self.addresses
+    }
+
+    pub fn contains(&self, address: &str) -> bool {
+        self.addresses.iter().any(|tip| tip == address)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.addresses.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.addresses.len()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, String> {
+        self.addresses.iter()
+    }
+}
+
+impl IntoIterator for TipAccounts {
+    type Item = String;
+    type IntoIter = std::vec::IntoIter<String>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.addresses.into_iter()
+    }
+}
+
+impl From<GetTipAddressesResponse> for TipAccounts {
+    fn from(response: GetTipAddressesResponse) -> Self {
+        Self::new(response.address)
+    }
+}
+
+impl From<TipAccounts> for GetTipAddressesResponse {
+    fn from(tips: TipAccounts) -> Self {
+        Self {
+            address: tips.addresses,
+        }
+    }
+}
+
+/// What happened to a submitted bundle. Failure variants carry the engine's detail string.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BundleOutcome {
+    Included,
+    HigherTipWon(String),
+    ConflictingBundle(String),
+    Expired(String),
+    SimulationFailed(String),
+}
+
+impl BundleOutcome {
+    pub fn is_included(&self) -> bool {
+        matches!(self, Self::Included)
+    }
+
+    /// Stable snake_case name of the variant, e.g. for metric labels.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Included => "included",
+            Self::HigherTipWon(_) => "higher_tip_won",
+            Self::ConflictingBundle(_) => "conflicting_bundle",
+            Self::Expired(_) => "expired",
+            Self::SimulationFailed(_) => "simulation_failed",
+        }
+    }
+
+    /// The tip of the winning bundle, when a [`HigherTipWon`](Self::HigherTipWon) detail
+    /// reports it as a plain nanoton amount.
+    ///
+    /// The detail is free-form text whose format the protocol does not define, so this is a
+    /// best-effort guess: any detail other than a bare decimal integer gives `None`.
+    pub fn winning_tip(&self) -> Option<u64> {
+        match self {
+            Self::HigherTipWon(detail)
+                if !detail.is_empty() && detail.bytes().all(|byte| byte.is_ascii_digit()) =>
+            {
+                detail.parse().ok()
+            }
+            _ => None,
+        }
+    }
+
+    pub fn detail(&self) -> Option<&str> {
+        match self {
+            Self::Included => None,
+            Self::HigherTipWon(detail)
+            | Self::ConflictingBundle(detail)
+            | Self::Expired(detail)
+            | Self::SimulationFailed(detail) => Some(detail),
+        }
+    }
+}
+
+impl fmt::Display for BundleOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.detail() {
+            Some(detail) if !detail.is_empty() => write!(f, "{} ({detail})", self.kind()),
+            _ => f.write_str(self.kind()),
+        }
+    }
+}
+
+impl TryFrom<bundle_result::Result> for BundleOutcome {
+    type Error = SovaError;
+
+    fn try_from(result: bundle_result::Result) -> Result<Self, Self::Error> {
+        let missing_reason = || SovaError::InvalidMessage("bundle result without a reason".into());
+
+        Ok(match result {
+            bundle_result::Result::Ok(_) => Self::Included,
+            bundle_result::Result::AuctionFailed(failed) => {
+                match failed.reason.ok_or_else(missing_reason)? {
+                    bundle_result_auction_failed::Reason::HigherTipWon(detail) => {
+                        Self::HigherTipWon(detail)
+                    }
+                    bundle_result_auction_failed::Reason::ConflictingBundle(detail) => {
+                        Self::ConflictingBundle(detail)
+                    }
+                }
+            }
+            bundle_result::Result::Interrupted(interrupted) => {
+                match interrupted.reason.ok_or_else(missing_reason)? {
+                    bundle_result_interrupted::Reason::Expired(detail) => Self::Expired(detail),
+                    bundle_result_interrupted::Reason::SimulationFailed(detail) => {
+                        Self::SimulationFailed(detail)
+                    }
+                }
+            }
+        })
+    }
+}
+
+impl From<BundleOutcome> for bundle_result::Result {
+    fn from(outcome: BundleOutcome) -> Self {
+        match outcome {
+            BundleOutcome::Included => Self::Ok(BundleResultOk {}),
+            BundleOutcome::HigherTipWon(detail) => Self::AuctionFailed(BundleResultAuctionFailed {
+                reason: Some(bundle_result_auction_failed::Reason::HigherTipWon(detail)),
+            }),
+            BundleOutcome::ConflictingBundle(detail) => {
+                Self::AuctionFailed(BundleResultAuctionFailed {
+                    reason: Some(bundle_result_auction_failed::Reason::ConflictingBundle(
+                        detail,
+                    )),
+                })
+            }
+            BundleOutcome::Expired(detail) => Self::Interrupted(BundleResultInterrupted {
+                reason: Some(bundle_result_interrupted::Reason::Expired(detail)),
+            }),
+            BundleOutcome::SimulationFailed(detail) => Self::Interrupted(BundleResultInterrupted {
+                reason: Some(bundle_result_interrupted::Reason::SimulationFailed(detail)),
+            }),
+        }
+    }
+}
+
+/// A bundle result from the results stream.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BundleUpdate {
+    pub bundle_id: String,
+    pub outcome: BundleOutcome,
+}
+
+impl TryFrom<BundleResult> for BundleUpdate {
+    type Error = SovaError;
+
+    fn try_from(result: BundleResult) -> Result<Self, Self::Error> {
+        let outcome = result
+            .result
+            .ok_or_else(|| SovaError::InvalidMessage("bundle result without an outcome".into()))?
+            .try_into()?;
+
+        Ok(Self {
+            bundle_id: result.id,
+            outcome,
+        })
+    }
+}
+
+impl From<BundleUpdate> for BundleResult {
+    fn from(update: BundleUpdate) -> Self {
+        Self {
+            id: update.bundle_id,
+            result: Some(update.outcome.into()),
+        }
+    }
+}
+
+/// A TON shard identifier: the shard prefix bits followed by a single tag bit, as a `u64`.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ShardId(pub u64);
+
+impl ShardId {
+    /// The unsplit shard covering the whole workchain.
+    pub const ROOT: Self = Self(0x8000_0000_0000_0000);
+
+    /// The deepest split TON allows.
+    pub const MAX_SPLIT_DEPTH: u8 = 60;
+
+    /// The shard at split `depth` that holds the account with this address hash.
+    pub fn for_account(account: &[u8; 32], depth: u8) -> Self {
+        let depth = u32::from(depth.min(Self::MAX_SPLIT_DEPTH));
+        let prefix = u64::from_be_bytes(account[..8].try_into().unwrap());
+        let tag = 1 << (63 - depth);
+
+        Self((prefix & !(tag | (tag - 1))) | tag)
+    }
+
+    /// Number of prefix bits, 0 for [`ROOT`](Self::ROOT).
+    pub fn depth(self) -> u32 {
+        63u32.saturating_sub(self.0.trailing_zeros())
+    }
+
+    /// Whether the account with this address hash is in this shard.
+    pub fn contains(self, account: &[u8; 32]) -> bool {
+        let prefix = u64::from_be_bytes(account[..8].try_into().unwrap());
+        let tag = self.0 & self.0.wrapping_neg();
+        let mask = !(tag | tag.wrapping_sub(1));
+
+        prefix & mask == self.0 & mask
+    }
+
+    /// Parses the 8-byte big-endian form used on the wire.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        Some(Self(u64::from_be_bytes(bytes.try_into().ok()?)))
+    }
+
+    pub fn to_bytes(self) -> Vec<u8> {
+        self.0.to_be_bytes().to_vec()
+    }
+}
+
+impl fmt::Debug for ShardId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ShardId({self})")
+    }
+}
+
+impl fmt::Display for ShardId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl ExternalMessage {
+    /// The TON message hash: the representation hash of the message's root cell, as shown by
+    /// explorers and carried by [`MempoolMessage::hash`].
+    pub fn hash(&self) -> Result<[u8; 32], SovaError> {
+        boc::root_hash(&self.data)
+    }
+}
+
+impl Bundle {
+    /// SHA-256 over the [hashes](ExternalMessage::hash) of the messages in order. The
+    /// expiration is not covered, so resending the same messages keeps the same hash.
+    pub fn hash(&self) -> Result<[u8; 32], SovaError> {
+        let mut hasher = Sha256::new();
+        for message in &self.message {
+            hasher.update(message.hash()?);
+        }
+        Ok(hasher.finalize().into())
+    }
+}
+
+/// An external message seen in the mempool.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MempoolMessage {
+    pub hash: [u8; 32],
+    pub workchain_id: i32,
+    pub shard: ShardId,
+    /// The message as a serialized bag of cells.
+    pub data: Vec<u8>,
+    /// Set by the engine when the destination is a standard (`addr_std`) address.
+    pub std_smc_address: bool,
+}
+
+impl MempoolMessage {
+    /// The account the message is addressed to, read from [`data`](Self::data).
+    pub fn destination(&self) -> Result<Address, SovaError> {
+        shard::message_destination(&self.data)
+    }
+}
+
+/// A packet of mempool messages pushed by the engine.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MempoolEvent {
+    pub server_time: Option<SystemTime>,
+    pub expires_at: Option<SystemTime>,
+    pub messages: Vec<MempoolMessage>,
+    /// Set by the subscription that delivered the packet.
+    pub received: Option<ReceivedAt>,
+}
+
+impl MempoolEvent {
+    /// Time from the engine's `server_ts` to local receipt. `None` without both timestamps or
+    /// when the clocks are skewed so that receipt appears earlier.
+    pub fn latency(&self) -> Option<Duration> {
+        self.received?.wall.duration_since(self.server_time?).ok()
+    }
+
+    /// How long ago the packet was received.
+    pub fn since_received(&self) -> Option<Duration> {
+        Some(self.received?.instant.elapsed())
+    }
+}
+
+impl TryFrom<MempoolExternalMessage> for MempoolMessage {
+    type Error = SovaError;
+
+    fn try_from(message: MempoolExternalMessage) -> Result<Self, Self::Error> {
+        Ok(Self {
+            hash: message.hash.as_slice().try_into().map_err(|_| {
+                SovaError::InvalidMessage(format!(
+                    "message hash has {} bytes, expected 32",
+                    message.hash.len()
+                ))
+            })?,
+            workchain_id: message.workchain_id,
+            shard: ShardId::from_bytes(&message.shard).ok_or_else(|| {
+                SovaError::InvalidMessage(format!(
+                    "shard has {} bytes, expected 8",
+                    message.shard.len()
+                ))
+            })?,
+            data: message.data,
+            std_smc_address: message.std_smc_address,
+        })
+    }
+}
+
+impl From<MempoolMessage> for MempoolExternalMessage {
+    fn from(message: MempoolMessage) -> Self {
+        Self {
+            hash: message.hash.to_vec(),
+            workchain_id: message.workchain_id,
+            shard: message.shard.to_bytes(),
+            data: message.data,
+            std_smc_address: message.std_smc_address,
+        }
+    }
+}
+
+impl MempoolEvent {
+    /// Decodes `packet`, skipping the messages that do not decode and passing their error to
+    /// `on_invalid`.
+    pub fn from_packet_lossy(packet: MempoolPacket, mut on_invalid: impl FnMut(SovaError)) -> Self {
+        Self {
+            server_time: packet
+                .server_ts
+                .and_then(|timestamp| SystemTime::try_from(timestamp).ok()),
+            expires_at: packet
+                .expiration_ns
+                .and_then(|timestamp| SystemTime::try_from(timestamp).ok()),
+            messages: packet
+                .external_messages
+                .into_iter()
+                .filter_map(|message| {
+                    MempoolMessage::try_from(message)
+                        .map_err(&mut on_invalid)
+                        .ok()
+                })
+                .collect(),
+            received: None,
+        }
+    }
+}
+
+/// Fails if any message does not decode. See [`MempoolEvent::from_packet_lossy`] to skip them.
+impl TryFrom<MempoolPacket> for MempoolEvent {
+    type Error = SovaError;
+
+    fn try_from(packet: MempoolPacket) -> Result<Self, Self::Error> {
+        let mut error = None;
+        let event = Self::from_packet_lossy(packet, |invalid| {
+            error.get_or_insert(invalid);
+        });
+
+        match error {
+            Some(error) => Err(error),
+            None => Ok(event),
+        }
+    }
+}
+
+impl From<MempoolEvent> for MempoolPacket {
+    fn from(event: MempoolEvent) -> Self {
+        Self {
+            server_ts: event.server_time.map(Into::into),
+            expiration_ns: event.expires_at.map(Into::into),
+            external_messages: event.messages.into_iter().map(Into::into).collect(),
+        }
+    }
+}
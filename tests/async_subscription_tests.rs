@@ -0,0 +1,89 @@
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::{items_then_pending, MockSearcher};
+use sova_sdk_rs::delivery::Concurrency;
+use sova_sdk_rs::proto::dto::{Bundle, MempoolPacket};
+use sova_sdk_rs::proto::searcher::{
+    mempool_subscription, SendBundleResponse, WorkchainSubscriptionV0,
+};
+use sova_sdk_rs::searcher::SovaSearcher;
+use sova_sdk_rs::tls::TlsMode;
+use tokio::sync::mpsc;
+
+const PACKETS: usize = 6;
+
+// Sends a bundle from an async callback for every packet and returns the most callbacks that
+// were running at once.
+async fn max_in_flight(
+    searcher: &SovaSearcher,
+    concurrency: Concurrency,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let max_in_flight = Arc::new(AtomicUsize::new(0));
+    let (tx, mut rx) = mpsc::unbounded_channel();
+
+    let client = searcher.clone();
+    let (running, max) = (in_flight.clone(), max_in_flight.clone());
+    searcher
+        .subscribe_async(
+            mempool_subscription::Subscription::Workchain(WorkchainSubscriptionV0 {
+                workchain_id: 0,
+            }),
+            concurrency,
+            move |_event| {
+                let client = client.clone();
+                let (running, max, tx) = (running.clone(), max.clone(), tx.clone());
+
+                async move {
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    max.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+
+                    let response = client.send_bundle(Bundle::default()).await.unwrap();
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    tx.send(response.id).unwrap();
+                }
+            },
+        )
+        .await?;
+
+    for _ in 0..PACKETS {
+        assert_eq!(rx.recv().await.unwrap(), "bundle");
+    }
+
+    Ok(max_in_flight.load(Ordering::SeqCst))
+}
+
+#[tokio::test]
+async fn test_async_subscription_concurrency() -> Result<(), Box<dyn std::error::Error>> {
+    // Sends six empty mempool packets back to back and accepts every bundle.
+    let server = MockSearcher::new()
+        .on_mempool(|_| async {
+            Ok(items_then_pending(vec![
+                Ok(MempoolPacket::default());
+                PACKETS
+            ]))
+        })
+        .on_send_bundle(|_| async {
+            Ok(SendBundleResponse {
+                id: "bundle".to_owned(),
+            })
+        })
+        .serve()
+        .await;
+
+    let searcher = SovaSearcher::new(&server.url, TlsMode::InsecurePlaintext).await?;
+
+    assert_eq!(max_in_flight(&searcher, Concurrency::Sequential).await?, 1);
+    assert_eq!(max_in_flight(&searcher, Concurrency::Bounded(2)).await?, 2);
+    assert_eq!(
+        max_in_flight(&searcher, Concurrency::Unbounded).await?,
+        PACKETS
+    );
+
+    Ok(())
+}
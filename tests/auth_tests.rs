@@ -1,11 +1,16 @@
+use std::time::{Duration, SystemTime};
+
+use ed25519_dalek::SigningKey;
 use tonic::Response;
 
-use sova_sdk_rs::auth::SovaAuth;
+use sova_sdk_rs::auth::{SovaAuth, TokenInfo};
 use sova_sdk_rs::proto::auth::auth_service_server::{AuthService, AuthServiceServer};
 use sova_sdk_rs::proto::auth::{
     GenerateAuthChallengeRequest, GenerateAuthChallengeResponse, GenerateAuthTokensRequest,
     GenerateAuthTokensResponse, RefreshAccessTokenRequest, RefreshAccessTokenResponse, Token,
 };
+use sova_sdk_rs::tls::TlsMode;
+use sova_sdk_rs::types::AccessToken;
 use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
 use tonic::transport::Server;
@@ -82,17 +87,58 @@ async fn test_authenticate() -> Result<(), Box<dyn std::error::Error>> {
     // A dummy key, replace with real one for actual tests
 
     // Create SovaAuth instance
-    let mut auth = SovaAuth::new("http://[::1]:50051", None, None, &private_key_bytes).await?;
+    let auth = SovaAuth::new(
+        "http://[::1]:50051",
+        TlsMode::InsecurePlaintext,
+        &private_key_bytes,
+    )
+    .await?;
 
     // Test authenticate function
     auth.authenticate().await?;
 
     // Assert that the tokens were set correctly
-    assert_eq!(auth.access_token().unwrap().value, "access_token");
-    assert_eq!(auth.refresh_token().unwrap().value, "refresh_token");
+    assert_eq!(auth.access_token().unwrap().value(), "access_token");
+    assert_eq!(auth.refresh_token().unwrap().value(), "refresh_token");
+
+    let info = auth.info();
+    assert_eq!(
+        info.public_key.unwrap().to_bytes(),
+        SigningKey::from_bytes(&private_key_bytes)
+            .verifying_key()
+            .to_bytes()
+    );
+    assert!(!info.needs_refresh(Duration::from_secs(60)));
+    assert!(info.can_refresh());
 
     // Stop the server
     server_handle.abort();
 
     Ok(())
 }
+
+#[test]
+fn test_token_info() {
+    let token = Token {
+        value: "secret_token_value".to_string(),
+        expires_at_utc: Some((SystemTime::now() + Duration::from_secs(30)).into()),
+    };
+
+    let info = AccessToken::from(token.clone()).info();
+    let remaining = info.time_remaining().unwrap();
+    assert!(remaining <= Duration::from_secs(30));
+    assert!(remaining > Duration::from_secs(20));
+    assert!(info.expires_within(Duration::from_secs(60)));
+    assert!(!info.expires_within(Duration::from_secs(10)));
+    assert!(!info.is_expired());
+
+    let expired = TokenInfo::from(&AccessToken::new(
+        "secret_token_value",
+        Some(SystemTime::now() - Duration::from_secs(1)),
+    ));
+    assert!(expired.is_expired());
+    assert_eq!(expired.time_remaining(), Some(Duration::ZERO));
+
+    assert!(!format!("{:?}", token).contains("secret_token_value"));
+    assert!(!format!("{:?}", AccessToken::from(token)).contains("secret_token_value"));
+}
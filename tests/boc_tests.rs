@@ -0,0 +1,117 @@
+use sha2::{Digest, Sha256};
+use sova_sdk_rs::boc::root_hash;
+use sova_sdk_rs::error::SovaError;
+use sova_sdk_rs::proto::dto::{Bundle, ExternalMessage};
+use sova_sdk_rs::shard::message_destination;
+
+const EMPTY_CELL_HASH: &str = "96a296d224f285c67bee93c30f8a309157f0daa35dc5b87e410b78630a09cfc7";
+
+// A root holding the 3 bits `101` with a reference to an empty cell.
+const TREE_BOC: &str = "b5ee9c72010102010006000101b0010000";
+const TREE_HASH: &str = "fac61656549b4993fdc8aeb47c115c422d07cde9d92793ed80f4b9bcea6cbacb";
+
+// A wallet v4 transfer laid out as wallets send it: an external message to the wallet whose body
+// holds the signature, subwallet id, valid_until, seqno and mode, and references the internal
+// message to send. The hash comes from a separate implementation of the cell hashing rules.
+const WALLET_MESSAGE_BOC: &str = concat!(
+    "b5ee9c720101030100ad000145880107bfaaa5cc6e5368e5f9799188bd798cd22e04ab16d1d8ea4fc37480741e",
+    "63500c01019c000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20212223242526",
+    "2728292a2b2c2d2e2f303132333435363738393a3b3c3d3e3f29a9a3176553f13c0000002a000302006862002f",
+    "979e15d051dc736250f17a7874d27e94747925e6abd4707266a6507950dce1a1dcd65000000000000000000000",
+    "00000000",
+);
+const WALLET_MESSAGE_HASH: &str =
+    "92f23531c06f36092df3cbc59cc921692d9dc9b3f73fe13bc76f6ba34fc67dd6";
+const WALLET_ADDRESS: &str = "83dfd552e63729b472fcbcc8c45ebcc6691702558b68ec7527e1ba403a0f31a8";
+
+fn hash(boc: &str) -> Result<String, SovaError> {
+    root_hash(&hex::decode(boc).unwrap()).map(hex::encode)
+}
+
+fn message(boc: &str) -> ExternalMessage {
+    ExternalMessage {
+        data: hex::decode(boc).unwrap(),
+    }
+}
+
+#[test]
+fn test_root_hash() {
+    // The same empty cell serialized with a checksum, without one and with an index.
+    assert_eq!(
+        hash("b5ee9c724101010100020000004cacb9cd").unwrap(),
+        EMPTY_CELL_HASH
+    );
+    assert_eq!(hash("b5ee9c72010101010002000000").unwrap(), EMPTY_CELL_HASH);
+    assert_eq!(
+        hash("b5ee9c7281010101000200020000").unwrap(),
+        EMPTY_CELL_HASH
+    );
+
+    assert_eq!(hash(TREE_BOC).unwrap(), TREE_HASH);
+}
+
+#[test]
+fn test_wallet_message_hash() -> Result<(), SovaError> {
+    let wallet_message = message(WALLET_MESSAGE_BOC);
+    assert_eq!(hex::encode(wallet_message.hash()?), WALLET_MESSAGE_HASH);
+
+    let destination = message_destination(&wallet_message.data)?;
+    assert_eq!(destination.workchain, 0);
+    assert_eq!(hex::encode(destination.account), WALLET_ADDRESS);
+
+    Ok(())
+}
+
+#[test]
+fn test_invalid_boc() {
+    for boc in [
+        "",
+        "deadbeef",
+        // Wrong checksum.
+        "b5ee9c724101010100020000004cacb9ce",
+        // Truncated cell data.
+        "b5ee9c720101010100020000",
+        // Trailing bytes.
+        "b5ee9c7201010101000200000000",
+        // A reference back to the root.
+        "b5ee9c7201010101000300010000",
+    ] {
+        assert!(
+            matches!(hash(boc), Err(SovaError::InvalidBoc(_))),
+            "{boc} was accepted"
+        );
+    }
+}
+
+#[test]
+fn test_message_and_bundle_hashes() -> Result<(), SovaError> {
+    let empty = message("b5ee9c72010101010002000000");
+    let tree = message(TREE_BOC);
+    assert_eq!(hex::encode(empty.hash()?), EMPTY_CELL_HASH);
+    assert_eq!(hex::encode(tree.hash()?), TREE_HASH);
+
+    let bundle = Bundle {
+        message: vec![empty.clone(), tree.clone()],
+        ..Default::default()
+    };
+    let expected: [u8; 32] = Sha256::new()
+        .chain_update(empty.hash()?)
+        .chain_update(tree.hash()?)
+        .finalize()
+        .into();
+    assert_eq!(bundle.hash()?, expected);
+
+    // Message order matters, the expiration does not.
+    let reversed = Bundle {
+        message: vec![tree, empty],
+        ..Default::default()
+    };
+    assert_ne!(reversed.hash()?, expected);
+    let expiring = Bundle {
+        expiration_ns: Some(Default::default()),
+        ..bundle
+    };
+    assert_eq!(expiring.hash()?, expected);
+
+    Ok(())
+}
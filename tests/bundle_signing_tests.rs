@@ -0,0 +1,181 @@
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use common::MockSearcher;
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use prost_types::Timestamp;
+use sova_sdk_rs::bundle_signing::{sign_bundle, BundleSignature, BundleVerifier, SIGNATURE_HEADER};
+use sova_sdk_rs::error::SovaError;
+use sova_sdk_rs::proto::dto::{Bundle, ExternalMessage};
+use sova_sdk_rs::proto::searcher::SendBundleResponse;
+use sova_sdk_rs::searcher::SovaSearcher;
+use sova_sdk_rs::signer::{ChallengeSigner, SignerError};
+use sova_sdk_rs::tls::TlsMode;
+use tonic::metadata::MetadataMap;
+use tonic::Status;
+
+// Signs with `key` and counts the requests for its public key.
+struct CountingSigner {
+    key: SigningKey,
+    public_key_calls: AtomicUsize,
+}
+
+#[tonic::async_trait]
+impl ChallengeSigner for CountingSigner {
+    async fn public_key(&self) -> Result<VerifyingKey, SignerError> {
+        self.public_key_calls.fetch_add(1, Ordering::SeqCst);
+        self.key.public_key().await
+    }
+
+    async fn sign_challenge(&self, challenge: &[u8]) -> Result<Signature, SignerError> {
+        self.key.sign_challenge(challenge).await
+    }
+}
+
+// A single cell holding `data` as whole bytes.
+fn cell_boc(data: &[u8]) -> Vec<u8> {
+    let mut boc = vec![0xb5, 0xee, 0x9c, 0x72, 0x01, 0x01, 1, 1, 0];
+    boc.extend([2 + data.len() as u8, 0, 0, 2 * data.len() as u8]);
+    boc.extend(data);
+    boc
+}
+
+fn bundle(data: &[&[u8]]) -> Bundle {
+    Bundle {
+        message: data
+            .iter()
+            .map(|data| ExternalMessage {
+                data: cell_boc(data),
+            })
+            .collect(),
+        expiration_ns: Some(Timestamp {
+            seconds: 1_700_000_000,
+            nanos: 0,
+        }),
+    }
+}
+
+#[tokio::test]
+async fn test_sign_and_verify() -> Result<(), Box<dyn std::error::Error>> {
+    let key = SigningKey::from_bytes(&[7u8; 32]);
+    let other = SigningKey::from_bytes(&[8u8; 32]);
+    let signed = bundle(&[b"message"]);
+
+    let signature = sign_bundle(&key, &signed).await?;
+    assert_eq!(signature.signer, key.verifying_key());
+    BundleVerifier::new().verify(&signed, &signature)?;
+    BundleVerifier::new()
+        .trust(key.verifying_key())
+        .verify(&signed, &signature)?;
+
+    // The signature travels through metadata unchanged.
+    let mut metadata = MetadataMap::new();
+    assert_eq!(BundleSignature::from_metadata(&metadata)?, None);
+    signature.to_metadata(&mut metadata);
+    assert_eq!(BundleSignature::from_metadata(&metadata)?, Some(signature));
+
+    let tampered = bundle(&[b"massage"]);
+    assert!(matches!(
+        BundleVerifier::new().verify(&tampered, &signature),
+        Err(SovaError::InvalidBundleSignature(_))
+    ));
+    // The expiration is not part of the bundle hash but is signed.
+    let extended = Bundle {
+        expiration_ns: None,
+        ..signed.clone()
+    };
+    assert_eq!(extended.hash()?, signature.hash);
+    assert!(matches!(
+        BundleVerifier::new().verify(&extended, &signature),
+        Err(SovaError::InvalidSignature)
+    ));
+    assert!(matches!(
+        BundleVerifier::new()
+            .trust(other.verifying_key())
+            .verify(&signed, &signature),
+        Err(SovaError::InvalidBundleSignature(_))
+    ));
+
+    // A raw signature over the hash, as an auth challenge answer would be, is not accepted.
+    let raw = BundleSignature {
+        signature: key.sign(&signature.hash),
+        ..signature
+    };
+    assert!(matches!(
+        BundleVerifier::new().verify(&signed, &raw),
+        Err(SovaError::InvalidSignature)
+    ));
+
+    metadata.insert(SIGNATURE_HEADER, "zz".parse().unwrap());
+    assert!(BundleSignature::from_metadata(&metadata).is_err());
+
+    let garbage = Bundle {
+        message: vec![ExternalMessage {
+            data: b"message".to_vec(),
+        }],
+        expiration_ns: None,
+    };
+    assert!(matches!(
+        sign_bundle(&key, &garbage).await,
+        Err(SovaError::InvalidBoc(_))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_signed_send_bundle() -> Result<(), Box<dyn std::error::Error>> {
+    let key = SigningKey::from_bytes(&[7u8; 32]);
+    let verifier = BundleVerifier::new().trust(key.verifying_key());
+    let signatures = Arc::new(Mutex::new(Vec::new()));
+    let recorded = signatures.clone();
+    // Accepts only bundles signed by a trusted searcher and records who signed them.
+    let server = MockSearcher::new()
+        .on_send_bundle(move |request| {
+            let signature = verifier
+                .verify_request(&request)
+                .map_err(|error| Status::unauthenticated(error.to_string()));
+            if let Ok(signature) = &signature {
+                recorded.lock().unwrap().push(*signature);
+            }
+            async move {
+                Ok(SendBundleResponse {
+                    id: hex::encode(signature?.hash),
+                })
+            }
+        })
+        .serve()
+        .await;
+
+    let unsigned = SovaSearcher::new(&server.url, TlsMode::InsecurePlaintext).await?;
+    let error = unsigned
+        .send_bundle(bundle(&[b"message"]))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<Status>().map(Status::code),
+        Some(tonic::Code::Unauthenticated)
+    );
+
+    let signer = Arc::new(CountingSigner {
+        key: key.clone(),
+        public_key_calls: AtomicUsize::new(0),
+    });
+    let searcher = unsigned.with_bundle_signer(signer.clone()).await?;
+    let sent = bundle(&[b"message"]);
+    let response = searcher.send_bundle(sent.clone()).await?;
+    assert_eq!(response.id, hex::encode(sent.hash()?));
+    searcher.send_bundle(bundle(&[b"another"])).await?;
+    // The public key is fetched once, not for every bundle.
+    assert_eq!(signer.public_key_calls.load(Ordering::SeqCst), 1);
+
+    let signed = signatures.lock().unwrap().clone();
+    assert_eq!(signed.len(), 2);
+    assert_eq!(signed[0].signer, key.verifying_key());
+    // Auditors can check the recorded signature against the stored bundle later.
+    BundleVerifier::new().verify(&sent, &signed[0])?;
+
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use std::time::Duration;
+
+use sova_sdk_rs::bundle_stats::{BundleStats, UNTAGGED};
+use sova_sdk_rs::proto::searcher::BundleResult;
+use sova_sdk_rs::types::{BundleOutcome, BundleUpdate};
+
+fn update(bundle_id: &str, outcome: BundleOutcome) -> BundleUpdate {
+    BundleUpdate {
+        bundle_id: bundle_id.to_owned(),
+        outcome,
+    }
+}
+
+#[test]
+fn test_bundle_stats_snapshot() {
+    let stats = BundleStats::default();
+
+    stats.track("a", "backrun", Some(100));
+    stats.track("b", "backrun", Some(150));
+    stats.track("c", "arbitrage", Some(300));
+    stats.record(&update("a", BundleOutcome::HigherTipWon("200".to_owned())));
+    stats.record(&update("b", BundleOutcome::Included));
+    stats.record(&update(
+        "c",
+        BundleOutcome::ConflictingBundle(String::new()),
+    ));
+    stats.record(&update("d", BundleOutcome::Expired("late".to_owned())));
+    stats.record_result(BundleResult {
+        id: "e".to_owned(),
+        result: None,
+    });
+
+    let snapshot = stats.snapshot(Duration::from_secs(60));
+    assert_eq!(snapshot.outcomes.results, 4);
+    assert_eq!(snapshot.outcomes.included, 1);
+    assert_eq!(snapshot.outcomes.win_rate, Some(0.25));
+    assert_eq!(snapshot.invalid, 1);
+    assert_eq!(
+        snapshot.outcomes.failures().collect::<Vec<_>>(),
+        [
+            ("conflicting_bundle", 1),
+            ("expired", 1),
+            ("higher_tip_won", 1)
+        ]
+    );
+
+    assert_eq!(snapshot.tips.included, 1);
+    assert_eq!(snapshot.tips.average_included_tip, Some(150));
+    assert_eq!(snapshot.tips.lost_to_higher_tip, 1);
+    assert_eq!(snapshot.tips.average_lost_tip, Some(100));
+    assert_eq!(snapshot.tips.average_winning_tip, Some(200));
+
+    assert_eq!(snapshot.tags["backrun"].results, 2);
+    assert_eq!(snapshot.tags["backrun"].win_rate, Some(0.5));
+    assert_eq!(snapshot.tags["arbitrage"].win_rate, Some(0.0));
+    assert_eq!(snapshot.tags[UNTAGGED].by_outcome["expired"], 1);
+
+    let json = serde_json::to_value(&snapshot).unwrap();
+    assert_eq!(json["results"], 4);
+    assert_eq!(json["win_rate"], 0.25);
+    assert_eq!(json["tags"]["backrun"]["by_outcome"]["included"], 1);
+    assert_eq!(json["tips"]["average_winning_tip"], 200);
+}
+
+#[test]
+fn test_bundle_stats_windows() {
+    let stats = BundleStats::new(Duration::from_millis(200));
+    stats.record(&update("old", BundleOutcome::Included));
+    std::thread::sleep(Duration::from_millis(100));
+    stats.record(&update("new", BundleOutcome::Included));
+
+    assert_eq!(
+        stats.snapshot(Duration::from_millis(50)).outcomes.results,
+        1
+    );
+    assert_eq!(stats.snapshot(Duration::from_secs(60)).outcomes.results, 2);
+    assert_eq!(
+        stats.snapshot(Duration::from_secs(60)).window,
+        Duration::from_millis(200)
+    );
+
+    // Results older than the retention are dropped.
+    std::thread::sleep(Duration::from_millis(150));
+    stats.record(&update("latest", BundleOutcome::Included));
+    let snapshot = stats.snapshot(Duration::from_secs(60));
+    assert_eq!(snapshot.outcomes.results, 2);
+    assert_eq!(snapshot.outcomes.win_rate, Some(1.0));
+
+    assert_eq!(
+        BundleStats::default()
+            .snapshot(Duration::from_secs(1))
+            .outcomes
+            .win_rate,
+        None
+    );
+}
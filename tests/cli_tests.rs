@@ -0,0 +1,159 @@
+#![cfg(feature = "cli")]
+
+mod common;
+
+use std::process::Output;
+use std::sync::{Arc, Mutex};
+
+use common::{items_then_pending, MockSearcher};
+use sova_sdk_rs::proto::dto::MempoolPacket;
+use sova_sdk_rs::proto::searcher::{
+    mempool_subscription, InternalMessageBodyOpcodeSubscriptionV0, SendBundleResponse,
+};
+use tonic::Status;
+
+// Runs the `sova` binary against `url` without a key, so no authentication is attempted.
+async fn sova(url: &str, args: &[&str]) -> Output {
+    let mut command = std::process::Command::new(env!("CARGO_BIN_EXE_sova"));
+    command
+        .args(["--url", url, "--tls", "plaintext"])
+        .args(args)
+        .env_remove("SOVA_KEY_FILE")
+        .env_remove("SOVA_PRIVATE_KEY")
+        .env_remove("SOVA_SIGNER_SOCKET");
+
+    tokio::task::spawn_blocking(move || command.output().unwrap())
+        .await
+        .unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_mempool_tail_filters() {
+    let requested = Arc::new(Mutex::new(Vec::new()));
+    let recorded = requested.clone();
+    let server = MockSearcher::new()
+        .on_mempool(move |request| {
+            recorded
+                .lock()
+                .unwrap()
+                .push(request.into_inner().subscription);
+            async { Ok(items_then_pending([Ok(MempoolPacket::default())])) }
+        })
+        .serve()
+        .await;
+
+    let output = sova(
+        &server.url,
+        &[
+            "mempool",
+            "tail",
+            "--workchain=-1",
+            "--shard",
+            "8000000000000000",
+            "--opcode",
+            "0x2a",
+            "--internal",
+            "--count",
+            "1",
+        ],
+    )
+    .await;
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(
+        requested.lock().unwrap()[0],
+        Some(
+            mempool_subscription::Subscription::InternalMessageBodyOpcode(
+                InternalMessageBodyOpcodeSubscriptionV0 {
+                    workchain_id: -1,
+                    shard: Some(vec![0x80, 0, 0, 0, 0, 0, 0, 0]),
+                    opcode: 42,
+                }
+            )
+        )
+    );
+
+    // Decimal opcodes are accepted too, anything else is rejected before connecting.
+    let output = sova(
+        &server.url,
+        &["mempool", "tail", "--opcode", "42", "--count", "1"],
+    )
+    .await;
+    assert!(output.status.success(), "{output:?}");
+    let output = sova(&server.url, &["mempool", "tail", "--opcode", "0xzz"]).await;
+    assert!(!output.status.success());
+    assert_eq!(requested.lock().unwrap().len(), 2);
+
+    // Without a filter or profile subscriptions there is nothing to tail.
+    let output = sova(&server.url, &["mempool", "tail"]).await;
+    assert!(!output.status.success());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_bundle_send_reads_file() {
+    let sent = Arc::new(Mutex::new(Vec::new()));
+    let recorded = sent.clone();
+    let server = MockSearcher::new()
+        .on_send_bundle(move |request| {
+            recorded.lock().unwrap().push(request.into_inner());
+            async {
+                Ok(SendBundleResponse {
+                    id: "bundle-1".to_owned(),
+                })
+            }
+        })
+        .serve()
+        .await;
+
+    let dir = std::env::temp_dir().join(format!("sova-cli-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let bundle = dir.join("bundle.json");
+    std::fs::write(
+        &bundle,
+        r#"{ "messages": ["te6ccg==", "AQID"], "expires_in_ms": 5000 }"#,
+    )
+    .unwrap();
+
+    let output = sova(
+        &server.url,
+        &["--json", "bundle", "send", bundle.to_str().unwrap()],
+    )
+    .await;
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap().trim(),
+        r#"{"id":"bundle-1"}"#
+    );
+
+    let sent = sent.lock().unwrap().clone();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].message[0].data, vec![0xb5, 0xee, 0x9c, 0x72]);
+    assert_eq!(sent[0].message[1].data, vec![1, 2, 3]);
+    assert!(sent[0].expiration_ns.is_some());
+
+    // Invalid base64 and unknown fields are rejected before anything is sent.
+    std::fs::write(&bundle, r#"{ "messages": ["not base64!"] }"#).unwrap();
+    let output = sova(&server.url, &["bundle", "send", bundle.to_str().unwrap()]).await;
+    assert!(!output.status.success());
+    std::fs::write(&bundle, r#"{ "messages": [], "expires_in": 5 }"#).unwrap();
+    let output = sova(&server.url, &["bundle", "send", bundle.to_str().unwrap()]).await;
+    assert!(!output.status.success());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_bundles_watch_fails_with_its_stream() {
+    let server = MockSearcher::new()
+        .on_bundle_results(|_| async {
+            Ok(items_then_pending([Err(Status::internal(
+                "engine restarting",
+            ))]))
+        })
+        .serve()
+        .await;
+
+    let output = sova(&server.url, &["bundles", "watch"]).await;
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("engine restarting"), "{stderr}");
+}
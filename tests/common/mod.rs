@@ -0,0 +1,185 @@
+//! A configurable mock searcher service and a server helper shared by the integration tests.
+
+#![allow(dead_code)]
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use futures_util::future::BoxFuture;
+use futures_util::{stream, FutureExt, Stream, StreamExt};
+use sova_sdk_rs::proto::dto::{Bundle, MempoolPacket};
+use sova_sdk_rs::proto::searcher::searcher_service_server::{
+    SearcherService, SearcherServiceServer,
+};
+use sova_sdk_rs::proto::searcher::{
+    BundleResult, GetTipAddressesRequest, GetTipAddressesResponse, MempoolSubscription,
+    SendBundleResponse, SubscribeBundleResultsRequest,
+};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+use tonic::transport::server::Router;
+use tonic::transport::Server;
+use tonic::{Request, Response, Status};
+
+pub type ResponseStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
+
+type Handler<Req, Res> =
+    Arc<dyn Fn(Request<Req>) -> BoxFuture<'static, Result<Res, Status>> + Send + Sync>;
+
+fn handler<Req, Res, F, Fut>(handle: F) -> Handler<Req, Res>
+where
+    F: Fn(Request<Req>) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<Res, Status>> + Send + 'static,
+{
+    Arc::new(move |request| handle(request).boxed())
+}
+
+/// Sends `items`, then keeps the stream open.
+pub fn items_then_pending<T: Send + 'static>(
+    items: impl IntoIterator<Item = Result<T, Status>>,
+) -> ResponseStream<T> {
+    let items: Vec<_> = items.into_iter().collect();
+    Box::pin(stream::iter(items).chain(stream::pending()))
+}
+
+/// A searcher service whose streams stay open without sending anything and whose calls return
+/// default responses, unless a test overrides them with the `on_*` methods.
+#[derive(Clone)]
+pub struct MockSearcher {
+    bundle_results: Handler<SubscribeBundleResultsRequest, ResponseStream<BundleResult>>,
+    mempool: Handler<MempoolSubscription, ResponseStream<MempoolPacket>>,
+    send_bundle: Handler<Bundle, SendBundleResponse>,
+    tip_addresses: Handler<GetTipAddressesRequest, GetTipAddressesResponse>,
+}
+
+impl Default for MockSearcher {
+    fn default() -> Self {
+        Self {
+            bundle_results: handler(|_| async { Ok(items_then_pending([])) }),
+            mempool: handler(|_| async { Ok(items_then_pending([])) }),
+            send_bundle: handler(|_| async { Ok(SendBundleResponse::default()) }),
+            tip_addresses: handler(|_| async { Ok(GetTipAddressesResponse::default()) }),
+        }
+    }
+}
+
+impl MockSearcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn on_bundle_results<F, Fut>(mut self, handle: F) -> Self
+    where
+        F: Fn(Request<SubscribeBundleResultsRequest>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<ResponseStream<BundleResult>, Status>> + Send + 'static,
+    {
+        self.bundle_results = handler(handle);
+        self
+    }
+
+    pub fn on_mempool<F, Fut>(mut self, handle: F) -> Self
+    where
+        F: Fn(Request<MempoolSubscription>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<ResponseStream<MempoolPacket>, Status>> + Send + 'static,
+    {
+        self.mempool = handler(handle);
+        self
+    }
+
+    pub fn on_send_bundle<F, Fut>(mut self, handle: F) -> Self
+    where
+        F: Fn(Request<Bundle>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<SendBundleResponse, Status>> + Send + 'static,
+    {
+        self.send_bundle = handler(handle);
+        self
+    }
+
+    pub fn on_tip_addresses<F, Fut>(mut self, handle: F) -> Self
+    where
+        F: Fn(Request<GetTipAddressesRequest>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<GetTipAddressesResponse, Status>> + Send + 'static,
+    {
+        self.tip_addresses = handler(handle);
+        self
+    }
+
+    pub async fn serve(self) -> MockServer {
+        serve(self.router()).await
+    }
+
+    pub fn serve_on(self, listener: TcpListener) -> MockServer {
+        serve_on(listener, self.router())
+    }
+
+    fn router(self) -> Router {
+        Server::builder().add_service(SearcherServiceServer::new(self))
+    }
+}
+
+#[tonic::async_trait]
+impl SearcherService for MockSearcher {
+    type SubscribeBundleResultsStream = ResponseStream<BundleResult>;
+    type SubscribeMempoolStream = ResponseStream<MempoolPacket>;
+
+    async fn subscribe_bundle_results(
+        &self,
+        request: Request<SubscribeBundleResultsRequest>,
+    ) -> Result<Response<Self::SubscribeBundleResultsStream>, Status> {
+        (self.bundle_results)(request).await.map(Response::new)
+    }
+
+    async fn subscribe_mempool(
+        &self,
+        request: Request<MempoolSubscription>,
+    ) -> Result<Response<Self::SubscribeMempoolStream>, Status> {
+        (self.mempool)(request).await.map(Response::new)
+    }
+
+    async fn send_bundle(
+        &self,
+        request: Request<Bundle>,
+    ) -> Result<Response<SendBundleResponse>, Status> {
+        (self.send_bundle)(request).await.map(Response::new)
+    }
+
+    async fn get_tip_addresses(
+        &self,
+        request: Request<GetTipAddressesRequest>,
+    ) -> Result<Response<GetTipAddressesResponse>, Status> {
+        (self.tip_addresses)(request).await.map(Response::new)
+    }
+}
+
+/// A running mock server, stopped when dropped.
+pub struct MockServer {
+    pub url: String,
+    handle: JoinHandle<()>,
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// Serves `router` on a free local port. The port is bound before this returns, so clients can
+/// connect right away.
+pub async fn serve(router: Router) -> MockServer {
+    serve_on(TcpListener::bind("127.0.0.1:0").await.unwrap(), router)
+}
+
+pub fn serve_on(listener: TcpListener, router: Router) -> MockServer {
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let incoming = stream::unfold(listener, |listener| async move {
+        let connection = listener.accept().await.map(|(stream, _)| stream);
+        Some((connection, listener))
+    });
+
+    let handle = tokio::spawn(async move {
+        router.serve_with_incoming(incoming).await.unwrap();
+    });
+
+    MockServer { url, handle }
+}
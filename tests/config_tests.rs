@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use sova_sdk_rs::client::SovaClient;
+use sova_sdk_rs::config::{KeySource, SovaConfig};
+use sova_sdk_rs::pool::EngineEndpoint;
+use sova_sdk_rs::proto::searcher::{mempool_subscription, WorkchainShardSubscriptionV0};
+use sova_sdk_rs::tls::TlsMode;
+
+const CONFIG: &str = r#"
+default_profile = "local"
+
+[profiles.local]
+endpoints = [
+    { url = "http://[::1]:50051", tls = { mode = "plaintext" } },
+    { url = "http://[::1]:50052", tls = { mode = "plaintext" } },
+]
+key = { file = "searcher.key" }
+request_timeout_ms = 5000
+reconnect = { retry_delay_ms = 250, health_check_interval_ms = 10000 }
+
+[[profiles.local.subscriptions]]
+type = "workchain_shard"
+workchain_id = 0
+shard = "8000000000000000"
+
+[profiles.mainnet]
+network = "mainnet"
+key = { env = "SOVA_PRIVATE_KEY" }
+"#;
+
+fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+    let vars = vars
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect::<HashMap<_, _>>();
+
+    move |var| vars.get(var).cloned()
+}
+
+#[test]
+fn test_profile_from_toml() {
+    let config = SovaConfig::from_toml_str(CONFIG).unwrap();
+    let profile = config.profile_with_env(None, env(&[])).unwrap();
+
+    assert_eq!(profile.name, "local");
+    assert_eq!(
+        profile.endpoints,
+        vec![
+            EngineEndpoint::new("http://[::1]:50051", TlsMode::InsecurePlaintext),
+            EngineEndpoint::new("http://[::1]:50052", TlsMode::InsecurePlaintext),
+        ]
+    );
+    assert_eq!(profile.key, Some(KeySource::File("searcher.key".into())));
+    assert_eq!(profile.request_timeout, Some(Duration::from_secs(5)));
+    assert_eq!(profile.pool.retry_delay, Duration::from_millis(250));
+    assert_eq!(profile.pool.request_timeout, Some(Duration::from_secs(5)));
+    assert_eq!(
+        profile.pool.health_check_interval,
+        Some(Duration::from_secs(10))
+    );
+    assert_eq!(
+        profile.subscriptions,
+        vec![mempool_subscription::Subscription::WorkchainShard(
+            WorkchainShardSubscriptionV0 {
+                workchain_id: 0,
+                shard: vec![0x80, 0, 0, 0, 0, 0, 0, 0],
+            }
+        )]
+    );
+
+    let mainnet = config.profile_with_env(Some("mainnet"), env(&[])).unwrap();
+    assert_eq!(mainnet.endpoints, vec![EngineEndpoint::mainnet()]);
+}
+
+#[test]
+fn test_environment_overrides_file() {
+    let config = SovaConfig::from_toml_str(CONFIG).unwrap();
+    let profile = config
+        .profile_with_env(
+            None,
+            env(&[
+                ("SOVA_PROFILE", "mainnet"),
+                ("SOVA_URL", "https://engine.example.com:30020"),
+                ("SOVA_TLS", "webpki"),
+                ("SOVA_KEY_FILE", "/run/secrets/searcher.key"),
+                ("SOVA_REQUEST_TIMEOUT_MS", "750"),
+            ]),
+        )
+        .unwrap();
+
+    assert_eq!(profile.name, "mainnet");
+    assert_eq!(
+        profile.endpoints,
+        vec![EngineEndpoint::new(
+            "https://engine.example.com:30020",
+            TlsMode::WebPkiRoots
+        )]
+    );
+    assert_eq!(
+        profile.key,
+        Some(KeySource::File("/run/secrets/searcher.key".into()))
+    );
+    assert_eq!(profile.request_timeout, Some(Duration::from_millis(750)));
+
+    let error = config
+        .profile_with_env(None, env(&[("SOVA_RETRY_DELAY_MS", "soon")]))
+        .unwrap_err();
+    assert!(error.to_string().contains("SOVA_RETRY_DELAY_MS"));
+}
+
+#[test]
+fn test_profile_from_json_and_environment_only() {
+    let config = SovaConfig::from_json_str(
+        r#"{ "profiles": { "testnet": { "network": "testnet", "request_timeout_ms": 100 } } }"#,
+    )
+    .unwrap();
+    let profile = config.profile_with_env(None, env(&[])).unwrap();
+    assert_eq!(profile.endpoints, vec![EngineEndpoint::testnet()]);
+
+    let profile = SovaConfig::default()
+        .profile_with_env(
+            None,
+            env(&[
+                ("SOVA_URL", "http://[::1]:50051"),
+                ("SOVA_TLS", "plaintext"),
+            ]),
+        )
+        .unwrap();
+    assert_eq!(
+        profile.endpoints,
+        vec![EngineEndpoint::new(
+            "http://[::1]:50051",
+            TlsMode::InsecurePlaintext
+        )]
+    );
+
+    assert!(SovaConfig::default()
+        .profile_with_env(None, env(&[]))
+        .is_err());
+    assert!(SovaConfig::from_toml_str("[profiles.a]\nendpoint = []").is_err());
+}
+
+#[test]
+fn test_tls_override_applies_to_presets() {
+    let config = SovaConfig::from_toml_str(CONFIG).unwrap();
+    let profile = config
+        .profile_with_env(Some("mainnet"), env(&[("SOVA_TLS", "webpki")]))
+        .unwrap();
+    assert_eq!(
+        profile.endpoints,
+        vec![EngineEndpoint::new(
+            &EngineEndpoint::mainnet().url,
+            TlsMode::WebPkiRoots
+        )]
+    );
+
+    let ca_pem = "-----BEGIN CERTIFICATE-----\nMIIB\n-----END CERTIFICATE-----";
+    let profile = SovaConfig::default()
+        .profile_with_env(
+            None,
+            env(&[
+                ("SOVA_NETWORK", "testnet"),
+                ("SOVA_TLS", "custom_ca"),
+                ("SOVA_CA_BUNDLE", ca_pem),
+            ]),
+        )
+        .unwrap();
+    assert_eq!(profile.endpoints[0].tls, TlsMode::custom_ca(ca_pem));
+
+    let profile = SovaConfig::default()
+        .profile_with_env(
+            None,
+            env(&[
+                ("SOVA_URL", "https://engine.example.com:30020"),
+                ("SOVA_TLS", "custom_ca"),
+                ("SOVA_CA_BUNDLE", "/etc/sova/ca.pem"),
+            ]),
+        )
+        .unwrap();
+    assert_eq!(
+        profile.endpoints[0].tls,
+        TlsMode::ca_bundle_file("/etc/sova/ca.pem")
+    );
+
+    assert!(SovaConfig::default()
+        .profile_with_env(
+            None,
+            env(&[("SOVA_NETWORK", "testnet"), ("SOVA_TLS", "custom_ca")])
+        )
+        .is_err());
+}
+
+#[test]
+fn test_shards_must_be_eight_bytes() {
+    let config = |shard: &str| {
+        SovaConfig::from_toml_str(&format!(
+            r#"
+[profiles.local]
+endpoints = [{{ url = "http://[::1]:50051", tls = {{ mode = "plaintext" }} }}]
+
+[[profiles.local.subscriptions]]
+type = "workchain_shard"
+workchain_id = 0
+shard = "{shard}"
+"#
+        ))
+        .unwrap()
+    };
+
+    let profile = config("0x8000000000000000")
+        .profile_with_env(None, env(&[]))
+        .unwrap();
+    assert_eq!(profile.subscriptions.len(), 1);
+
+    // Too short, too long and not hex are all rejected rather than padded or truncated.
+    for shard in ["80", "800000000000000000", "zz00000000000000"] {
+        let error = config(shard).profile_with_env(None, env(&[])).unwrap_err();
+        assert!(error.to_string().contains("8 bytes"), "{error}");
+    }
+}
+
+#[test]
+fn test_client_from_config_file() {
+    let dir = std::env::temp_dir().join(format!("sova-config-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let key_path = dir.join("searcher.key");
+    std::fs::write(&key_path, hex::encode([7u8; 32])).unwrap();
+    assert_eq!(
+        KeySource::File(key_path.clone()).private_key().unwrap(),
+        [7u8; 32]
+    );
+
+    let config_path = dir.join("sova.toml");
+    std::fs::write(&config_path, CONFIG).unwrap();
+    let client = SovaClient::from_config(&config_path, Some("local")).unwrap();
+    assert_eq!(client.endpoint().url, "http://[::1]:50051");
+    assert_eq!(client.profile().endpoints.len(), 2);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,235 @@
+mod common;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::{MockSearcher, ResponseStream};
+use futures_util::{stream, StreamExt};
+use sova_sdk_rs::call_options::CallOptions;
+use sova_sdk_rs::delivery::{DeliveryConfig, DeliveryStats, OverflowPolicy};
+use sova_sdk_rs::interceptor::TokenSource;
+use sova_sdk_rs::pool::{EngineEndpoint, PoolConfig, SearcherPool};
+use sova_sdk_rs::proto::searcher::{bundle_result, BundleResult, BundleResultOk};
+use sova_sdk_rs::searcher::SovaSearcher;
+use sova_sdk_rs::subscription::EndReason;
+use sova_sdk_rs::tls::TlsMode;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+const RESULTS: usize = 10;
+
+// Bundle result "0", then "1" to "9" back to back once it has been picked up, and the stream
+// stays open.
+fn bundle_results() -> ResponseStream<BundleResult> {
+    let result = |id: usize| BundleResult {
+        id: id.to_string(),
+        result: Some(bundle_result::Result::Ok(BundleResultOk {})),
+    };
+    let rest = stream::once(tokio::time::sleep(Duration::from_millis(100)))
+        .flat_map(move |_| stream::iter((1..RESULTS).map(result).map(Ok)));
+
+    Box::pin(
+        stream::once(async move { Ok(result(0)) })
+            .chain(rest)
+            .chain(stream::pending()),
+    )
+}
+
+// Unblocks the callback when dropped, including when a wait panics.
+struct Release(Arc<AtomicBool>);
+
+impl Drop for Release {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Release);
+    }
+}
+
+async fn wait_for(condition: impl Fn() -> bool) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !condition() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("condition not reached");
+}
+
+// Subscribes with a capacity-2 buffer and a callback stuck on the first result until
+// `wait_stuck` holds, then releases it and returns the delivered ids once `wait_done` holds.
+async fn deliver(
+    searcher: &SovaSearcher,
+    policy: OverflowPolicy,
+    wait_stuck: impl Fn(&DeliveryStats) -> bool,
+    wait_done: impl Fn(&DeliveryStats) -> bool,
+) -> Result<(Vec<String>, DeliveryStats), Box<dyn std::error::Error>> {
+    let config = DeliveryConfig::new(2, policy);
+    let stats = config.stats().clone();
+    let released = Arc::new(AtomicBool::new(false));
+    let (tx, mut rx) = mpsc::unbounded_channel();
+
+    let gate = released.clone();
+    searcher
+        .subscribe_bundle_results_with_options(CallOptions::new().delivery(config), move |update| {
+            while !gate.load(Ordering::Acquire) {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            tx.send(update.bundle_id).unwrap();
+        })
+        .await?;
+
+    let release = Release(released);
+    wait_for(|| wait_stuck(&stats)).await;
+    drop(release);
+    wait_for(|| wait_done(&stats)).await;
+
+    let mut ids = Vec::new();
+    while let Ok(id) = rx.try_recv() {
+        ids.push(id);
+    }
+
+    Ok((ids, stats))
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_overflow_policies() -> Result<(), Box<dyn std::error::Error>> {
+    let server = MockSearcher::new()
+        .on_bundle_results(|_| async { Ok(bundle_results()) })
+        .serve()
+        .await;
+
+    let searcher = SovaSearcher::new(&server.url, TlsMode::InsecurePlaintext).await?;
+
+    // One result is in the callback and two are queued; the other seven are dropped.
+    let (ids, stats) = deliver(
+        &searcher,
+        OverflowPolicy::DropNewest,
+        |stats| stats.dropped_newest() == 7,
+        |stats| stats.delivered() == 3,
+    )
+    .await?;
+    assert_eq!(ids, ["0", "1", "2"]);
+    assert_eq!(stats.dropped(), 7);
+
+    let (ids, stats) = deliver(
+        &searcher,
+        OverflowPolicy::DropOldest,
+        |stats| stats.dropped_oldest() == 7,
+        |stats| stats.delivered() == 3,
+    )
+    .await?;
+    assert_eq!(ids, ["0", "8", "9"]);
+    assert_eq!(stats.dropped_newest(), 0);
+
+    let (ids, stats) = deliver(
+        &searcher,
+        OverflowPolicy::Disconnect,
+        |stats| stats.disconnects() == 1,
+        |stats| stats.delivered() == 3,
+    )
+    .await?;
+    assert_eq!(ids, ["0", "1", "2"]);
+    assert_eq!(stats.dropped(), 0);
+
+    let (ids, stats) = deliver(
+        &searcher,
+        OverflowPolicy::Block,
+        |stats| stats.blocked() >= 1,
+        |stats| stats.delivered() == RESULTS as u64,
+    )
+    .await?;
+    assert_eq!(ids.len(), RESULTS);
+    assert_eq!(stats.dropped(), 0);
+    assert_eq!(stats.disconnects(), 0);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_pool_delivery_options() -> Result<(), Box<dyn std::error::Error>> {
+    let server = MockSearcher::new()
+        .on_bundle_results(|_| async { Ok(bundle_results()) })
+        .serve()
+        .await;
+
+    let pool = SearcherPool::connect(
+        vec![EngineEndpoint::new(&server.url, TlsMode::InsecurePlaintext)],
+        TokenSource::default(),
+        PoolConfig::default(),
+    )
+    .await?;
+
+    // The pool subscription disconnects on overflow like a searcher one.
+    let config = DeliveryConfig::new(2, OverflowPolicy::Disconnect);
+    let stats = config.stats().clone();
+    let released = Arc::new(AtomicBool::new(false));
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let gate = released.clone();
+    let handle = pool.subscribe_bundle_results_with_options(
+        CallOptions::new().delivery(config),
+        move |update| {
+            while !gate.load(Ordering::Acquire) {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            tx.send(update.bundle_id).unwrap();
+        },
+    );
+
+    let release = Release(released);
+    wait_for(|| stats.disconnects() == 1).await;
+    drop(release);
+    assert_eq!(handle.join().await, EndReason::Overflow);
+    let mut ids = Vec::new();
+    while let Ok(id) = rx.try_recv() {
+        ids.push(id);
+    }
+    assert_eq!(ids, ["0", "1", "2"]);
+
+    // Cancelling the options token ends it too.
+    let token = CancellationToken::new();
+    let handle = pool.subscribe_bundle_results_with_options(
+        CallOptions::new().cancellation(token.clone()),
+        |_| {},
+    );
+    token.cancel();
+    assert_eq!(handle.join().await, EndReason::Cancelled);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_pool_join_waits_for_callbacks() -> Result<(), Box<dyn std::error::Error>> {
+    let server = MockSearcher::new()
+        .on_bundle_results(|_| async { Ok(bundle_results()) })
+        .serve()
+        .await;
+
+    let pool = SearcherPool::connect(
+        vec![EngineEndpoint::new(&server.url, TlsMode::InsecurePlaintext)],
+        TokenSource::default(),
+        PoolConfig::default(),
+    )
+    .await?;
+
+    // The callback is still busy with the first result when the subscription is cancelled.
+    let started = Arc::new(AtomicBool::new(false));
+    let finished = Arc::new(AtomicBool::new(false));
+    let (busy, done) = (started.clone(), finished.clone());
+    let handle = pool.subscribe_bundle_results_with_options(
+        CallOptions::new().delivery(DeliveryConfig::new(8, OverflowPolicy::Block)),
+        move |_| {
+            if !busy.swap(true, Ordering::AcqRel) {
+                std::thread::sleep(Duration::from_millis(200));
+                done.store(true, Ordering::Release);
+            }
+        },
+    );
+
+    wait_for(|| started.load(Ordering::Acquire)).await;
+    handle.cancel();
+    assert_eq!(handle.join().await, EndReason::Cancelled);
+    assert!(finished.load(Ordering::Acquire));
+    assert!(pool.subscriptions().is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use sova_sdk_rs::client::SovaClient;
+use sova_sdk_rs::error::SovaError;
+use sova_sdk_rs::interceptor::{AuthInterceptor, TokenSource};
+use sova_sdk_rs::proto::auth::auth_service_server::{AuthService, AuthServiceServer};
+use sova_sdk_rs::proto::auth::{
+    GenerateAuthChallengeRequest, GenerateAuthChallengeResponse, GenerateAuthTokensRequest,
+    GenerateAuthTokensResponse, RefreshAccessTokenRequest, RefreshAccessTokenResponse, Token,
+};
+use sova_sdk_rs::tls::TlsMode;
+use sova_sdk_rs::types::AccessToken;
+use tonic::service::Interceptor;
+use tonic::transport::Server;
+use tonic::{Request, Response, Status};
+
+// Issues an access token that expires in a second, and numbered refreshed tokens that last an
+// hour.
+#[derive(Default)]
+struct MockAuthService {
+    refreshes: AtomicUsize,
+}
+
+#[tonic::async_trait]
+impl AuthService for MockAuthService {
+    async fn generate_auth_challenge(
+        &self,
+        _request: Request<GenerateAuthChallengeRequest>,
+    ) -> Result<Response<GenerateAuthChallengeResponse>, Status> {
+        Ok(Response::new(GenerateAuthChallengeResponse {
+            challenge: b"challenge".to_vec(),
+        }))
+    }
+
+    async fn generate_auth_tokens(
+        &self,
+        _request: Request<GenerateAuthTokensRequest>,
+    ) -> Result<Response<GenerateAuthTokensResponse>, Status> {
+        Ok(Response::new(GenerateAuthTokensResponse {
+            access_token: Some(token("access_token", Duration::from_secs(1))),
+            refresh_token: Some(token("refresh_token", Duration::from_secs(3600))),
+        }))
+    }
+
+    async fn refresh_access_token(
+        &self,
+        request: Request<RefreshAccessTokenRequest>,
+    ) -> Result<Response<RefreshAccessTokenResponse>, Status> {
+        assert_eq!(request.into_inner().refresh_token, "refresh_token");
+        let refreshes = self.refreshes.fetch_add(1, Ordering::SeqCst) + 1;
+
+        Ok(Response::new(RefreshAccessTokenResponse {
+            access_token: Some(token(
+                &format!("refreshed_token_{refreshes}"),
+                Duration::from_secs(3600),
+            )),
+        }))
+    }
+}
+
+fn token(value: &str, lifetime: Duration) -> Token {
+    Token {
+        value: value.to_owned(),
+        expires_at_utc: Some((SystemTime::now() + lifetime).into()),
+    }
+}
+
+fn authorization(interceptor: &mut AuthInterceptor) -> Option<String> {
+    let request = interceptor.call(tonic::Request::new(())).unwrap();
+
+    request
+        .metadata()
+        .get("authorization")
+        .map(|value| value.to_str().unwrap().to_owned())
+}
+
+#[test]
+fn test_auth_interceptor_follows_token_source() {
+    let tokens = TokenSource::default();
+    let mut interceptor = AuthInterceptor::new(tokens.clone());
+
+    assert_eq!(authorization(&mut interceptor), None);
+
+    tokens.set(Some(AccessToken::new("access_token", None)));
+    assert_eq!(
+        authorization(&mut interceptor).as_deref(),
+        Some("Bearer access_token")
+    );
+
+    tokens.set(Some(AccessToken::new("new_access_token", None)));
+    assert_eq!(
+        authorization(&mut interceptor).as_deref(),
+        Some("Bearer new_access_token")
+    );
+}
+
+#[tokio::test]
+async fn test_client_refreshes_shared_token() -> Result<(), Box<dyn std::error::Error>> {
+    let service = Arc::new(MockAuthService::default());
+    let server =
+        common::serve(Server::builder().add_service(AuthServiceServer::from_arc(service.clone())))
+            .await;
+    let client = SovaClient::custom(&server.url, TlsMode::InsecurePlaintext, None);
+
+    assert!(matches!(
+        client.spawn_token_refresh(Duration::from_secs(10)),
+        Err(SovaError::AuthenticationRequired)
+    ));
+
+    let token = client.authenticate([7; 32]).await?;
+    assert_eq!(token.value(), "access_token");
+    assert!(client.auth().unwrap().refresh_token().is_some());
+
+    let token = client.refresh_access_token().await?;
+    assert_eq!(token.value(), "refreshed_token_1");
+    assert_eq!(client.access_token().unwrap().value(), "refreshed_token_1");
+
+    // The background task refreshes a token about to expire.
+    client.authenticate([7; 32]).await?;
+    let refresh = client.spawn_token_refresh(Duration::from_secs(10))?;
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while client.access_token().unwrap().value() != "refreshed_token_2" {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await?;
+    refresh.abort();
+
+    Ok(())
+}
@@ -0,0 +1,132 @@
+mod common;
+
+use std::time::{Duration, SystemTime};
+
+use common::{items_then_pending, MockSearcher};
+use sova_sdk_rs::call_options::CallOptions;
+use sova_sdk_rs::latency::{LatencyStats, ReceivedAt};
+use sova_sdk_rs::proto::dto::MempoolPacket;
+use sova_sdk_rs::proto::searcher::{mempool_subscription, WorkchainSubscriptionV0};
+use sova_sdk_rs::searcher::SovaSearcher;
+use sova_sdk_rs::subscription::SubscriptionId;
+use sova_sdk_rs::tls::TlsMode;
+use sova_sdk_rs::types::MempoolEvent;
+use tokio::sync::mpsc;
+
+#[test]
+fn test_latency_summary() {
+    let stats = LatencyStats::new(100);
+    let (first, second, other) = (
+        SubscriptionId::next(),
+        SubscriptionId::next(),
+        SubscriptionId::next(),
+    );
+    assert!(stats.summary(first).is_none());
+
+    // The first 100 samples are evicted by the window.
+    for millis in (1..=100).chain(1..=100) {
+        stats.record(first, "mempool_workchain", Duration::from_millis(millis));
+    }
+    stats.record(second, "mempool_workchain", Duration::from_millis(500));
+    stats.record(other, "mempool_addresses", Duration::from_millis(7));
+
+    let summary = stats.summary(first).unwrap();
+    assert_eq!(summary.samples, 100);
+    assert_eq!(summary.min, Duration::from_millis(1));
+    assert_eq!(summary.p50, Duration::from_millis(50));
+    assert_eq!(summary.p90, Duration::from_millis(90));
+    assert_eq!(summary.p99, Duration::from_millis(99));
+    assert_eq!(summary.max, Duration::from_millis(100));
+
+    // Subscriptions of one kind keep separate windows, and their kind sums them up.
+    let single = stats.summary(second).unwrap();
+    assert_eq!(single.p50, Duration::from_millis(500));
+    assert_eq!(single.p99, Duration::from_millis(500));
+    let kind = stats.kind_summary("mempool_workchain").unwrap();
+    assert_eq!(kind.samples, 100);
+    assert_eq!(kind.min, Duration::from_millis(2));
+    assert_eq!(kind.max, Duration::from_millis(500));
+    assert_eq!(stats.summaries().len(), 3);
+    assert_eq!(stats.kind_summaries().len(), 2);
+
+    stats.remove(second);
+    assert!(stats.summary(second).is_none());
+    assert!(stats.kind_summary("mempool_workchain").is_some());
+
+    stats.clear();
+    assert!(stats.summaries().is_empty());
+    assert!(stats.kind_summaries().is_empty());
+}
+
+#[test]
+fn test_event_latency() {
+    let received = ReceivedAt::now();
+    let mut event = MempoolEvent {
+        server_time: Some(received.wall - Duration::from_millis(20)),
+        received: Some(received),
+        ..Default::default()
+    };
+    assert_eq!(event.latency(), Some(Duration::from_millis(20)));
+    assert!(event.since_received().is_some());
+
+    // A server clock ahead of ours gives no latency rather than a bogus one.
+    event.server_time = Some(received.wall + Duration::from_millis(20));
+    assert_eq!(event.latency(), None);
+
+    event.received = None;
+    assert_eq!(event.latency(), None);
+}
+
+#[tokio::test]
+async fn test_subscription_records_latency() -> Result<(), Box<dyn std::error::Error>> {
+    // Sends one mempool packet stamped 50 ms in the past.
+    let server = MockSearcher::new()
+        .on_mempool(|_| async {
+            let packet = MempoolPacket {
+                server_ts: Some((SystemTime::now() - Duration::from_millis(50)).into()),
+                ..Default::default()
+            };
+            Ok(items_then_pending([Ok(packet)]))
+        })
+        .serve()
+        .await;
+
+    let searcher = SovaSearcher::new(&server.url, TlsMode::InsecurePlaintext).await?;
+    let workchain = || {
+        mempool_subscription::Subscription::Workchain(WorkchainSubscriptionV0 { workchain_id: 0 })
+    };
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let decoded = searcher
+        .subscribe(workchain(), move |event| tx.send(event).unwrap())
+        .await?;
+
+    let event = rx.recv().await.unwrap();
+    assert!(event.received.is_some());
+    assert!(event.latency().unwrap() >= Duration::from_millis(50));
+
+    let summary = searcher.latency_stats().summary(decoded.id()).unwrap();
+    assert_eq!(summary.samples, 1);
+    assert!(summary.min >= Duration::from_millis(50));
+
+    // Raw packets come with their receive time and get a window of their own, while their kind
+    // covers both subscriptions.
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let raw = searcher
+        .subscribe_raw_with_options(
+            workchain(),
+            CallOptions::default(),
+            move |packet, received| tx.send((packet, received)).unwrap(),
+        )
+        .await?;
+
+    let (packet, received) = rx.recv().await.unwrap();
+    let server_time = SystemTime::try_from(packet.server_ts.unwrap())?;
+    assert!(received.wall.duration_since(server_time)? >= Duration::from_millis(50));
+    let stats = searcher.latency_stats();
+    assert_ne!(decoded.id(), raw.id());
+    assert_eq!(stats.summary(decoded.id()).unwrap().samples, 1);
+    assert_eq!(stats.summary(raw.id()).unwrap().samples, 1);
+    assert_eq!(stats.kind_summary("mempool_workchain").unwrap().samples, 2);
+
+    Ok(())
+}
@@ -0,0 +1,109 @@
+#![cfg(feature = "metrics")]
+
+mod common;
+
+use common::{items_then_pending, MockSearcher};
+use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+use metrics_util::MetricKind;
+use sova_sdk_rs::proto::dto::Bundle;
+use sova_sdk_rs::proto::searcher::{bundle_result, BundleResult, BundleResultOk};
+use sova_sdk_rs::searcher::SovaSearcher;
+use sova_sdk_rs::telemetry::{
+    BUNDLES_SENT_TOTAL, BUNDLE_RESULTS_TOTAL, DECODE_FAILURES_TOTAL, RPC_DURATION_SECONDS,
+    SUBSCRIPTION_PACKETS_TOTAL,
+};
+use sova_sdk_rs::tls::TlsMode;
+use tokio::sync::mpsc;
+use tonic::Status;
+
+#[tokio::test]
+async fn test_searcher_metrics() -> Result<(), Box<dyn std::error::Error>> {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    recorder.install()?;
+
+    // Sends a result without an outcome and one included bundle result to every results
+    // subscriber, and has no tip addresses.
+    let server = MockSearcher::new()
+        .on_bundle_results(|_| async {
+            let invalid = BundleResult {
+                id: "bundle-0".to_owned(),
+                result: None,
+            };
+            let result = BundleResult {
+                id: "bundle-1".to_owned(),
+                result: Some(bundle_result::Result::Ok(BundleResultOk {})),
+            };
+            Ok(items_then_pending([Ok(invalid), Ok(result)]))
+        })
+        .on_tip_addresses(|_| async { Err(Status::unavailable("no tips")) })
+        .serve()
+        .await;
+
+    let searcher = SovaSearcher::new(&server.url, TlsMode::InsecurePlaintext).await?;
+    searcher.send_bundle(Bundle::default()).await?;
+    assert!(searcher.get_tip_addresses().await.is_err());
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    searcher
+        .subscribe_bundle_results(move |update| tx.send(update).unwrap())
+        .await?;
+    rx.recv().await.unwrap();
+
+    let metrics = snapshotter
+        .snapshot()
+        .into_vec()
+        .into_iter()
+        .map(|(key, _, _, value)| {
+            let (kind, key) = key.into_parts();
+            let mut labels = key
+                .labels()
+                .map(|label| format!("{}={}", label.key(), label.value()))
+                .collect::<Vec<_>>();
+            labels.sort();
+
+            (kind, key.name().to_owned(), labels, value)
+        })
+        .collect::<Vec<_>>();
+
+    let counter = |name: &str, labels: &[&str]| {
+        metrics
+            .iter()
+            .find_map(|(kind, metric, metric_labels, value)| match (kind, value) {
+                (MetricKind::Counter, DebugValue::Counter(count))
+                    if metric == name && metric_labels == labels =>
+                {
+                    Some(count.to_owned())
+                }
+                _ => None,
+            })
+    };
+    let histogram_exists = |labels: &[&str]| {
+        metrics.iter().any(|(kind, metric, metric_labels, _)| {
+            *kind == MetricKind::Histogram
+                && metric == RPC_DURATION_SECONDS
+                && metric_labels == labels
+        })
+    };
+
+    assert_eq!(counter(BUNDLES_SENT_TOTAL, &["status=ok"]), Some(1));
+    assert_eq!(
+        counter(BUNDLE_RESULTS_TOTAL, &["outcome=included"]),
+        Some(1)
+    );
+    assert_eq!(
+        counter(SUBSCRIPTION_PACKETS_TOTAL, &["subscription=bundle_results"]),
+        Some(2)
+    );
+    assert_eq!(
+        counter(DECODE_FAILURES_TOTAL, &["subscription=bundle_results"]),
+        Some(1)
+    );
+    assert!(histogram_exists(&["method=send_bundle", "status=ok"]));
+    assert!(histogram_exists(&[
+        "method=get_tip_addresses",
+        "status=error"
+    ]));
+
+    Ok(())
+}
@@ -0,0 +1,292 @@
+mod common;
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use common::{MockSearcher, ResponseStream};
+use futures_util::{stream, StreamExt};
+use sova_sdk_rs::proto::dto::{Bundle, ExternalMessage};
+use sova_sdk_rs::proto::searcher::{BundleResult, SendBundleResponse};
+use sova_sdk_rs::resubmit::{ResubmitPolicy, StopReason, TipSchedule};
+use sova_sdk_rs::searcher::SovaSearcher;
+use sova_sdk_rs::tls::TlsMode;
+use sova_sdk_rs::types::{BundleOutcome, BundleUpdate};
+use tokio::sync::{broadcast, Notify};
+
+// Answers each accepted bundle with the next scripted outcome after its delay, or no result once
+// the script runs out, and records every bundle.
+#[derive(Clone)]
+struct Script {
+    outcomes: Arc<Mutex<VecDeque<(BundleOutcome, Duration)>>>,
+    sent: Arc<Mutex<Vec<Bundle>>>,
+    results: broadcast::Sender<BundleResult>,
+}
+
+impl Script {
+    fn new() -> Self {
+        Self {
+            outcomes: Arc::default(),
+            sent: Arc::default(),
+            results: broadcast::channel(16).0,
+        }
+    }
+
+    fn searcher(&self) -> MockSearcher {
+        let results = self.results.clone();
+        let script = self.clone();
+
+        MockSearcher::new()
+            .on_bundle_results(move |_| {
+                let results: ResponseStream<BundleResult> = Box::pin(stream::unfold(
+                    results.subscribe(),
+                    |mut results| async move {
+                        let result = results.recv().await.ok()?;
+                        Some((Ok(result), results))
+                    },
+                ));
+                async { Ok(results) }
+            })
+            .on_send_bundle(move |request| {
+                let id = script.accept(request.into_inner());
+                async { Ok(SendBundleResponse { id }) }
+            })
+    }
+
+    fn accept(&self, bundle: Bundle) -> String {
+        let mut sent = self.sent.lock().unwrap();
+        sent.push(bundle);
+        let id = format!("bundle-{}", sent.len());
+
+        if let Some((outcome, delay)) = self.outcomes.lock().unwrap().pop_front() {
+            let results = self.results.clone();
+            let update = BundleUpdate {
+                bundle_id: id.clone(),
+                outcome,
+            };
+            tokio::spawn(async move {
+                tokio::time::sleep(delay).await;
+                results.send(update.into()).unwrap();
+            });
+        }
+
+        id
+    }
+
+    fn script(&self, outcomes: impl IntoIterator<Item = BundleOutcome>) {
+        let delay = Duration::from_millis(20);
+        self.script_delayed(outcomes.into_iter().map(|outcome| (outcome, delay)));
+    }
+
+    fn script_delayed(&self, outcomes: impl IntoIterator<Item = (BundleOutcome, Duration)>) {
+        *self.outcomes.lock().unwrap() = outcomes.into_iter().collect();
+        self.sent.lock().unwrap().clear();
+    }
+
+    // The tip encoded in each bundle sent.
+    fn tips(&self) -> Vec<u64> {
+        self.sent
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|bundle| u64::from_be_bytes(bundle.message[0].data.as_slice().try_into().unwrap()))
+            .collect()
+    }
+
+    fn all_expiring(&self) -> bool {
+        let sent = self.sent.lock().unwrap();
+        sent.iter().all(|bundle| bundle.expiration_ns.is_some())
+    }
+}
+
+fn bundle(tip: u64) -> Bundle {
+    Bundle {
+        message: vec![ExternalMessage {
+            data: tip.to_be_bytes().to_vec(),
+        }],
+        ..Default::default()
+    }
+}
+
+fn in_a_minute() -> SystemTime {
+    SystemTime::now() + Duration::from_secs(60)
+}
+
+#[test]
+fn test_tip_schedules() {
+    assert_eq!(TipSchedule::fixed(5).tip(3), 5);
+    assert_eq!(TipSchedule::linear(100, 50).tip(1), 100);
+    assert_eq!(TipSchedule::linear(100, 50).tip(3), 200);
+    assert_eq!(TipSchedule::multiply(100, 2.0).tip(4), 800);
+    assert_eq!(TipSchedule::multiply(100, 2.0).max(300).tip(4), 300);
+
+    let steps = TipSchedule::steps(vec![10, 20, 40]);
+    assert_eq!(
+        [1, 2, 3, 4].map(|attempt| steps.tip(attempt)),
+        [10, 20, 40, 40]
+    );
+}
+
+#[tokio::test]
+async fn test_resubmission() -> Result<(), Box<dyn std::error::Error>> {
+    let service = Script::new();
+    let server = service.searcher().serve().await;
+
+    let searcher = SovaSearcher::new(&server.url, TlsMode::InsecurePlaintext).await?;
+    let policy = ResubmitPolicy::new(5, TipSchedule::linear(100, 50)).valid_until(in_a_minute());
+
+    // Lost auctions and interruptions are retried with a higher tip until the bundle lands.
+    service.script([
+        BundleOutcome::HigherTipWon(String::new()),
+        BundleOutcome::Expired(String::new()),
+        BundleOutcome::Included,
+    ]);
+    let mut reported = Vec::new();
+    let resubmission = searcher
+        .send_bundle_with_resubmission(&policy, bundle, |attempt| reported.push(attempt.clone()))
+        .await?;
+    assert!(resubmission.is_included());
+    assert_eq!(resubmission.attempts, reported);
+    assert_eq!(service.tips(), [100, 150, 200]);
+    assert!(service.all_expiring());
+    assert_eq!(resubmission.attempts[2].bundle_id, "bundle-3");
+    assert_eq!(
+        resubmission.attempts[0].outcome,
+        Some(BundleOutcome::HigherTipWon(String::new()))
+    );
+
+    // A conflicting bundle winning stops resubmission.
+    service.script([
+        BundleOutcome::HigherTipWon(String::new()),
+        BundleOutcome::ConflictingBundle(String::new()),
+    ]);
+    let resubmission = searcher
+        .send_bundle_with_resubmission(&policy, bundle, |_| {})
+        .await?;
+    assert_eq!(resubmission.stop, StopReason::ConflictingSuccess);
+    assert_eq!(resubmission.attempts.len(), 2);
+
+    // A failed simulation stops resubmission unless the policy retries it.
+    service.script([BundleOutcome::SimulationFailed(String::new())]);
+    let resubmission = searcher
+        .send_bundle_with_resubmission(&policy, bundle, |_| {})
+        .await?;
+    assert_eq!(resubmission.stop, StopReason::SimulationFailed);
+    assert_eq!(service.tips(), [100]);
+
+    service.script([
+        BundleOutcome::SimulationFailed(String::new()),
+        BundleOutcome::Included,
+    ]);
+    let retrying = policy.clone().retry_simulation_failures(true);
+    let resubmission = searcher
+        .send_bundle_with_resubmission(&retrying, bundle, |_| {})
+        .await?;
+    assert!(resubmission.is_included());
+    assert_eq!(service.tips(), [100, 150]);
+
+    // Attempts without a result count towards the limit.
+    service.script([BundleOutcome::HigherTipWon(String::new())]);
+    let short = ResubmitPolicy::new(2, TipSchedule::fixed(7))
+        .valid_until(in_a_minute())
+        .result_timeout(Duration::from_millis(200));
+    let resubmission = searcher
+        .send_bundle_with_resubmission(&short, bundle, |_| {})
+        .await?;
+    assert_eq!(resubmission.stop, StopReason::AttemptsExhausted);
+    assert_eq!(resubmission.attempts[1].outcome, None);
+    assert_eq!(service.tips(), [7, 7]);
+
+    // The first attempt turns out included while the second one is pending.
+    service.script_delayed([(BundleOutcome::Included, Duration::from_millis(300))]);
+    let short = ResubmitPolicy::new(3, TipSchedule::fixed(7))
+        .valid_until(in_a_minute())
+        .result_timeout(Duration::from_millis(200));
+    let mut reported = Vec::new();
+    let resubmission = searcher
+        .send_bundle_with_resubmission(&short, bundle, |attempt| reported.push(attempt.clone()))
+        .await?;
+    assert!(resubmission.is_included());
+    assert_eq!(service.tips(), [7, 7]);
+    assert_eq!(
+        resubmission.attempts[0].outcome,
+        Some(BundleOutcome::Included)
+    );
+    assert_eq!(resubmission.attempts[1].outcome, None);
+    assert_eq!(
+        reported
+            .iter()
+            .map(|attempt| (attempt.number, attempt.outcome.clone()))
+            .collect::<Vec<_>>(),
+        [(1, None), (1, Some(BundleOutcome::Included)), (2, None)]
+    );
+
+    // Without an expiry, a result timeout too long to add to the current time waits for the
+    // result.
+    service.script([BundleOutcome::Included]);
+    let patient = ResubmitPolicy::new(1, TipSchedule::fixed(7)).result_timeout(Duration::MAX);
+    let resubmission = searcher
+        .send_bundle_with_resubmission(&patient, bundle, |_| {})
+        .await?;
+    assert!(resubmission.is_included());
+    assert!(!service.all_expiring());
+
+    // Nothing is sent once the messages are no longer valid.
+    service.script([]);
+    let expired = ResubmitPolicy::new(3, TipSchedule::fixed(7))
+        .valid_until(SystemTime::now() - Duration::from_secs(1));
+    let resubmission = searcher
+        .send_bundle_with_resubmission(&expired, bundle, |_| {})
+        .await?;
+    assert_eq!(resubmission.stop, StopReason::Expired);
+    assert!(resubmission.attempts.is_empty());
+    assert!(service.tips().is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_resubmission_stops_without_results() -> Result<(), Box<dyn std::error::Error>> {
+    // The results stream ends as soon as the first bundle is sent.
+    let sent = Arc::new(AtomicUsize::new(0));
+    let closing = Arc::new(Notify::new());
+    let (counter, notify) = (sent.clone(), closing.clone());
+    let server = MockSearcher::new()
+        .on_bundle_results(move |_| {
+            let closing = closing.clone();
+            let results: ResponseStream<BundleResult> = Box::pin(
+                stream::once(async move { closing.notified().await })
+                    .filter_map(|()| async { None }),
+            );
+            async { Ok(results) }
+        })
+        .on_send_bundle(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            notify.notify_one();
+            async {
+                Ok(SendBundleResponse {
+                    id: "bundle-1".to_owned(),
+                })
+            }
+        })
+        .serve()
+        .await;
+
+    let searcher = SovaSearcher::new(&server.url, TlsMode::InsecurePlaintext).await?;
+    let policy = ResubmitPolicy::new(3, TipSchedule::linear(100, 50))
+        .valid_until(in_a_minute())
+        .result_timeout(Duration::from_secs(10));
+    let resubmission = tokio::time::timeout(
+        Duration::from_secs(5),
+        searcher.send_bundle_with_resubmission(&policy, bundle, |_| {}),
+    )
+    .await??;
+
+    assert_eq!(resubmission.stop, StopReason::ResultsUnavailable);
+    assert_eq!(resubmission.attempts.len(), 1);
+    assert_eq!(resubmission.attempts[0].outcome, None);
+    assert_eq!(sent.load(Ordering::SeqCst), 1);
+
+    Ok(())
+}
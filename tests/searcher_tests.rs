@@ -0,0 +1,379 @@
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::{items_then_pending, MockSearcher};
+use futures_util::future::join_all;
+use sova_sdk_rs::auth::SovaAuth;
+use sova_sdk_rs::block_engine::SovaBlockEngine;
+use sova_sdk_rs::call_options::CallOptions;
+use sova_sdk_rs::client::SovaClient;
+use sova_sdk_rs::config::Profile;
+use sova_sdk_rs::interceptor::TokenSource;
+use sova_sdk_rs::pool::{EngineEndpoint, PoolConfig, SearcherPool};
+use sova_sdk_rs::proto::dto::Bundle;
+use sova_sdk_rs::proto::searcher::{
+    mempool_subscription, AddressSubscriptionV0, GetTipAddressesResponse, SendBundleResponse,
+};
+use sova_sdk_rs::searcher::SovaSearcher;
+use sova_sdk_rs::subscription::EndReason;
+use sova_sdk_rs::tls::TlsMode;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use tonic::Status;
+
+// Records the `x-request-id` of every `send_bundle` call.
+fn recording_searcher(request_ids: mpsc::UnboundedSender<Option<String>>) -> MockSearcher {
+    MockSearcher::new().on_send_bundle(move |request| {
+        let request_id = request
+            .metadata()
+            .get("x-request-id")
+            .map(|value| value.to_str().unwrap().to_owned());
+        request_ids.send(request_id).unwrap();
+        async { Ok(SendBundleResponse::default()) }
+    })
+}
+
+#[tokio::test]
+async fn test_call_options() -> Result<(), Box<dyn std::error::Error>> {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    // `get_tip_addresses` never answers requests carrying `x-hang`.
+    let server = recording_searcher(tx)
+        .on_tip_addresses(|request| {
+            let hang = request.metadata().contains_key("x-hang");
+            async move {
+                if hang {
+                    std::future::pending::<()>().await;
+                }
+                Ok(GetTipAddressesResponse::default())
+            }
+        })
+        .serve()
+        .await;
+
+    let searcher = SovaSearcher::new(&server.url, TlsMode::InsecurePlaintext).await?;
+
+    // Custom metadata reaches the server.
+    searcher
+        .send_bundle_with_options(
+            Bundle::default(),
+            CallOptions::new().request_id("support-ticket-42"),
+        )
+        .await?;
+    assert_eq!(
+        rx.recv().await.unwrap().as_deref(),
+        Some("support-ticket-42")
+    );
+
+    // The deadline is enforced on a call the server never answers.
+    let error = searcher
+        .get_tip_addresses_with_options(
+            CallOptions::new()
+                .metadata("x-hang", "1")
+                .timeout(Duration::from_millis(100)),
+        )
+        .await
+        .unwrap_err();
+    let status = error.downcast_ref::<Status>().unwrap();
+    assert_eq!(status.code(), tonic::Code::DeadlineExceeded);
+
+    // Cancelling the token aborts the call.
+    let token = CancellationToken::new();
+    let cancel = token.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        cancel.cancel();
+    });
+    let error = searcher
+        .get_tip_addresses_with_options(
+            CallOptions::new()
+                .metadata("x-hang", "1")
+                .cancellation(token),
+        )
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("cancelled"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_searcher_pool_routes_around_dead_endpoints() -> Result<(), Box<dyn std::error::Error>>
+{
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let server = recording_searcher(tx).serve().await;
+
+    // Nothing listens on the dead endpoint's port until it comes back below.
+    let address = TcpListener::bind("127.0.0.1:0").await?.local_addr()?;
+    let live = EngineEndpoint::new(&server.url, TlsMode::InsecurePlaintext);
+    let dead = EngineEndpoint::new(&format!("http://{address}"), TlsMode::InsecurePlaintext);
+    let pool = SearcherPool::connect(
+        vec![dead.clone(), live.clone()],
+        TokenSource::default(),
+        PoolConfig::default(),
+    )
+    .await?;
+
+    let status = pool.status();
+    assert!(!status[0].healthy);
+    assert!(status[1].healthy);
+    assert!(status[1].rtt.is_some());
+    assert_eq!(pool.fastest(), Some(&live));
+
+    pool.send_bundle(Bundle::default()).await?;
+    assert!(rx.recv().await.is_some());
+
+    let results = pool.broadcast_bundle(Bundle::default()).await;
+    assert!(results[0].is_err());
+    assert!(results[1].is_ok());
+
+    // An endpoint that was down at connect time joins the pool once a probe reaches it.
+    let (tx, mut revived_rx) = mpsc::unbounded_channel();
+    let _revived = recording_searcher(tx).serve_on(TcpListener::bind(address).await?);
+    pool.probe().await;
+    assert!(pool.status()[0].healthy);
+    let results = pool.broadcast_bundle(Bundle::default()).await;
+    assert!(results.iter().all(Result::is_ok));
+    assert!(revived_rx.recv().await.is_some());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_searcher_pool_keeps_rejecting_endpoints_healthy(
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Both endpoints reject every bundle.
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let rejecting = || {
+        let tx = tx.clone();
+        MockSearcher::new().on_send_bundle(move |_| {
+            tx.send(()).unwrap();
+            async { Err(Status::invalid_argument("bundle has no messages")) }
+        })
+    };
+    let first = rejecting().serve().await;
+    let second = rejecting().serve().await;
+
+    let pool = SearcherPool::connect(
+        vec![
+            EngineEndpoint::new(&first.url, TlsMode::InsecurePlaintext),
+            EngineEndpoint::new(&second.url, TlsMode::InsecurePlaintext),
+        ],
+        TokenSource::default(),
+        PoolConfig::default(),
+    )
+    .await?;
+
+    // The rejection is returned as is, without trying the other endpoint.
+    let error = pool.send_bundle(Bundle::default()).await.unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<Status>().map(Status::code),
+        Some(tonic::Code::InvalidArgument)
+    );
+    rx.recv().await.unwrap();
+    assert!(rx.try_recv().is_err());
+    assert!(pool.status().iter().all(|status| status.healthy));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_searcher_pool_surfaces_auth_errors() -> Result<(), Box<dyn std::error::Error>> {
+    // The engine rejects the token on every call.
+    let server = MockSearcher::new()
+        .on_tip_addresses(|_| async { Err(Status::unauthenticated("token expired")) })
+        .on_send_bundle(|_| async { Err(Status::unauthenticated("token expired")) })
+        .serve()
+        .await;
+
+    let pool = SearcherPool::connect(
+        vec![EngineEndpoint::new(&server.url, TlsMode::InsecurePlaintext)],
+        TokenSource::default(),
+        PoolConfig::default(),
+    )
+    .await?;
+
+    // The endpoint answered the probe, so it stays healthy and the rejection is returned.
+    assert!(pool.status()[0].healthy);
+    let error = pool.send_bundle(Bundle::default()).await.unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<Status>().map(Status::code),
+        Some(tonic::Code::Unauthenticated)
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_searcher_pool_recovers_without_health_checks(
+) -> Result<(), Box<dyn std::error::Error>> {
+    // The first bundle finds the engine unavailable, later ones are accepted.
+    let sends = Arc::new(AtomicUsize::new(0));
+    let counter = sends.clone();
+    let server = MockSearcher::new()
+        .on_send_bundle(move |_| {
+            let first = counter.fetch_add(1, Ordering::SeqCst) == 0;
+            async move {
+                if first {
+                    return Err(Status::unavailable("restarting"));
+                }
+                Ok(SendBundleResponse::default())
+            }
+        })
+        .serve()
+        .await;
+
+    let pool = SearcherPool::connect(
+        vec![EngineEndpoint::new(&server.url, TlsMode::InsecurePlaintext)],
+        TokenSource::default(),
+        PoolConfig::default(),
+    )
+    .await?;
+
+    assert!(pool.send_bundle(Bundle::default()).await.is_err());
+    assert!(!pool.status()[0].healthy);
+
+    // With every endpoint unhealthy, the next send probes them again instead of giving up.
+    pool.send_bundle(Bundle::default()).await?;
+    assert!(pool.status()[0].healthy);
+    assert_eq!(sends.load(Ordering::SeqCst), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_searcher_pool_subscriptions_end_on_rejection(
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Bundle results are refused outright, mempool streams fail after opening.
+    let server = MockSearcher::new()
+        .on_bundle_results(|_| async { Err(Status::unauthenticated("token expired")) })
+        .on_mempool(|_| async {
+            Ok(items_then_pending([Err(Status::permission_denied(
+                "subscription not allowed",
+            ))]))
+        })
+        .serve()
+        .await;
+
+    let pool = SearcherPool::connect(
+        vec![EngineEndpoint::new(&server.url, TlsMode::InsecurePlaintext)],
+        TokenSource::default(),
+        PoolConfig::default(),
+    )
+    .await?;
+
+    let handle = pool.subscribe_bundle_results(|_| {});
+    let reason = tokio::time::timeout(Duration::from_secs(5), handle.join()).await?;
+    assert_eq!(
+        reason,
+        EndReason::Failed {
+            code: tonic::Code::Unauthenticated,
+            message: "token expired".to_owned(),
+        }
+    );
+
+    let subscription = mempool_subscription::Subscription::Addresses(AddressSubscriptionV0 {
+        address: vec!["0:00".to_owned()],
+    });
+    let handle = pool.subscribe(subscription, |_| {});
+    let reason = tokio::time::timeout(Duration::from_secs(5), handle.join()).await?;
+    assert!(matches!(
+        reason,
+        EndReason::Failed {
+            code: tonic::Code::PermissionDenied,
+            ..
+        }
+    ));
+
+    // Neither rejection counts against the endpoint.
+    assert!(pool.status()[0].healthy);
+
+    Ok(())
+}
+
+fn assert_shareable<T: Clone + Send + Sync + 'static>() {}
+
+#[tokio::test]
+async fn test_concurrent_sends() -> Result<(), Box<dyn std::error::Error>> {
+    assert_shareable::<SovaSearcher>();
+    assert_shareable::<SovaBlockEngine>();
+    assert_shareable::<SovaAuth>();
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let server = recording_searcher(tx).serve().await;
+
+    let searcher = SovaSearcher::new(&server.url, TlsMode::InsecurePlaintext).await?;
+
+    // Clones send over the same connection from separate tasks, without a lock.
+    let sends = (0..16).map(|index| {
+        let searcher = searcher.clone();
+        tokio::spawn(async move {
+            searcher
+                .send_bundle_with_options(
+                    Bundle::default(),
+                    CallOptions::new().request_id(index.to_string()),
+                )
+                .await
+                .map(|_| ())
+                .map_err(|error| error.to_string())
+        })
+    });
+    for send in join_all(sends).await {
+        send??;
+    }
+
+    let mut request_ids = Vec::new();
+    for _ in 0..16 {
+        request_ids.push(rx.recv().await.unwrap().unwrap().parse::<usize>()?);
+    }
+    request_ids.sort();
+    assert_eq!(request_ids, (0..16).collect::<Vec<_>>());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_client_applies_profile_settings() -> Result<(), Box<dyn std::error::Error>> {
+    // `send_bundle` never answers.
+    let server = MockSearcher::new()
+        .on_send_bundle(|_| std::future::pending())
+        .serve()
+        .await;
+
+    let mut profile = Profile::new(
+        "local",
+        EngineEndpoint::new(&server.url, TlsMode::InsecurePlaintext),
+    );
+    profile.request_timeout = Some(Duration::from_millis(100));
+    profile.pool.request_timeout = Some(Duration::from_millis(100));
+    profile.pool.health_check_interval = Some(Duration::from_millis(50));
+    let client = SovaClient::from_profile(profile, None)?;
+
+    let searcher = client.searcher().await?;
+    let send = searcher.send_bundle(Bundle::default());
+    assert!(tokio::time::timeout(Duration::from_secs(5), send)
+        .await?
+        .is_err());
+
+    // The extra endpoint is down at connect time and picked up by the health checks.
+    let address = TcpListener::bind("127.0.0.1:0").await?.local_addr()?;
+    let extra = EngineEndpoint::new(&format!("http://{address}"), TlsMode::InsecurePlaintext);
+    let pool = client.searcher_pool(vec![extra]).await?;
+    let send = pool.send_bundle(Bundle::default());
+    assert!(tokio::time::timeout(Duration::from_secs(5), send)
+        .await?
+        .is_err());
+
+    let _revived = MockSearcher::new().serve_on(TcpListener::bind(address).await?);
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !pool.status()[1].healthy {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,88 @@
+#![cfg(feature = "serde")]
+
+use prost_types::Timestamp;
+use serde_json::json;
+use sova_sdk_rs::proto::auth::Token;
+use sova_sdk_rs::proto::searcher::{
+    bundle_result, bundle_result_auction_failed, BundleResult, BundleResultAuctionFailed,
+};
+use sova_sdk_rs::searcher::{Bundle, ExternalMessage};
+
+#[test]
+fn test_bundle_bytes_as_base64() {
+    let bundle = Bundle {
+        message: vec![ExternalMessage {
+            data: vec![0xb5, 0xee, 0x9c, 0x72],
+        }],
+        expiration_ns: None,
+    };
+
+    let value = serde_json::to_value(&bundle).unwrap();
+    assert_eq!(value["message"][0]["data"], json!("te6ccg=="));
+
+    let decoded: Bundle = serde_json::from_value(value).unwrap();
+    assert_eq!(decoded, bundle);
+}
+
+#[test]
+fn test_token_expiry_as_rfc3339() {
+    let token = Token {
+        value: "access".to_owned(),
+        expires_at_utc: Some(Timestamp {
+            seconds: 1_700_000_000,
+            nanos: 0,
+        }),
+    };
+
+    let value = serde_json::to_value(&token).unwrap();
+    assert_eq!(value["expires_at_utc"], json!("2023-11-14T22:13:20Z"));
+
+    let decoded: Token = serde_json::from_value(value).unwrap();
+    assert_eq!(decoded.expires_at_utc, token.expires_at_utc);
+}
+
+#[test]
+fn test_token_value_not_serialized() {
+    let token = Token {
+        value: "secret-access-token".to_owned(),
+        expires_at_utc: None,
+    };
+
+    let json = serde_json::to_string(&token).unwrap();
+    assert!(!json.contains("secret-access-token"));
+
+    // The round trip is one-way: the token comes back without its value.
+    let round_trip: Token = serde_json::from_str(&json).unwrap();
+    assert_eq!(round_trip.value, "");
+    assert_ne!(round_trip, token);
+
+    let decoded: Token = serde_json::from_value(json!({ "value": "secret-access-token" })).unwrap();
+    assert_eq!(decoded.value, "secret-access-token");
+}
+
+#[test]
+fn test_bundle_result_oneof() {
+    let result = BundleResult {
+        id: "bundle-1".to_owned(),
+        result: Some(bundle_result::Result::AuctionFailed(
+            BundleResultAuctionFailed {
+                reason: Some(bundle_result_auction_failed::Reason::HigherTipWon(
+                    "bundle-2".to_owned(),
+                )),
+            },
+        )),
+    };
+
+    let value = serde_json::to_value(&result).unwrap();
+    assert_eq!(
+        value["result"],
+        json!({ "auction_failed": { "reason": { "higher_tip_won": "bundle-2" } } })
+    );
+
+    let decoded: BundleResult = serde_json::from_value(value).unwrap();
+    assert_eq!(decoded, result);
+
+    // Missing fields take their proto defaults.
+    let decoded: BundleResult = serde_json::from_value(json!({ "id": "bundle-3" })).unwrap();
+    assert_eq!(decoded.result, None);
+}
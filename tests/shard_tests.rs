@@ -0,0 +1,264 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use common::{items_then_pending, MockSearcher};
+use sova_sdk_rs::error::SovaError;
+use sova_sdk_rs::proto::dto::{MempoolExternalMessage, MempoolPacket};
+use sova_sdk_rs::proto::searcher::{
+    mempool_subscription, InternalMessageBodyOpcodeSubscriptionV0, WorkchainShardSubscriptionV0,
+};
+use sova_sdk_rs::searcher::SovaSearcher;
+use sova_sdk_rs::shard::{message_destination, Address, ShardCover};
+use sova_sdk_rs::tls::TlsMode;
+use sova_sdk_rs::types::{MempoolMessage, ShardId};
+use tonic::Status;
+
+fn address(workchain: i32, first: u8) -> Address {
+    let mut account = [0x11; 32];
+    account[0] = first;
+    Address::new(workchain, account)
+}
+
+// An external message to `destination` without source, state init or body, as a single cell.
+fn external_message(destination: &Address) -> Vec<u8> {
+    let mut bits = vec![true, false, false, false, true, false, false];
+    for byte in std::iter::once(destination.workchain as u8).chain(destination.account) {
+        bits.extend((0..8).rev().map(|bit| (byte >> bit) & 1 == 1));
+    }
+    bits.extend([false; 6]);
+
+    let d2 = bits.len().div_ceil(8) * 2 - usize::from(bits.len() % 8 != 0);
+    if bits.len() % 8 != 0 {
+        // Completion tag.
+        bits.push(true);
+    }
+    let data: Vec<u8> = bits
+        .chunks(8)
+        .map(|chunk| {
+            (0..8).fold(0, |byte, bit| {
+                (byte << 1) | u8::from(chunk.get(bit).copied().unwrap_or(false))
+            })
+        })
+        .collect();
+
+    let mut boc = vec![0xb5, 0xee, 0x9c, 0x72, 0x01, 0x01, 1, 1, 0];
+    boc.extend([2 + data.len() as u8, 0, 0, d2 as u8]);
+    boc.extend(data);
+    boc
+}
+
+#[test]
+fn test_shard_ids() {
+    let account = address(0, 0b1011_0000).account;
+
+    assert_eq!(ShardId::for_account(&account, 0), ShardId::ROOT);
+    assert_eq!(
+        ShardId::for_account(&account, 2),
+        ShardId(0xa000_0000_0000_0000)
+    );
+    assert_eq!(ShardId::for_account(&account, 2).depth(), 2);
+    assert_eq!(ShardId::ROOT.depth(), 0);
+    assert_eq!(ShardId::for_account(&account, 200).depth(), 60);
+
+    assert!(ShardId::ROOT.contains(&account));
+    assert!(ShardId(0xa000_0000_0000_0000).contains(&account));
+    assert!(!ShardId(0xe000_0000_0000_0000).contains(&account));
+    assert!(!ShardId(0x6000_0000_0000_0000).contains(&account));
+}
+
+#[test]
+fn test_addresses() -> Result<(), SovaError> {
+    let zero = Address::new(0, [0; 32]);
+    assert_eq!(
+        "0:0000000000000000000000000000000000000000000000000000000000000000".parse::<Address>()?,
+        zero
+    );
+    assert_eq!(
+        "EQAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAM9c".parse::<Address>()?,
+        zero
+    );
+
+    let masterchain = Address::new(-1, [0xab; 32]);
+    assert_eq!(
+        "Uf-rq6urq6urq6urq6urq6urq6urq6urq6urq6urq6urq2ep".parse::<Address>()?,
+        masterchain
+    );
+    assert_eq!(
+        "Uf+rq6urq6urq6urq6urq6urq6urq6urq6urq6urq6urq2ep".parse::<Address>()?,
+        masterchain
+    );
+    assert_eq!(masterchain.to_string().parse::<Address>()?, masterchain);
+    // The masterchain is not split.
+    assert_eq!(masterchain.shard(4), ShardId::ROOT);
+
+    for invalid in [
+        "",
+        "0:00",
+        "zero:0000000000000000000000000000000000000000000000000000000000000000",
+        "EQAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAM9d",
+    ] {
+        assert!(matches!(
+            invalid.parse::<Address>(),
+            Err(SovaError::InvalidAddress(_))
+        ));
+    }
+
+    let destination = address(-1, 0x42);
+    assert_eq!(
+        message_destination(&external_message(&destination))?,
+        destination
+    );
+    assert!(matches!(
+        message_destination(&[0xb5, 0xee, 0x9c, 0x72, 1, 1, 1, 1, 0, 2, 0, 0, 0]),
+        Err(SovaError::InvalidBoc(_))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_shard_cover() {
+    let watched = [
+        address(0, 0x10),
+        address(0, 0x20),
+        address(0, 0x90),
+        address(-1, 0x10),
+    ];
+    let cover = ShardCover::new(watched, 1);
+
+    assert_eq!(
+        cover.shards().collect::<Vec<_>>(),
+        [
+            (-1, ShardId::ROOT),
+            (0, ShardId(0x4000_0000_0000_0000)),
+            (0, ShardId(0xc000_0000_0000_0000)),
+        ]
+    );
+    assert_eq!(ShardCover::new(watched, 0).shards().count(), 2);
+    assert_eq!(ShardCover::new(watched, 4).shards().count(), 4);
+
+    assert_eq!(
+        cover.internal_msg_body_opcode_subscriptions(7)[1],
+        mempool_subscription::Subscription::InternalMessageBodyOpcode(
+            InternalMessageBodyOpcodeSubscriptionV0 {
+                workchain_id: 0,
+                shard: Some(ShardId(0x4000_0000_0000_0000).to_bytes()),
+                opcode: 7,
+            }
+        )
+    );
+    assert_eq!(
+        cover.workchain_shard_subscriptions()[0],
+        mempool_subscription::Subscription::WorkchainShard(WorkchainShardSubscriptionV0 {
+            workchain_id: -1,
+            shard: ShardId::ROOT.to_bytes(),
+        })
+    );
+
+    let message = |address: &Address| MempoolMessage {
+        hash: [0; 32],
+        workchain_id: address.workchain,
+        shard: ShardId::ROOT,
+        data: external_message(address),
+        std_smc_address: true,
+    };
+    assert!(cover.matches(&message(&watched[0])));
+    assert!(!cover.matches(&message(&address(0, 0x30))));
+    assert!(!cover.matches(&MempoolMessage {
+        data: vec![1, 2, 3],
+        ..message(&watched[0])
+    }));
+}
+
+#[tokio::test]
+async fn test_subscribe_by_shard_cover() -> Result<(), Box<dyn std::error::Error>> {
+    let watched = [address(0, 0x10), address(0, 0x90)];
+    // The last address shares a shard with a watched one but is not watched itself.
+    let addresses = [watched[0], watched[1], address(0, 0x30)];
+    let shards = Arc::new(Mutex::new(Vec::new()));
+    let requested = shards.clone();
+    // Sends one packet per workchain-shard subscription with the messages of every known
+    // address in that shard.
+    let server = MockSearcher::new()
+        .on_mempool(move |request| {
+            let requested = requested.clone();
+            async move {
+                let Some(mempool_subscription::Subscription::WorkchainShard(subscription)) =
+                    request.into_inner().subscription
+                else {
+                    return Err(Status::invalid_argument("expected a workchain shard"));
+                };
+                let shard = ShardId::from_bytes(&subscription.shard).unwrap();
+                requested.lock().unwrap().push(shard);
+
+                let packet = MempoolPacket {
+                    external_messages: addresses
+                        .iter()
+                        .filter(|address| shard.contains(&address.account))
+                        .map(|address| MempoolExternalMessage {
+                            hash: vec![0; 32],
+                            workchain_id: address.workchain,
+                            shard: shard.to_bytes(),
+                            data: external_message(address),
+                            ..Default::default()
+                        })
+                        .collect(),
+                    ..Default::default()
+                };
+                Ok(items_then_pending([Ok(packet)]))
+            }
+        })
+        .serve()
+        .await;
+
+    let searcher = SovaSearcher::new(&server.url, TlsMode::InsecurePlaintext).await?;
+    let cover = ShardCover::new(watched, 1);
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let sink = received.clone();
+    let handles = searcher
+        .subscribe_by_shard_cover(
+            &cover,
+            cover.workchain_shard_subscriptions(),
+            move |event| {
+                let mut sink = sink.lock().unwrap();
+                sink.extend(event.messages.iter().map(|m| m.destination().unwrap()));
+            },
+        )
+        .await?;
+    assert_eq!(handles.len(), 2);
+
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let mut received = received.lock().unwrap().clone();
+    received.sort();
+    assert_eq!(received, watched);
+    assert_eq!(
+        *shards.lock().unwrap(),
+        [
+            ShardId(0x4000_0000_0000_0000),
+            ShardId(0xc000_0000_0000_0000)
+        ]
+    );
+
+    // A subscription that fails to open cancels the others.
+    let error = searcher
+        .subscribe_by_shard_cover(
+            &cover,
+            vec![
+                cover.workchain_shard_subscriptions()[0].clone(),
+                cover.internal_msg_body_opcode_subscriptions(7)[0].clone(),
+            ],
+            |_| {},
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<Status>().map(Status::code),
+        Some(tonic::Code::InvalidArgument)
+    );
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(searcher.subscriptions().len(), 2);
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+#![cfg(unix)]
+
+use ed25519_dalek::{Signer, SigningKey, Verifier};
+use sova_sdk_rs::signer::{ChallengeSigner, UnixSocketSigner};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixListener;
+use tokio::task::JoinHandle;
+
+// Minimal signing daemon speaking the `UnixSocketSigner` framing.
+fn spawn_signing_daemon(listener: UnixListener, key: SigningKey) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let op = stream.read_u8().await.unwrap();
+            let len = stream.read_u32().await.unwrap() as usize;
+            let mut payload = vec![0u8; len];
+            stream.read_exact(&mut payload).await.unwrap();
+
+            let (status, response) = match op {
+                0x01 => (0u8, key.verifying_key().to_bytes().to_vec()),
+                0x02 if payload == b"refuse" => (1u8, b"policy violation".to_vec()),
+                0x02 => (0u8, key.sign(&payload).to_bytes().to_vec()),
+                _ => (1u8, b"unknown op".to_vec()),
+            };
+
+            stream.write_u8(status).await.unwrap();
+            stream.write_u32(response.len() as u32).await.unwrap();
+            stream.write_all(&response).await.unwrap();
+        }
+    })
+}
+
+#[tokio::test]
+async fn test_unix_socket_signer() -> Result<(), Box<dyn std::error::Error>> {
+    let key = SigningKey::from_bytes(&[7u8; 32]);
+
+    let path = std::env::temp_dir().join(format!("sova-signer-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let daemon_handle = spawn_signing_daemon(UnixListener::bind(&path)?, key.clone());
+
+    let signer = UnixSocketSigner::new(&path);
+
+    let public_key = signer.public_key().await.map_err(|e| e.to_string())?;
+    assert_eq!(public_key, key.verifying_key());
+
+    let signature = signer
+        .sign_challenge(b"test_challenge")
+        .await
+        .map_err(|e| e.to_string())?;
+    assert!(public_key.verify(b"test_challenge", &signature).is_ok());
+
+    let error = signer.sign_challenge(b"refuse").await.unwrap_err();
+    assert!(error.to_string().contains("policy violation"));
+
+    daemon_handle.abort();
+    std::fs::remove_file(&path)?;
+
+    Ok(())
+}
@@ -0,0 +1,205 @@
+mod common;
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::{items_then_pending, MockSearcher, ResponseStream};
+use futures_util::stream;
+use sova_sdk_rs::bundle_stats::BundleStats;
+use sova_sdk_rs::error::SovaError;
+use sova_sdk_rs::proto::dto::{Bundle, MempoolPacket};
+use sova_sdk_rs::proto::searcher::{
+    bundle_result, mempool_subscription, BundleResult, BundleResultOk, GetTipAddressesResponse,
+    SendBundleResponse, WorkchainSubscriptionV0,
+};
+use sova_sdk_rs::searcher::SovaSearcher;
+use sova_sdk_rs::strategy::{Strategy, StrategyContext, StrategyRunner};
+use sova_sdk_rs::subscription::EndReason;
+use sova_sdk_rs::tls::TlsMode;
+use sova_sdk_rs::types::{BundleUpdate, MempoolEvent};
+use tokio::sync::{broadcast, mpsc};
+
+// Sends one mempool packet per subscription, numbers accepted bundles and reports each one as
+// included shortly after accepting it, or already before answering the send if `early`.
+fn mock_searcher(early: bool) -> MockSearcher {
+    let sent = Arc::new(AtomicUsize::new(0));
+    let (results, _) = broadcast::channel::<String>(16);
+    let subscribed = results.clone();
+
+    MockSearcher::new()
+        .on_bundle_results(move |_| {
+            let results: ResponseStream<BundleResult> = Box::pin(stream::unfold(
+                subscribed.subscribe(),
+                |mut results| async move {
+                    let id = results.recv().await.ok()?;
+                    let result = BundleResult {
+                        id,
+                        result: Some(bundle_result::Result::Ok(BundleResultOk {})),
+                    };
+                    Some((Ok(result), results))
+                },
+            ));
+            async { Ok(results) }
+        })
+        .on_mempool(|_| async { Ok(items_then_pending([Ok(MempoolPacket::default())])) })
+        .on_send_bundle(move |_| {
+            let id = format!("bundle-{}", sent.fetch_add(1, Ordering::SeqCst));
+            let results = results.clone();
+            let result_id = id.clone();
+            if early {
+                results.send(result_id).unwrap();
+            } else {
+                tokio::spawn(async move {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    results.send(result_id).unwrap();
+                });
+            }
+            async move {
+                if early {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                }
+                Ok(SendBundleResponse { id })
+            }
+        })
+        .on_tip_addresses(|_| async {
+            Ok(GetTipAddressesResponse {
+                address: vec!["tip-address".to_owned()],
+            })
+        })
+}
+
+// Sends a bundle for every packet and reports `name:bundle id` for each result it receives.
+struct Backrun {
+    name: &'static str,
+    results: mpsc::UnboundedSender<String>,
+    stopped: Arc<AtomicBool>,
+}
+
+#[tonic::async_trait]
+impl Strategy for Backrun {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn filters(&self) -> Vec<mempool_subscription::Subscription> {
+        vec![mempool_subscription::Subscription::Workchain(
+            WorkchainSubscriptionV0 { workchain_id: 0 },
+        )]
+    }
+
+    async fn on_packet(&self, ctx: &StrategyContext, _event: MempoolEvent) {
+        assert!(ctx.tips().contains("tip-address"));
+        if self.name == "first" {
+            ctx.send_bundle_with_tip(Bundle::default(), 1_000)
+                .await
+                .unwrap();
+        } else {
+            ctx.send_bundle(Bundle::default()).await.unwrap();
+        }
+    }
+
+    async fn on_bundle_result(&self, _ctx: &StrategyContext, update: BundleUpdate) {
+        assert!(update.outcome.is_included());
+        self.results
+            .send(format!("{}:{}", self.name, update.bundle_id))
+            .unwrap();
+    }
+
+    async fn on_shutdown(&self, ctx: &StrategyContext) {
+        assert!(ctx.is_shutting_down());
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+}
+
+#[tokio::test]
+async fn test_strategy_runner() -> Result<(), Box<dyn std::error::Error>> {
+    for early in [false, true] {
+        run_backruns(early).await?;
+    }
+
+    Ok(())
+}
+
+async fn run_backruns(early: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let server = mock_searcher(early).serve().await;
+
+    let searcher = SovaSearcher::new(&server.url, TlsMode::InsecurePlaintext).await?;
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let stopped = Arc::new(AtomicBool::new(false));
+    let stats = BundleStats::default();
+    let runner = StrategyRunner::new(searcher)
+        .bundle_stats(stats.clone())
+        .strategy(Backrun {
+            name: "first",
+            results: tx.clone(),
+            stopped: stopped.clone(),
+        })
+        .strategy(Backrun {
+            name: "second",
+            results: tx,
+            stopped: stopped.clone(),
+        });
+    let shutdown = runner.shutdown_token();
+
+    let (result, mut results) = tokio::join!(runner.run(), async move {
+        let results = vec![rx.recv().await.unwrap(), rx.recv().await.unwrap()];
+        shutdown.cancel();
+        results
+    });
+    result?;
+
+    // Each strategy gets the result of its own bundle back.
+    results.sort();
+    assert!(results[0].starts_with("first:bundle-"));
+    assert!(results[1].starts_with("second:bundle-"));
+    assert_ne!(results[0][6..], results[1][7..]);
+    assert!(stopped.load(Ordering::SeqCst));
+
+    let snapshot = stats.snapshot(Duration::from_secs(60));
+    assert_eq!(snapshot.outcomes.included, 2);
+    assert_eq!(snapshot.tags["first"].included, 1);
+    assert_eq!(snapshot.tags["second"].included, 1);
+    // Only the first strategy reports the tip it paid.
+    assert_eq!(snapshot.tips.included, 1);
+    assert_eq!(snapshot.tips.average_included_tip, Some(1_000));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_strategy_runner_stops_when_a_subscription_ends(
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Mempool streams end right after their first packet.
+    let server = mock_searcher(false)
+        .on_mempool(|_| async {
+            let packets: ResponseStream<MempoolPacket> =
+                Box::pin(stream::iter([Ok(MempoolPacket::default())]));
+            Ok(packets)
+        })
+        .serve()
+        .await;
+
+    let searcher = SovaSearcher::new(&server.url, TlsMode::InsecurePlaintext).await?;
+    let (tx, _rx) = mpsc::unbounded_channel();
+    let stopped = Arc::new(AtomicBool::new(false));
+    let runner = StrategyRunner::new(searcher).strategy(Backrun {
+        name: "first",
+        results: tx,
+        stopped: stopped.clone(),
+    });
+
+    let error = tokio::time::timeout(Duration::from_secs(5), runner.run())
+        .await?
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<SovaError>(),
+        Some(SovaError::SubscriptionEnded {
+            kind: "mempool_workchain",
+            reason: EndReason::ServerClosed,
+        })
+    ));
+    assert!(stopped.load(Ordering::SeqCst));
+
+    Ok(())
+}
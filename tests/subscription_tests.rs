@@ -0,0 +1,111 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{MockSearcher, ResponseStream};
+use futures_util::{stream, StreamExt};
+use sova_sdk_rs::client::SovaClient;
+use sova_sdk_rs::delivery::Concurrency;
+use sova_sdk_rs::error::SovaError;
+use sova_sdk_rs::proto::dto::MempoolPacket;
+use sova_sdk_rs::proto::searcher::{mempool_subscription, WorkchainSubscriptionV0};
+use sova_sdk_rs::subscription::EndReason;
+use sova_sdk_rs::tls::TlsMode;
+use tokio::sync::mpsc;
+use tonic::Status;
+
+fn workchain(workchain_id: i32) -> mempool_subscription::Subscription {
+    mempool_subscription::Subscription::Workchain(WorkchainSubscriptionV0 { workchain_id })
+}
+
+#[tokio::test]
+async fn test_subscription_handles() -> Result<(), Box<dyn std::error::Error>> {
+    // Fails every bundle results stream. Mempool streams send one packet, then end for
+    // workchain 0 and stay open otherwise.
+    let server = MockSearcher::new()
+        .on_bundle_results(|_| async {
+            let failed: ResponseStream<_> =
+                Box::pin(stream::iter([Err(Status::unavailable("going away"))]));
+            Ok(failed)
+        })
+        .on_mempool(|request| {
+            let packet = stream::iter([Ok(MempoolPacket::default())]);
+            let packets: ResponseStream<_> =
+                if request.into_inner().subscription == Some(workchain(0)) {
+                    Box::pin(packet)
+                } else {
+                    Box::pin(packet.chain(stream::pending()))
+                };
+            async { Ok(packets) }
+        })
+        .serve()
+        .await;
+
+    let client = SovaClient::custom(&server.url, TlsMode::InsecurePlaintext, None);
+    let searcher = client.searcher().await?;
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let closed = searcher
+        .subscribe(workchain(0), move |event| tx.send(event).unwrap())
+        .await?;
+    assert_eq!(closed.kind(), "mempool_workchain");
+    assert_eq!(closed.join().await, EndReason::ServerClosed);
+    assert!(!closed.is_alive());
+    assert!(rx.recv().await.is_some());
+
+    let failed = searcher.subscribe_bundle_results(|_| {}).await?;
+    assert_eq!(
+        failed.join().await,
+        EndReason::Failed {
+            code: tonic::Code::Unavailable,
+            message: "going away".to_owned(),
+        }
+    );
+
+    let open = searcher.subscribe(workchain(1), |_| {}).await?;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(open.is_alive());
+    assert_eq!(searcher.subscriptions().len(), 1);
+    open.cancel();
+    assert_eq!(open.join().await, EndReason::Cancelled);
+    assert!(searcher.subscriptions().is_empty());
+
+    // Shutdown stops subscriptions of every searcher created by the client.
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let first = searcher.subscribe(workchain(1), |_| {}).await?;
+    let second = client
+        .searcher()
+        .await?
+        .subscribe_async(workchain(2), Concurrency::Bounded(2), move |_| {
+            let tx = tx.clone();
+            async move {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                tx.send(()).unwrap();
+            }
+        })
+        .await?;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    client.shutdown(Duration::from_secs(5)).await?;
+    assert_eq!(first.join().await, EndReason::Cancelled);
+    assert_eq!(second.join().await, EndReason::Cancelled);
+    // The running callback finished before shutdown returned.
+    assert!(rx.try_recv().is_ok());
+
+    // A callback that outlives the deadline makes shutdown fail.
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let stuck = searcher
+        .subscribe_async(workchain(1), Concurrency::Sequential, move |_| {
+            tx.send(()).unwrap();
+            tokio::time::sleep(Duration::from_secs(60))
+        })
+        .await?;
+    rx.recv().await.unwrap();
+    let error = client
+        .shutdown(Duration::from_millis(100))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, SovaError::ShutdownTimedOut(1)));
+    assert!(stuck.is_alive());
+
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use sova_sdk_rs::resubmit::Attempt;
+use sova_sdk_rs::tip_advisor::{Observation, TipAdvisor};
+use sova_sdk_rs::types::BundleOutcome;
+
+fn won(tip: u64) -> Observation {
+    Observation::new(Some(tip), BundleOutcome::Included)
+}
+
+fn outbid(tip: u64, winning_tip: &str) -> Observation {
+    Observation::new(
+        Some(tip),
+        BundleOutcome::HigherTipWon(winning_tip.to_owned()),
+    )
+}
+
+#[test]
+fn test_winning_bids() {
+    assert_eq!(won(100).winning_bid(), Some(100));
+    assert_eq!(outbid(100, "250").winning_bid(), Some(250));
+    // Without a reported winning tip the lost tip is a lower bound.
+    assert_eq!(outbid(100, "").winning_bid(), Some(101));
+    assert_eq!(
+        Observation::new(Some(100), BundleOutcome::Expired(String::new())).winning_bid(),
+        None
+    );
+    assert_eq!(
+        Observation::new(None, BundleOutcome::Included).winning_bid(),
+        None
+    );
+}
+
+#[test]
+fn test_suggestions() {
+    let advisor = TipAdvisor::new(10).floor(50).ceiling(900);
+    assert_eq!(advisor.suggest(0.9), 50);
+
+    for tip in [100, 200, 300, 400] {
+        advisor.observe(&won(tip));
+    }
+    advisor.observe(&outbid(100, "1000"));
+    advisor.observe(&Observation::new(
+        Some(10),
+        BundleOutcome::SimulationFailed(String::new()),
+    ));
+    assert_eq!(advisor.samples(), 5);
+
+    assert_eq!(advisor.suggest(0.0), 100);
+    assert_eq!(advisor.suggest(0.5), 300);
+    assert_eq!(advisor.suggest(0.8), 400);
+    // 1000 is above the ceiling.
+    assert_eq!(advisor.suggest(1.0), 900);
+
+    let schedule = advisor.schedule(&[0.5, 0.8]);
+    assert_eq!(
+        [1, 2, 3].map(|attempt| schedule.tip(attempt)),
+        [300, 400, 400]
+    );
+
+    // Only the last `window` bids are kept.
+    let small = TipAdvisor::new(2);
+    for tip in [1000, 10, 20] {
+        small.observe(&won(tip));
+    }
+    assert_eq!(small.suggest(1.0), 20);
+
+    advisor.observe_attempt(&Attempt {
+        number: 1,
+        tip: 700,
+        bundle_id: "bundle".to_owned(),
+        outcome: Some(BundleOutcome::Included),
+    });
+    assert_eq!(advisor.samples(), 6);
+}
+
+#[test]
+fn test_backtest() {
+    let recorded = [100, 120, 100, 120, 100, 120, 100, 120]
+        .map(won)
+        .into_iter()
+        .chain([outbid(50, "500")])
+        .collect::<Vec<_>>();
+
+    let advisor = TipAdvisor::default().floor(100);
+    let backtest = advisor.backtest(&recorded, 1.0);
+    assert_eq!(backtest.auctions, 9);
+    // Only the second bid and the final 500 are above every bid seen before them.
+    assert_eq!(backtest.won, 7);
+    assert_eq!(backtest.win_rate, Some(7.0 / 9.0));
+    assert!(backtest.average_tip.unwrap() >= 100);
+
+    // Bidding the lowest recent winner only wins the auctions at 100.
+    let cautious = advisor.backtest(&recorded, 0.0);
+    assert_eq!(cautious.won, 4);
+    assert_eq!(cautious.average_tip, Some(100));
+
+    // Backtesting leaves the live model untouched.
+    assert_eq!(advisor.samples(), 0);
+}
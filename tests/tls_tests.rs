@@ -0,0 +1,78 @@
+use sova_sdk_rs::searcher::SovaSearcher;
+use sova_sdk_rs::tls::{spki_sha256, TlsMode};
+
+#[tokio::test]
+async fn test_plaintext_requires_explicit_http_url() {
+    let error = SovaSearcher::new("https://[::1]:50056", TlsMode::InsecurePlaintext)
+        .await
+        .err()
+        .unwrap();
+    assert!(error
+        .to_string()
+        .contains("plaintext requires an http:// URL"));
+}
+
+#[tokio::test]
+async fn test_tls_never_falls_back_to_plaintext() {
+    for tls in [TlsMode::SystemRoots, TlsMode::WebPkiRoots] {
+        let error = SovaSearcher::new("http://[::1]:50056", tls)
+            .await
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("TLS requires an https:// URL"));
+    }
+}
+
+#[tokio::test]
+async fn test_custom_ca_rejects_empty_bundle() {
+    let error = SovaSearcher::new("https://[::1]:50056", TlsMode::custom_ca(""))
+        .await
+        .err()
+        .unwrap();
+    assert!(error.to_string().contains("no certificates in CA bundle"));
+}
+
+#[test]
+fn test_lets_encrypt_bundle_contains_isrg_root_x1() {
+    let TlsMode::CustomCa { ca_pem } = TlsMode::lets_encrypt() else {
+        panic!("expected a custom CA bundle");
+    };
+    let pins = rustls_pemfile::certs(&mut ca_pem.as_bytes())
+        .map(|cert| spki_sha256(&cert.unwrap()).unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(pins.len(), 2);
+    assert!(pins.contains(&"C5+lpZ7tcVwmwQIMcRtPbsQtWLABXhQzejna0wHFr8M=".to_owned()));
+}
+
+#[test]
+fn test_ca_bundle_from_env() {
+    std::env::set_var("SOVA_TEST_CA_BUNDLE_PATH", "/etc/sova/ca.pem");
+    assert_eq!(
+        TlsMode::from_env("SOVA_TEST_CA_BUNDLE_PATH"),
+        Some(TlsMode::ca_bundle_file("/etc/sova/ca.pem"))
+    );
+
+    let TlsMode::CustomCa { ca_pem } = TlsMode::lets_encrypt() else {
+        panic!("expected a custom CA bundle");
+    };
+    std::env::set_var("SOVA_TEST_CA_BUNDLE_PEM", &ca_pem);
+    assert_eq!(
+        TlsMode::from_env("SOVA_TEST_CA_BUNDLE_PEM"),
+        Some(TlsMode::custom_ca(ca_pem.trim()))
+    );
+
+    assert_eq!(TlsMode::from_env("SOVA_TEST_CA_BUNDLE_UNSET"), None);
+}
+
+#[tokio::test]
+async fn test_missing_ca_bundle_file() {
+    let error = SovaSearcher::new(
+        "https://[::1]:50056",
+        TlsMode::ca_bundle_file("/nonexistent/sova-ca.pem"),
+    )
+    .await
+    .err()
+    .unwrap();
+    assert!(error.to_string().contains("reading CA bundle"));
+}
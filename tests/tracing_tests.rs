@@ -0,0 +1,106 @@
+mod common;
+
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use sova_sdk_rs::auth::SovaAuth;
+use sova_sdk_rs::proto::auth::auth_service_server::{AuthService, AuthServiceServer};
+use sova_sdk_rs::proto::auth::{
+    GenerateAuthChallengeRequest, GenerateAuthChallengeResponse, GenerateAuthTokensRequest,
+    GenerateAuthTokensResponse, RefreshAccessTokenRequest, RefreshAccessTokenResponse, Token,
+};
+use sova_sdk_rs::tls::TlsMode;
+use tonic::transport::Server;
+use tonic::{Request, Response, Status};
+use tracing::Level;
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::layer::SubscriberExt;
+
+const PRIVATE_KEY: [u8; 32] = [7; 32];
+
+struct MockAuthService;
+
+#[tonic::async_trait]
+impl AuthService for MockAuthService {
+    async fn generate_auth_challenge(
+        &self,
+        _request: Request<GenerateAuthChallengeRequest>,
+    ) -> Result<Response<GenerateAuthChallengeResponse>, Status> {
+        Ok(Response::new(GenerateAuthChallengeResponse {
+            challenge: b"challenge".to_vec(),
+        }))
+    }
+
+    async fn generate_auth_tokens(
+        &self,
+        _request: Request<GenerateAuthTokensRequest>,
+    ) -> Result<Response<GenerateAuthTokensResponse>, Status> {
+        Ok(Response::new(GenerateAuthTokensResponse {
+            access_token: Some(Token {
+                value: "secret-access-token".to_owned(),
+                expires_at_utc: None,
+            }),
+            refresh_token: Some(Token {
+                value: "secret-refresh-token".to_owned(),
+                expires_at_utc: None,
+            }),
+        }))
+    }
+
+    async fn refresh_access_token(
+        &self,
+        _request: Request<RefreshAccessTokenRequest>,
+    ) -> Result<Response<RefreshAccessTokenResponse>, Status> {
+        Err(Status::unauthenticated("refresh token revoked"))
+    }
+}
+
+#[derive(Clone, Default)]
+struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+impl io::Write for Buffer {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_auth_spans_hide_secrets() -> Result<(), Box<dyn std::error::Error>> {
+    let buffer = Buffer::default();
+    let writer = buffer.clone();
+    let subscriber = tracing_subscriber::registry()
+        .with(Targets::new().with_target("sova_sdk_rs", Level::DEBUG))
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_writer(move || writer.clone()),
+        );
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let server =
+        common::serve(Server::builder().add_service(AuthServiceServer::new(MockAuthService))).await;
+
+    let auth = SovaAuth::new(&server.url, TlsMode::InsecurePlaintext, &PRIVATE_KEY).await?;
+    auth.authenticate().await?;
+    assert!(auth.refresh_access_token().await.is_err());
+
+    let public_key = auth.info().public_key.unwrap().to_hex();
+    let logs = String::from_utf8(buffer.0.lock().unwrap().clone())?;
+
+    assert!(logs.contains(&format!("connect{{endpoint=\"{}\"", server.url)));
+    assert!(logs.contains(&format!("authenticate{{public_key=\"{public_key}\"}}")));
+    assert!(logs.contains("authenticated"));
+    assert!(logs.contains("refresh_access_token"));
+    assert!(logs.contains("token refresh failed"));
+    assert!(logs.contains("refresh token revoked"));
+    assert!(!logs.contains("MetadataMap"));
+    assert!(!logs.contains("secret-access-token"));
+    assert!(!logs.contains("secret-refresh-token"));
+    assert!(!logs.contains(&hex::encode(PRIVATE_KEY)));
+
+    Ok(())
+}
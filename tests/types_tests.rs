@@ -0,0 +1,146 @@
+use std::time::{Duration, SystemTime};
+
+use sova_sdk_rs::proto::auth::Token;
+use sova_sdk_rs::proto::dto::{MempoolExternalMessage, MempoolPacket};
+use sova_sdk_rs::proto::searcher::{
+    bundle_result, BundleResult, BundleResultInterrupted, GetTipAddressesResponse,
+};
+use sova_sdk_rs::types::{
+    AccessToken, BundleOutcome, BundleUpdate, MempoolEvent, MempoolMessage, ShardId, TipAccounts,
+};
+
+#[test]
+fn test_access_token_round_trip() {
+    let expires_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    let token = AccessToken::new("access", Some(expires_at));
+
+    let proto = Token::from(token.clone());
+    assert_eq!(proto.value, "access");
+    assert_eq!(AccessToken::from(proto), token);
+    assert_eq!(token.info().expires_at(), Some(expires_at));
+}
+
+#[test]
+fn test_tip_accounts() {
+    let tips = TipAccounts::from(GetTipAddressesResponse {
+        address: vec!["EQA".to_owned(), "EQB".to_owned()],
+    });
+
+    assert_eq!(tips.len(), 2);
+    assert!(tips.contains("EQB"));
+    assert!(!tips.contains("EQC"));
+    assert_eq!(GetTipAddressesResponse::from(tips).address.len(), 2);
+}
+
+#[test]
+fn test_bundle_update_conversions() {
+    for outcome in [
+        BundleOutcome::Included,
+        BundleOutcome::HigherTipWon("bundle-2".to_owned()),
+        BundleOutcome::ConflictingBundle("bundle-3".to_owned()),
+        BundleOutcome::Expired(String::new()),
+        BundleOutcome::SimulationFailed("exit code 37".to_owned()),
+    ] {
+        let update = BundleUpdate {
+            bundle_id: "bundle-1".to_owned(),
+            outcome,
+        };
+
+        let proto = BundleResult::from(update.clone());
+        assert_eq!(BundleUpdate::try_from(proto).unwrap(), update);
+    }
+
+    assert_eq!(
+        BundleOutcome::SimulationFailed("exit code 37".to_owned()).to_string(),
+        "simulation_failed (exit code 37)"
+    );
+
+    let missing_outcome = BundleResult {
+        id: "bundle-1".to_owned(),
+        result: None,
+    };
+    assert!(BundleUpdate::try_from(missing_outcome).is_err());
+
+    let missing_reason = BundleResult {
+        id: "bundle-1".to_owned(),
+        result: Some(bundle_result::Result::Interrupted(
+            BundleResultInterrupted { reason: None },
+        )),
+    };
+    assert!(BundleUpdate::try_from(missing_reason).is_err());
+}
+
+#[test]
+fn test_winning_tip() {
+    let outbid = |detail: &str| BundleOutcome::HigherTipWon(detail.to_owned()).winning_tip();
+
+    assert_eq!(outbid("1500000"), Some(1_500_000));
+
+    // Anything but a bare integer is not taken as a tip.
+    for detail in [
+        "",
+        " 200",
+        "+200",
+        "200 nanotons",
+        "1.5",
+        "bundle-2",
+        "99999999999999999999",
+    ] {
+        assert_eq!(outbid(detail), None, "{detail:?}");
+    }
+    assert_eq!(
+        BundleOutcome::ConflictingBundle("200".to_owned()).winning_tip(),
+        None
+    );
+}
+
+#[test]
+fn test_mempool_event_conversions() {
+    let event = MempoolEvent {
+        server_time: Some(SystemTime::UNIX_EPOCH + Duration::from_millis(1_700_000_000_123)),
+        expires_at: None,
+        messages: vec![MempoolMessage {
+            hash: [0xab; 32],
+            workchain_id: 0,
+            shard: ShardId::ROOT,
+            data: vec![0xb5, 0xee, 0x9c, 0x72],
+            std_smc_address: true,
+        }],
+        received: None,
+    };
+
+    let packet = MempoolPacket::from(event.clone());
+    assert_eq!(
+        packet.external_messages[0].shard,
+        vec![0x80, 0, 0, 0, 0, 0, 0, 0]
+    );
+    assert_eq!(MempoolEvent::try_from(packet).unwrap(), event);
+
+    let short_hash = MempoolPacket {
+        external_messages: vec![MempoolExternalMessage {
+            hash: vec![0xab; 31],
+            shard: ShardId::ROOT.to_bytes(),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    assert!(MempoolEvent::try_from(short_hash.clone()).is_err());
+
+    // Lossy decoding skips only the bad message.
+    let mut mixed = MempoolPacket::from(event.clone());
+    mixed.external_messages.extend(short_hash.external_messages);
+    let mut invalid = Vec::new();
+    let decoded = MempoolEvent::from_packet_lossy(mixed, |error| invalid.push(error));
+    assert_eq!(decoded, event);
+    assert_eq!(invalid.len(), 1);
+}
+
+#[test]
+fn test_shard_id() {
+    assert_eq!(
+        ShardId::from_bytes(&ShardId::ROOT.to_bytes()),
+        Some(ShardId::ROOT)
+    );
+    assert_eq!(ShardId::from_bytes(&[0x80]), None);
+    assert_eq!(ShardId::ROOT.to_string(), "8000000000000000");
+}